@@ -0,0 +1,38 @@
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use my_chip8::{Chip8, FrameBuffer};
+
+#[path = "../src/workloads.rs"]
+mod workloads;
+
+const FOREGROUND: [u8; 4] = [0xff, 0xff, 0xff, 0xff];
+const BACKGROUND: [u8; 4] = [0x00, 0x00, 0x00, 0xff];
+
+// Converting the display to RGBA texels, as the SDL renderer does for every
+// frame it draws, a pixel at a time and through `write_rgba`.
+fn rgba_conversion(c: &mut Criterion) {
+    let (_, rom) = workloads::WORKLOADS[0];
+    let mut chip8 = Chip8::with_seed(0);
+    chip8.load_rom_bytes(rom);
+    chip8.run_cycles(10_000).unwrap();
+    let display = *chip8.frame_buffer();
+    let mut texels = vec![0; display.width() * display.height() * 4];
+
+    let mut group = c.benchmark_group("rgba");
+    group.throughput(Throughput::Elements(
+        (display.width() * display.height()) as u64,
+    ));
+    group.bench_function("per_pixel", |b| b.iter(|| per_pixel(&display, &mut texels)));
+    group.bench_function("write_rgba", |b| {
+        b.iter(|| display.write_rgba(FOREGROUND, BACKGROUND, &mut texels))
+    });
+    group.finish();
+}
+
+fn per_pixel(display: &FrameBuffer, texels: &mut [u8]) {
+    for (texel, pixel) in texels.chunks_exact_mut(4).zip(display.to_bytes()) {
+        texel.copy_from_slice(if pixel == 1 { &FOREGROUND } else { &BACKGROUND });
+    }
+}
+
+criterion_group!(benches, rgba_conversion);
+criterion_main!(benches);
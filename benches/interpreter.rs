@@ -0,0 +1,27 @@
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use my_chip8::Chip8;
+
+#[path = "../src/workloads.rs"]
+mod workloads;
+
+const CYCLES: u64 = 10_000;
+
+fn instructions_per_second(c: &mut Criterion) {
+    let mut group = c.benchmark_group("instructions");
+    group.throughput(Throughput::Elements(CYCLES));
+    for (name, rom) in workloads::WORKLOADS {
+        let mut chip8 = Chip8::with_seed(0);
+        chip8.load_rom_bytes(rom);
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                for _ in 0..CYCLES {
+                    chip8.run_cycle().unwrap();
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, instructions_per_second);
+criterion_main!(benches);
@@ -0,0 +1,36 @@
+#![no_main]
+
+use libfuzzer_sys::arbitrary::{self, Arbitrary};
+use libfuzzer_sys::fuzz_target;
+use my_chip8::Chip8;
+
+const START_ADDR: usize = 0x200;
+const CYCLES_PER_STEP: usize = 64;
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+    seed: u64,
+    rom: Vec<u8>,
+    // The keypad state for each step of CYCLES_PER_STEP cycles.
+    keys: Vec<u16>,
+}
+
+fuzz_target!(|input: Input| {
+    let mut chip8 = Chip8::with_seed(input.seed);
+    let max_len = chip8.memory().len() - START_ADDR;
+    chip8.load_rom_bytes(&input.rom[..input.rom.len().min(max_len)]);
+
+    for &keys in &input.keys {
+        chip8.set_keys(keys);
+        for _ in 0..CYCLES_PER_STEP {
+            // PC may leave memory by jumping or skipping past the end, but then
+            // the next fetch has to fail instead of reading out of bounds.
+            let pc_in_memory = (chip8.pc() as usize) < chip8.memory().len() - 1;
+            match chip8.run_cycle() {
+                Ok(()) => assert!(pc_in_memory, "fetched from PC {:#x}", chip8.pc()),
+                Err(_) => return,
+            }
+            assert!(chip8.state().sp <= 16);
+        }
+    }
+});
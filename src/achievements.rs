@@ -0,0 +1,151 @@
+use crate::cheats::Comparison;
+use crate::config;
+use crate::replay;
+use crate::RunArgs;
+use my_chip8::{Chip8, MEM_SIZE};
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process;
+
+const ACHIEVEMENTS_DIR: &str = "achievements";
+
+// A .ach file next to the ROM lists one achievement per line: a hex address,
+// a comparison, and a hex value the byte there must reach, followed by the
+// achievement's name:
+//
+//   3a0 >= 32  Fifty points
+//   3a2 == 03  Reached the third level
+//
+// Which ones have been unlocked is kept in the config directory as
+// `achievements/<rom hash>.txt`, one name per line, so they stay unlocked.
+pub struct Achievements {
+    achievements: Vec<Achievement>,
+    unlocked: HashSet<String>,
+    path: Option<PathBuf>,
+}
+
+struct Achievement {
+    addr: u16,
+    comparison: Comparison,
+    value: u8,
+    name: String,
+}
+
+impl Achievements {
+    // Loads the achievements for the ROM being run, if it has a .ach file.
+    pub fn from_args(args: &RunArgs, rom: &[u8]) -> Option<Achievements> {
+        Achievements::for_rom(Path::new(args.rom.as_deref()?), rom).unwrap_or_else(|e| {
+            eprintln!("{e}");
+            process::exit(1);
+        })
+    }
+
+    pub fn for_rom(path: &Path, rom: &[u8]) -> Result<Option<Achievements>, String> {
+        let path = path.with_extension("ach");
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let mut achievements = Achievements::load(&path)?;
+        achievements.path = config::config_dir().map(|dir| {
+            dir.join(ACHIEVEMENTS_DIR)
+                .join(format!("{:016x}.txt", replay::rom_hash(rom)))
+        });
+        if let Some(path) = &achievements.path {
+            match fs::read_to_string(path) {
+                Ok(text) => achievements.unlocked = text.lines().map(str::to_string).collect(),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+                Err(e) => return Err(format!("Failed to read {}: {e}", path.display())),
+            }
+        }
+        println!(
+            "Loaded {} achievements from {}, {} unlocked",
+            achievements.achievements.len(),
+            path.display(),
+            achievements.unlocked_count()
+        );
+        Ok(Some(achievements))
+    }
+
+    pub fn load(path: &Path) -> Result<Achievements, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+
+        let mut achievements = Vec::new();
+        for (n, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap().trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let invalid = || format!("{}:{}: invalid achievement: {line}", path.display(), n + 1);
+            let mut words = line.split_whitespace();
+            let (Some(addr), Some(op), Some(value)) = (words.next(), words.next(), words.next())
+            else {
+                return Err(invalid());
+            };
+            let name = words.collect::<Vec<_>>().join(" ");
+            if name.is_empty() {
+                return Err(invalid());
+            }
+
+            achievements.push(Achievement {
+                addr: u16::from_str_radix(addr, 16)
+                    .ok()
+                    .filter(|&addr| (addr as usize) < MEM_SIZE)
+                    .ok_or_else(invalid)?,
+                comparison: Comparison::parse(op).ok_or_else(invalid)?,
+                value: u8::from_str_radix(value, 16).map_err(|_| invalid())?,
+                name,
+            });
+        }
+
+        Ok(Achievements {
+            achievements,
+            unlocked: HashSet::new(),
+            path: None,
+        })
+    }
+
+    // Unlocks the achievements `chip8`'s memory now meets, returning their
+    // names. Run once a frame.
+    pub fn check(&mut self, chip8: &Chip8) -> Vec<String> {
+        let memory = chip8.memory();
+        let unlocked: Vec<String> = self
+            .achievements
+            .iter()
+            .filter(|a| !self.unlocked.contains(&a.name))
+            .filter(|a| a.comparison.holds(memory[a.addr as usize], a.value))
+            .map(|a| a.name.clone())
+            .collect();
+        if !unlocked.is_empty() {
+            self.unlocked.extend(unlocked.iter().cloned());
+            if let Err(e) = self.save() {
+                eprintln!("{e}");
+            }
+        }
+        unlocked
+    }
+
+    fn unlocked_count(&self) -> usize {
+        self.achievements
+            .iter()
+            .filter(|a| self.unlocked.contains(&a.name))
+            .count()
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let mut names: Vec<&str> = self.unlocked.iter().map(String::as_str).collect();
+        names.sort_unstable();
+        let text: String = names.iter().map(|name| format!("{name}\n")).collect();
+        path.parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|()| fs::write(path, text))
+            .map_err(|e| format!("Failed to save achievements to {}: {e}", path.display()))
+    }
+}
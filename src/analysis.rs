@@ -0,0 +1,394 @@
+use crate::disasm::disassemble;
+use crate::START_ADDR;
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::{self, Write};
+
+/// A run of instructions that is only entered at its first and only leaves
+/// from its last.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Block {
+    pub start: u16,
+    /// Each instruction's address and opcode.
+    pub instructions: Vec<(u16, u16)>,
+    /// Where the last instruction can go next.
+    pub edges: Vec<Edge>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edge {
+    /// Falls through to the next instruction.
+    Next(u16),
+    Jump(u16),
+    /// Skips the next instruction.
+    Skip(u16),
+    Call(u16),
+}
+
+/// Bytes of the ROM no instruction reached from the start covers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Region {
+    pub start: u16,
+    pub end: u16,
+    /// Whether an ANNN points I into the region, as it would at sprites or
+    /// other data.
+    pub loaded: bool,
+}
+
+/// Something in the ROM's code that is likely a bug or a misreading of data
+/// as code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Issue {
+    /// A jump or call at `from` goes outside the ROM.
+    JumpOutsideRom { from: u16, to: u16 },
+    /// Execution reaches an opcode that isn't an instruction.
+    RunsIntoData { at: u16 },
+    /// Execution runs past the end of the ROM.
+    RunsOffEnd { at: u16 },
+    /// Instructions start at both `at` and the byte before, so one of them
+    /// jumps into the middle of the other.
+    Overlap { at: u16 },
+    /// A return the main program can reach without a call, which underflows
+    /// the stack.
+    ReturnFromMain { at: u16 },
+    /// Code the main program and the subroutine at `sub` both run, with
+    /// different things on the stack.
+    SharedWithMain { at: u16, sub: u16 },
+    /// A BNNN jump table, whose targets can't be followed.
+    JumpTable { at: u16 },
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Issue::JumpOutsideRom { from, to } => {
+                write!(f, "{from:#05x}: jumps to {to:#05x}, outside the ROM")
+            }
+            Issue::RunsIntoData { at } => write!(f, "{at:#05x}: runs into data"),
+            Issue::RunsOffEnd { at } => write!(f, "{at:#05x}: runs off the end of the ROM"),
+            Issue::Overlap { at } => write!(
+                f,
+                "{at:#05x}: jumped into the middle of the instruction at {:#05x}",
+                at - 1
+            ),
+            Issue::ReturnFromMain { at } => {
+                write!(
+                    f,
+                    "{at:#05x}: returns without a call, underflowing the stack"
+                )
+            }
+            Issue::SharedWithMain { at, sub } => write!(
+                f,
+                "{at:#05x}: run by both the main program and the subroutine at {sub:#05x}"
+            ),
+            Issue::JumpTable { at } => write!(f, "{at:#05x}: jump table can't be followed"),
+        }
+    }
+}
+
+/// The control-flow graph of a ROM loaded at `START_ADDR`, with what it
+/// leaves unreached and looks wrong.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Analysis {
+    pub blocks: Vec<Block>,
+    pub unreached: Vec<Region>,
+    pub issues: Vec<Issue>,
+}
+
+/// Follows jumps, calls, and skips from the start of `rom` to find its code
+/// and split it into blocks.
+pub fn analyze(rom: &[u8]) -> Analysis {
+    let mut trace = Trace::new(rom);
+    let mut issues = core::mem::take(&mut trace.issues);
+    for &offset in trace.instructions.keys() {
+        if offset > 0 && trace.instructions.contains_key(&(offset - 1)) {
+            issues.push(Issue::Overlap {
+                at: address(offset),
+            });
+        }
+    }
+    issues.sort();
+    issues.dedup();
+    Analysis {
+        blocks: trace.blocks(),
+        unreached: trace.unreached(),
+        issues,
+    }
+}
+
+impl Analysis {
+    /// The graph in Graphviz's DOT language, with calls dashed.
+    pub fn to_dot(&self) -> String {
+        let starts: BTreeSet<u16> = self.blocks.iter().map(|block| block.start).collect();
+        let mut dot = String::from("digraph rom {\n\tnode [shape=box, fontname=monospace];\n");
+        for block in &self.blocks {
+            let mut label = String::new();
+            for &(addr, opcode) in &block.instructions {
+                let _ = write!(label, "{addr:#05x}  {}\\l", disassemble(opcode));
+            }
+            let _ = writeln!(dot, "\tb{:03x} [label=\"{label}\"];", block.start);
+            for &edge in &block.edges {
+                let (to, style) = match edge {
+                    Edge::Next(to) | Edge::Jump(to) => (to, ""),
+                    Edge::Skip(to) => (to, " [label=skip]"),
+                    Edge::Call(to) => (to, " [style=dashed]"),
+                };
+                if starts.contains(&to) {
+                    let _ = writeln!(dot, "\tb{:03x} -> b{to:03x}{style};", block.start);
+                }
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+// Who runs an instruction: the main program or the subroutine starting at
+// an offset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Owner {
+    Main,
+    Sub(usize),
+}
+
+// What points at an address, in the order decompiled labels prefer them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Reference {
+    Call,
+    Jump,
+    Load,
+}
+
+// The instructions reachable from the start of a ROM, found by running the
+// main program and then each subroutine it calls.
+pub(crate) struct Trace<'a> {
+    rom: &'a [u8],
+    // ROM offsets of the instructions reached.
+    pub(crate) instructions: BTreeMap<usize, Owner>,
+    // Addresses jumped to, called, or loaded into I, inside the ROM or not.
+    pub(crate) references: BTreeMap<u16, Reference>,
+    issues: Vec<Issue>,
+}
+
+impl<'a> Trace<'a> {
+    pub(crate) fn new(rom: &'a [u8]) -> Trace<'a> {
+        let mut trace = Trace {
+            rom,
+            instructions: BTreeMap::new(),
+            references: BTreeMap::new(),
+            issues: Vec::new(),
+        };
+        if rom.len() >= 2 {
+            let mut subs = vec![(0, Owner::Main)];
+            let mut traced = BTreeSet::new();
+            while let Some((entry, owner)) = subs.pop() {
+                if traced.insert(entry) {
+                    trace.follow(entry, owner, &mut subs);
+                }
+            }
+        }
+        trace
+    }
+
+    pub(crate) fn opcode(&self, offset: usize) -> Option<u16> {
+        let bytes = self.rom.get(offset..offset + 2)?;
+        Some(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    // The ROM offset of `addr`, if it's inside the ROM.
+    pub(crate) fn offset(&self, addr: u16) -> Option<usize> {
+        let offset = (addr as usize).checked_sub(START_ADDR as usize)?;
+        (offset < self.rom.len()).then_some(offset)
+    }
+
+    fn refer(&mut self, addr: u16, reference: Reference) {
+        let entry = self.references.entry(addr).or_insert(reference);
+        *entry = (*entry).min(reference);
+    }
+
+    // Marks the code `owner` runs from `entry`, queueing the subroutines it
+    // calls.
+    fn follow(&mut self, entry: usize, owner: Owner, subs: &mut Vec<(usize, Owner)>) {
+        let mut pending = vec![entry];
+        while let Some(offset) = pending.pop() {
+            let at = address(offset);
+            let Some(opcode) = self.opcode(offset) else {
+                self.issues.push(Issue::RunsOffEnd { at });
+                continue;
+            };
+            if !is_instruction(opcode) {
+                self.issues.push(Issue::RunsIntoData { at });
+                continue;
+            }
+            if let Some(&other) = self.instructions.get(&offset) {
+                match (owner, other) {
+                    (Owner::Main, Owner::Sub(sub)) | (Owner::Sub(sub), Owner::Main) => {
+                        self.issues.push(Issue::SharedWithMain {
+                            at,
+                            sub: address(sub),
+                        });
+                    }
+                    _ => {}
+                }
+                continue;
+            }
+            self.instructions.insert(offset, owner);
+
+            let nnn = opcode & 0x0fff;
+            match opcode >> 12 {
+                0x0 if opcode == 0x00ee => {
+                    if owner == Owner::Main {
+                        self.issues.push(Issue::ReturnFromMain { at });
+                    }
+                }
+                0x0 if opcode == 0x00fd => {}
+                0x1 => {
+                    self.refer(nnn, Reference::Jump);
+                    match self.offset(nnn) {
+                        Some(target) => pending.push(target),
+                        None => self
+                            .issues
+                            .push(Issue::JumpOutsideRom { from: at, to: nnn }),
+                    }
+                }
+                0x2 => {
+                    self.refer(nnn, Reference::Call);
+                    match self.offset(nnn) {
+                        Some(target) => subs.push((target, Owner::Sub(target))),
+                        None => self
+                            .issues
+                            .push(Issue::JumpOutsideRom { from: at, to: nnn }),
+                    }
+                    pending.push(offset + 2);
+                }
+                0xb => {
+                    self.refer(nnn, Reference::Jump);
+                    self.issues.push(Issue::JumpTable { at });
+                }
+                0x3 | 0x4 | 0x5 | 0x9 | 0xe => {
+                    pending.push(offset + 2);
+                    pending.push(offset + 4);
+                }
+                op => {
+                    if op == 0xa {
+                        self.refer(nnn, Reference::Load);
+                    }
+                    pending.push(offset + 2);
+                }
+            }
+        }
+    }
+
+    fn blocks(&self) -> Vec<Block> {
+        let mut leaders: BTreeSet<usize> = BTreeSet::from([0]);
+        for (&addr, &reference) in &self.references {
+            if reference != Reference::Load {
+                leaders.extend(self.offset(addr));
+            }
+        }
+        for &offset in self.instructions.keys() {
+            let opcode = self.opcode(offset).unwrap();
+            if ends_block(opcode) {
+                leaders.insert(offset + 2);
+            }
+            if is_skip(opcode) {
+                leaders.insert(offset + 4);
+            }
+        }
+
+        let mut blocks: Vec<Block> = Vec::new();
+        let mut previous = None;
+        for &offset in self.instructions.keys() {
+            let opcode = self.opcode(offset).unwrap();
+            let continues =
+                previous.map(|previous| previous + 2) == Some(offset) && !leaders.contains(&offset);
+            match blocks.last_mut() {
+                Some(block) if continues => block.instructions.push((address(offset), opcode)),
+                _ => blocks.push(Block {
+                    start: address(offset),
+                    instructions: vec![(address(offset), opcode)],
+                    edges: Vec::new(),
+                }),
+            }
+            previous = Some(offset);
+        }
+        for block in &mut blocks {
+            let &(addr, opcode) = block.instructions.last().unwrap();
+            block.edges = edges(addr, opcode);
+        }
+        blocks
+    }
+
+    fn unreached(&self) -> Vec<Region> {
+        let mut covered = vec![false; self.rom.len()];
+        for &offset in self.instructions.keys() {
+            covered[offset] = true;
+            covered[offset + 1] = true;
+        }
+        let mut regions: Vec<Region> = Vec::new();
+        for (offset, &covered) in covered.iter().enumerate() {
+            if covered {
+                continue;
+            }
+            let addr = address(offset);
+            let loaded = self.references.get(&addr) == Some(&Reference::Load);
+            match regions.last_mut() {
+                Some(region) if region.end == addr => {
+                    region.end += 1;
+                    region.loaded |= loaded;
+                }
+                _ => regions.push(Region {
+                    start: addr,
+                    end: addr + 1,
+                    loaded,
+                }),
+            }
+        }
+        regions
+    }
+}
+
+// Whether `opcode` is a CHIP-8 or SUPER-CHIP instruction. 0000 would call
+// machine code at address 0, so it's taken as padding or code the ROM writes
+// at runtime.
+pub(crate) fn is_instruction(opcode: u16) -> bool {
+    let n = opcode & 0x000f;
+    let nn = opcode & 0x00ff;
+    match opcode >> 12 {
+        0x0 => opcode != 0x0000,
+        0x5 | 0x9 => n == 0,
+        0x8 => n <= 0x7 || n == 0xe,
+        0xe => nn == 0x9e || nn == 0xa1,
+        0xf => matches!(
+            nn,
+            0x07 | 0x0a | 0x15 | 0x18 | 0x1e | 0x29 | 0x30 | 0x33 | 0x55 | 0x65 | 0x75 | 0x85
+        ),
+        _ => true,
+    }
+}
+
+fn is_skip(opcode: u16) -> bool {
+    matches!(opcode >> 12, 0x3 | 0x4 | 0x5 | 0x9 | 0xe)
+}
+
+fn ends_block(opcode: u16) -> bool {
+    matches!(opcode, 0x00ee | 0x00fd) || matches!(opcode >> 12, 0x1 | 0x2 | 0xb) || is_skip(opcode)
+}
+
+fn edges(addr: u16, opcode: u16) -> Vec<Edge> {
+    let nnn = opcode & 0x0fff;
+    match opcode >> 12 {
+        0x0 if matches!(opcode, 0x00ee | 0x00fd) => Vec::new(),
+        0x1 => vec![Edge::Jump(nnn)],
+        0x2 => vec![Edge::Call(nnn), Edge::Next(addr + 2)],
+        0xb => Vec::new(),
+        _ if is_skip(opcode) => vec![Edge::Next(addr + 2), Edge::Skip(addr + 4)],
+        _ => vec![Edge::Next(addr + 2)],
+    }
+}
+
+fn address(offset: usize) -> u16 {
+    (offset + START_ADDR as usize) as u16
+}
@@ -0,0 +1,218 @@
+use crate::config::AudioConfig;
+use sdl2::audio::{
+    AudioCVT, AudioCallback, AudioDevice, AudioFormat, AudioSpecDesired, AudioSpecWAV,
+};
+use sdl2::Sdl;
+use std::path::Path;
+use std::process;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+const TONE_HZ: f32 = 440.0;
+// The wave's amplitude at full volume, loud enough without being harsh.
+const MAX_AMPLITUDE: f32 = 0.25;
+// How far [ and ] move the volume, in percent.
+const VOLUME_STEP: u8 = 10;
+
+// The buzzer, a square wave or a looped sample played while the sound timer
+// runs, at a volume from 0 to 100 percent. Without an audio device it stays
+// silent.
+//
+// The machine's sound hook starts and stops it straight from the emulation
+// thread, so the tone follows the timer to the instruction rather than to
+// the next frame the window sees.
+pub struct Buzzer {
+    device: Option<AudioDevice<Sound>>,
+    // Whether the machine is running, as the tone stops while it's paused.
+    running: bool,
+    sounding: Arc<AtomicBool>,
+    volume: u8,
+    muted: bool,
+}
+
+impl Buzzer {
+    // Plays the WAV file `sample` if given, and otherwise a tone.
+    pub fn open(sdl: &Sdl, config: &AudioConfig, sample: Option<&Path>) -> Buzzer {
+        let desired = AudioSpecDesired {
+            freq: Some(config.sample_rate),
+            channels: Some(1),
+            samples: Some(config.buffer_size),
+        };
+        let sounding = Arc::new(AtomicBool::new(false));
+        let device = sdl
+            .audio()
+            .and_then(|audio| {
+                audio.open_playback(None, &desired, |spec| {
+                    let source = match sample {
+                        Some(path) => Source::Sample {
+                            samples: load_sample(path, spec.freq).unwrap_or_else(|e| {
+                                eprintln!("{e}");
+                                process::exit(1);
+                            }),
+                            position: 0,
+                        },
+                        None => Source::Square {
+                            step: TONE_HZ / spec.freq as f32,
+                            phase: 0.0,
+                        },
+                    };
+                    Sound {
+                        source,
+                        level: 0.0,
+                        running: false,
+                        sounding: Arc::clone(&sounding),
+                    }
+                })
+            })
+            .map_err(|e| eprintln!("Failed to open audio, running without sound: {e}"))
+            .ok();
+        if let Some(device) = &device {
+            device.resume();
+        }
+
+        let mut buzzer = Buzzer {
+            device,
+            running: false,
+            sounding,
+            volume: config.volume.min(100),
+            muted: false,
+        };
+        buzzer.update();
+        buzzer
+    }
+
+    // Lets the tone play while the machine runs, or silences it while the
+    // machine is paused.
+    pub fn set_running(&mut self, running: bool) {
+        if running != self.running {
+            self.running = running;
+            self.update();
+        }
+    }
+
+    // A sound hook for a fresh machine, whose sound timer starts at zero, to
+    // start and stop the tone with.
+    pub fn sound_hook(&self) -> impl FnMut(bool) + Send + 'static {
+        self.sounding.store(false, Ordering::Relaxed);
+        let sounding = Arc::clone(&self.sounding);
+        move |on| sounding.store(on, Ordering::Relaxed)
+    }
+
+    pub fn volume(&self) -> u8 {
+        self.volume
+    }
+
+    // Moves the volume up or down a step, unmuting.
+    pub fn step_volume(&mut self, up: bool) {
+        self.volume = if up {
+            (self.volume + VOLUME_STEP).min(100)
+        } else {
+            self.volume.saturating_sub(VOLUME_STEP)
+        };
+        self.muted = false;
+        self.update();
+    }
+
+    pub fn toggle_mute(&mut self) {
+        self.muted = !self.muted;
+        self.update();
+    }
+
+    // The current level, for showing on screen.
+    pub fn describe(&self) -> String {
+        if self.muted {
+            "Muted".to_string()
+        } else {
+            format!("Volume {}%", self.volume)
+        }
+    }
+
+    fn update(&mut self) {
+        let level = if self.muted {
+            0.0
+        } else {
+            self.volume as f32 / 100.0
+        };
+        if let Some(device) = &mut self.device {
+            let mut sound = device.lock();
+            sound.level = level;
+            sound.running = self.running;
+        }
+    }
+}
+
+// Loads a WAV file as mono samples at `rate`.
+fn load_sample(path: &Path, rate: i32) -> Result<Vec<f32>, String> {
+    let error = |e: String| format!("Failed to load {}: {e}", path.display());
+    let wav = AudioSpecWAV::load_wav(path).map_err(error)?;
+    let cvt = AudioCVT::new(
+        wav.format,
+        wav.channels,
+        wav.freq,
+        AudioFormat::f32_sys(),
+        1,
+        rate,
+    )
+    .map_err(error)?;
+    let samples: Vec<f32> = cvt
+        .convert(wav.buffer().to_vec())
+        .chunks_exact(4)
+        .map(|bytes| f32::from_ne_bytes(bytes.try_into().unwrap()))
+        .collect();
+    if samples.is_empty() {
+        return Err(error("the file has no audio".to_string()));
+    }
+    Ok(samples)
+}
+
+struct Sound {
+    source: Source,
+    // The volume, from 0 to 1.
+    level: f32,
+    running: bool,
+    sounding: Arc<AtomicBool>,
+}
+
+enum Source {
+    Square {
+        // How far through a cycle of the tone each sample moves.
+        step: f32,
+        phase: f32,
+    },
+    // Played from the start each time the buzzer sounds, and looped for as
+    // long as it does.
+    Sample {
+        samples: Vec<f32>,
+        position: usize,
+    },
+}
+
+impl AudioCallback for Sound {
+    type Channel = f32;
+
+    fn callback(&mut self, out: &mut [f32]) {
+        let active = self.running && self.sounding.load(Ordering::Relaxed);
+        for sample in out {
+            *sample = match &mut self.source {
+                _ if !active => 0.0,
+                Source::Square { step, phase } => {
+                    let high = *phase < 0.5;
+                    *phase = (*phase + *step).fract();
+                    if high {
+                        MAX_AMPLITUDE * self.level
+                    } else {
+                        -MAX_AMPLITUDE * self.level
+                    }
+                }
+                Source::Sample { samples, position } => {
+                    let value = samples[*position];
+                    *position = (*position + 1) % samples.len();
+                    value * self.level
+                }
+            };
+        }
+        if let (false, Source::Sample { position, .. }) = (active, &mut self.source) {
+            *position = 0;
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use crate::workloads::WORKLOADS;
+use crate::BenchArgs;
+use my_chip8::Chip8;
+#[cfg(feature = "jit")]
+use my_chip8::Jit;
+#[cfg(feature = "jit")]
+use std::process;
+use std::time::Instant;
+
+#[cfg(feature = "jit")]
+const JIT_BATCH: u64 = 10_000;
+
+pub fn run(args: &BenchArgs) {
+    for (name, rom) in WORKLOADS {
+        let mut chip8 = Chip8::with_seed(0);
+        chip8.load_rom_bytes(rom);
+
+        let start = Instant::now();
+        match () {
+            #[cfg(feature = "jit")]
+            _ if args.jit => run_jit(&mut chip8, args.cycles),
+            _ => run_interpreter(&mut chip8, args.cycles),
+        }
+        let elapsed = start.elapsed().as_secs_f64();
+        println!(
+            "{name:<12} {:>8.2} MIPS  ({} cycles in {:.3} s)",
+            args.cycles as f64 / elapsed / 1e6,
+            args.cycles,
+            elapsed
+        );
+    }
+}
+
+fn run_interpreter(chip8: &mut Chip8, cycles: u64) {
+    for _ in 0..cycles {
+        chip8.run_cycle().expect("workload faulted");
+    }
+}
+
+#[cfg(feature = "jit")]
+fn run_jit(chip8: &mut Chip8, cycles: u64) {
+    let mut jit = Jit::new().unwrap_or_else(|e| {
+        eprintln!("{e}");
+        process::exit(1);
+    });
+    let mut remaining = cycles;
+    while remaining > 0 {
+        let batch = remaining.min(JIT_BATCH);
+        jit.run(chip8, batch as u32).expect("workload faulted");
+        remaining -= batch;
+    }
+}
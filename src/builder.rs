@@ -0,0 +1,186 @@
+use crate::clock::TimerClock;
+use crate::{
+    BoundsPolicy, Chip8, Clock, FontSet, Quirks, RandomSource, Rng, SysPolicy, Timing, Variant,
+    CYCLES_PER_FRAME, MEM_SIZE, TIMER_FREQ,
+};
+use alloc::boxed::Box;
+use alloc::sync::Arc;
+use core::fmt;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+/// Configures a [`Chip8`] before it starts, as an alternative to creating one
+/// and calling its setters:
+///
+/// ```
+/// use my_chip8::{Chip8, FontSet, Quirks};
+///
+/// let rom = [0x12, 0x00]; // 200: JP 0x200
+/// let chip8 = Chip8::builder()
+///     .quirks(Quirks::VIP)
+///     .cycles_per_second(700)
+///     .font(FontSet::SCHIP)
+///     .seed(42)
+///     .rom_bytes(&rom)
+///     .build()?;
+/// assert_eq!(chip8.cycles_per_frame(), 12);
+/// # Ok::<(), my_chip8::BuildError>(())
+/// ```
+///
+/// Anything left unset keeps the default `Chip8::new` gives it.
+#[derive(Default)]
+pub struct Chip8Builder<'a> {
+    rng: Option<Box<dyn RandomSource>>,
+    clock: Option<Arc<dyn Clock>>,
+    quirks: Quirks,
+    variant: Variant,
+    font: FontSet,
+    timing: Timing,
+    cycles_per_frame: Option<u32>,
+    start_addr: Option<u16>,
+    bounds: BoundsPolicy,
+    sys: SysPolicy,
+    rom: &'a [u8],
+}
+
+/// Why `Chip8Builder::build` couldn't create the machine.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuildError {
+    /// The start address is outside memory.
+    StartAddressOutOfBounds(u16),
+    /// The ROM is `size` bytes but only `max` fit after the start address.
+    RomTooLarge { size: usize, max: usize },
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            BuildError::StartAddressOutOfBounds(addr) => {
+                write!(f, "start address {addr:#x} is outside memory")
+            }
+            BuildError::RomTooLarge { size, max } => {
+                write!(f, "the ROM is {size} bytes, but only {max} fit in memory")
+            }
+        }
+    }
+}
+
+impl core::error::Error for BuildError {}
+
+impl Chip8 {
+    pub fn builder<'a>() -> Chip8Builder<'a> {
+        Chip8Builder::default()
+    }
+}
+
+impl<'a> Chip8Builder<'a> {
+    /// Draws CXNN results from a fixed seed, as `Chip8::with_seed` does.
+    pub fn seed(self, seed: u64) -> Chip8Builder<'a> {
+        self.rng(StdRng::seed_from_u64(seed))
+    }
+
+    /// Draws CXNN results from `rng`, as `Chip8::with_rng` does. Without this
+    /// or a seed, they come from the OS's entropy, or from seed 0 without the
+    /// `std` feature.
+    pub fn rng(mut self, rng: impl RandomSource + 'static) -> Chip8Builder<'a> {
+        self.rng = Some(Box::new(rng));
+        self
+    }
+
+    /// Ticks the timers by `clock`, as `Chip8::set_clock` does.
+    pub fn clock(mut self, clock: impl Clock + 'static) -> Chip8Builder<'a> {
+        self.clock = Some(Arc::new(clock));
+        self
+    }
+
+    pub fn quirks(mut self, quirks: Quirks) -> Chip8Builder<'a> {
+        self.quirks = quirks;
+        self
+    }
+
+    pub fn variant(mut self, variant: Variant) -> Chip8Builder<'a> {
+        self.variant = variant;
+        self
+    }
+
+    pub fn font(mut self, font: FontSet) -> Chip8Builder<'a> {
+        self.font = font;
+        self
+    }
+
+    pub fn timing(mut self, timing: Timing) -> Chip8Builder<'a> {
+        self.timing = timing;
+        self
+    }
+
+    /// Sets how many instructions run per 60Hz tick of the timers, as
+    /// `Chip8::set_cycles_per_frame` does.
+    pub fn cycles_per_frame(mut self, cycles: u32) -> Chip8Builder<'a> {
+        self.cycles_per_frame = Some(cycles);
+        self
+    }
+
+    /// Sets how many instructions run per second, rounded to a whole number
+    /// per 60Hz frame.
+    pub fn cycles_per_second(self, cycles: u32) -> Chip8Builder<'a> {
+        let timer_freq = TIMER_FREQ as u32;
+        self.cycles_per_frame((cycles + timer_freq / 2) / timer_freq)
+    }
+
+    /// Sets where the ROM is loaded and starts, as
+    /// `Chip8::set_start_address` does, instead of where the variant's ROMs
+    /// usually do.
+    pub fn start_address(mut self, addr: u16) -> Chip8Builder<'a> {
+        self.start_addr = Some(addr);
+        self
+    }
+
+    pub fn bounds_policy(mut self, bounds: BoundsPolicy) -> Chip8Builder<'a> {
+        self.bounds = bounds;
+        self
+    }
+
+    pub fn sys_policy(mut self, sys: SysPolicy) -> Chip8Builder<'a> {
+        self.sys = sys;
+        self
+    }
+
+    /// Loads `rom` at the start address.
+    pub fn rom_bytes(mut self, rom: &'a [u8]) -> Chip8Builder<'a> {
+        self.rom = rom;
+        self
+    }
+
+    pub fn build(self) -> Result<Chip8, BuildError> {
+        let start_addr = self.start_addr.unwrap_or(self.variant.start_address());
+        if start_addr as usize >= MEM_SIZE {
+            return Err(BuildError::StartAddressOutOfBounds(start_addr));
+        }
+        let max = MEM_SIZE - start_addr as usize;
+        if self.rom.len() > max {
+            return Err(BuildError::RomTooLarge {
+                size: self.rom.len(),
+                max,
+            });
+        }
+
+        #[cfg(feature = "std")]
+        let mut chip8 = Chip8::new();
+        #[cfg(not(feature = "std"))]
+        let mut chip8 = Chip8::with_seed(0);
+        if let Some(rng) = self.rng {
+            chip8.rng = Rng(rng);
+        }
+        chip8.clock = self.clock.map(TimerClock::new);
+        chip8.set_variant(self.variant);
+        chip8.set_font(self.font);
+        chip8.set_quirks(self.quirks);
+        chip8.set_timing(self.timing);
+        chip8.set_cycles_per_frame(self.cycles_per_frame.unwrap_or(CYCLES_PER_FRAME));
+        chip8.set_bounds_policy(self.bounds);
+        chip8.set_sys_policy(self.sys);
+        chip8.set_start_address(start_addr);
+        chip8.load_rom_bytes(self.rom);
+        Ok(chip8)
+    }
+}
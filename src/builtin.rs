@@ -0,0 +1,21 @@
+// ROMs compiled in, so the emulator can be demoed and checked with no other
+// files. Both were written for this project and are in the public domain;
+// their Octo sources are in the roms directory.
+
+/// Draws "CHIP-8" across the middle of the screen.
+pub const LOGO: &[u8] = include_bytes!("../roms/logo.ch8");
+
+/// Checks the arithmetic, logic, skip, jump, call, and memory instructions
+/// under any quirks, then draws "OK", or "E" and the number of the first
+/// failing test.
+pub const OPCODE_TEST: &[u8] = include_bytes!("../roms/opcode-test.ch8");
+
+/// The built-in ROMs by name.
+pub const ROMS: [(&str, &[u8]); 2] = [("logo", LOGO), ("test", OPCODE_TEST)];
+
+/// The built-in ROM called `name`, as listed in `ROMS`.
+pub fn get(name: &str) -> Option<&'static [u8]> {
+    ROMS.iter()
+        .find(|&&(rom_name, _)| rom_name == name)
+        .map(|&(_, rom)| rom)
+}
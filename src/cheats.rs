@@ -0,0 +1,154 @@
+use crate::RunArgs;
+use my_chip8::{Chip8, MEM_SIZE};
+use std::fs;
+use std::path::Path;
+use std::process;
+
+// A .cht file next to the ROM lists one cheat per line: a name, a hex address
+// and a hex value to keep there, and optionally a condition on the byte's
+// current value that must hold for the write to happen:
+//
+//   infinite-lives  3a0 03
+//   refill-ammo     3a1 09 if == 00
+pub struct Cheats {
+    cheats: Vec<Cheat>,
+    enabled: bool,
+}
+
+struct Cheat {
+    addr: u16,
+    value: u8,
+    condition: Option<(Comparison, u8)>,
+}
+
+// How a byte is compared against a value, in cheat conditions and
+// achievements.
+#[derive(Clone, Copy)]
+pub enum Comparison {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Cheats {
+    // Loads the cheats for the ROM being run, if it has a .cht file.
+    pub fn from_args(args: &RunArgs) -> Option<Cheats> {
+        Cheats::for_rom(Path::new(args.rom.as_deref()?)).unwrap_or_else(|e| {
+            eprintln!("{e}");
+            process::exit(1);
+        })
+    }
+
+    pub fn for_rom(rom: &Path) -> Result<Option<Cheats>, String> {
+        let path = rom.with_extension("cht");
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let cheats = Cheats::load(&path)?;
+        println!(
+            "Loaded {} cheats from {}",
+            cheats.cheats.len(),
+            path.display()
+        );
+        Ok(Some(cheats))
+    }
+
+    pub fn load(path: &Path) -> Result<Cheats, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+
+        let mut cheats = Vec::new();
+        for (n, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap().trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let invalid = || format!("{}:{}: invalid cheat: {line}", path.display(), n + 1);
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let (addr, value, condition) = match fields[..] {
+                [_, addr, value] => (addr, value, None),
+                [_, addr, value, "if", op, operand] => (addr, value, Some((op, operand))),
+                _ => return Err(invalid()),
+            };
+
+            let addr = u16::from_str_radix(addr, 16)
+                .ok()
+                .filter(|&addr| (addr as usize) < MEM_SIZE)
+                .ok_or_else(invalid)?;
+            let value = u8::from_str_radix(value, 16).map_err(|_| invalid())?;
+            let condition = match condition {
+                Some((op, operand)) => {
+                    let op = Comparison::parse(op).ok_or_else(invalid)?;
+                    Some((op, u8::from_str_radix(operand, 16).map_err(|_| invalid())?))
+                }
+                None => None,
+            };
+
+            cheats.push(Cheat {
+                addr,
+                value,
+                condition,
+            });
+        }
+
+        Ok(Cheats {
+            cheats,
+            enabled: true,
+        })
+    }
+
+    pub fn apply(&self, chip8: &mut Chip8) {
+        if !self.enabled {
+            return;
+        }
+
+        for cheat in &self.cheats {
+            let current = chip8.memory()[cheat.addr as usize];
+            let applies = cheat
+                .condition
+                .is_none_or(|(op, operand)| op.holds(current, operand));
+            if applies {
+                chip8.write_byte(cheat.addr, cheat.value).unwrap();
+            }
+        }
+    }
+
+    // Returns whether cheats are now enabled.
+    #[cfg(feature = "sdl")]
+    pub fn toggle(&mut self) -> bool {
+        self.enabled = !self.enabled;
+        self.enabled
+    }
+}
+
+impl Comparison {
+    // Parses an operator such as ">=".
+    pub fn parse(op: &str) -> Option<Comparison> {
+        let comparison = match op {
+            "==" => Comparison::Eq,
+            "!=" => Comparison::Ne,
+            "<" => Comparison::Lt,
+            "<=" => Comparison::Le,
+            ">" => Comparison::Gt,
+            ">=" => Comparison::Ge,
+            _ => return None,
+        };
+        Some(comparison)
+    }
+
+    pub fn holds(self, value: u8, operand: u8) -> bool {
+        match self {
+            Comparison::Eq => value == operand,
+            Comparison::Ne => value != operand,
+            Comparison::Lt => value < operand,
+            Comparison::Le => value <= operand,
+            Comparison::Gt => value > operand,
+            Comparison::Ge => value >= operand,
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use crate::TIMER_FREQ;
+use alloc::sync::Arc;
+use core::sync::atomic::{AtomicU64, Ordering};
+use core::time::Duration;
+
+/// A source of time for the 60Hz delay and sound timers.
+///
+/// Without one, the timers tick once every `Chip8::cycles_per_frame`
+/// instructions, so they only keep time if the host runs instructions at the
+/// right rate. With one, they tick as the clock says time passes. Implemented
+/// for any `Fn() -> Duration` closure, so web hosts can wrap
+/// `performance.now()`, embedded ones a SysTick counter, and tests a value
+/// they move by hand.
+pub trait Clock: Send + Sync {
+    /// The time since some fixed point. It must never go backwards.
+    fn now(&self) -> Duration;
+}
+
+impl<F: Fn() -> Duration + Send + Sync> Clock for F {
+    fn now(&self) -> Duration {
+        self()
+    }
+}
+
+/// The OS's monotonic clock.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy)]
+pub struct SystemClock {
+    start: std::time::Instant,
+}
+
+#[cfg(feature = "std")]
+impl SystemClock {
+    pub fn new() -> SystemClock {
+        SystemClock {
+            start: std::time::Instant::now(),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Default for SystemClock {
+    fn default() -> SystemClock {
+        SystemClock::new()
+    }
+}
+
+#[cfg(feature = "std")]
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+/// A clock that only moves when told to, for hosts that keep their own time,
+/// such as an embedded timer interrupt or an emulator running at a different
+/// speed, and for tests. Clones share the same time.
+#[derive(Debug, Clone, Default)]
+pub struct ManualClock {
+    nanos: Arc<AtomicU64>,
+}
+
+impl ManualClock {
+    pub fn new() -> ManualClock {
+        ManualClock::default()
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        let nanos = duration.as_nanos().min(u64::MAX as u128) as u64;
+        self.nanos.fetch_add(nanos, Ordering::Relaxed);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        Duration::from_nanos(self.nanos.load(Ordering::Relaxed))
+    }
+}
+
+// A clock and how many timer ticks it has given since it was set, counted
+// from the time it was set so rounding never builds up.
+#[derive(Clone)]
+pub(crate) struct TimerClock {
+    clock: Arc<dyn Clock>,
+    start: Duration,
+    ticks: u64,
+}
+
+impl TimerClock {
+    pub(crate) fn new(clock: Arc<dyn Clock>) -> TimerClock {
+        TimerClock {
+            start: clock.now(),
+            clock,
+            ticks: 0,
+        }
+    }
+
+    // How many times the timers should have ticked since this was last asked.
+    pub(crate) fn due_ticks(&mut self) -> u64 {
+        let elapsed = self.clock.now().saturating_sub(self.start);
+        let ticks = (elapsed.as_nanos() * TIMER_FREQ as u128 / 1_000_000_000) as u64;
+        let due = ticks.saturating_sub(self.ticks);
+        self.ticks = self.ticks.max(ticks);
+        due
+    }
+}
@@ -0,0 +1,113 @@
+use crate::{FrameBuffer, DISPLAY_HEIGHT, DISPLAY_WIDTH};
+use alloc::vec::Vec;
+
+/// The CHIP-8X background colors, in the order 02A0 steps through them.
+pub const BACKGROUND_COLORS: [[u8; 3]; 4] = [
+    [0x00, 0x00, 0x80], // blue
+    [0x00, 0x00, 0x00], // black
+    [0x00, 0x80, 0x00], // green
+    [0x80, 0x00, 0x00], // red
+];
+/// The CHIP-8X foreground colors, numbered as in BXYN.
+pub const FOREGROUND_COLORS: [[u8; 3]; 8] = [
+    [0x00, 0x00, 0x00], // black
+    [0xff, 0x00, 0x00], // red
+    [0x00, 0x00, 0xff], // blue
+    [0xff, 0x00, 0xff], // violet
+    [0x00, 0xff, 0x00], // green
+    [0xff, 0xff, 0x00], // yellow
+    [0x00, 0xff, 0xff], // aqua
+    [0xff, 0xff, 0xff], // white
+];
+// Foreground colors apply to blocks 8 pixels wide and one row tall; BXY0
+// colors them four rows at a time.
+const ZONE_WIDTH: usize = 8;
+const ZONE_HEIGHT: usize = 4;
+const ZONE_COLUMNS: usize = DISPLAY_WIDTH / ZONE_WIDTH;
+const ZONE_ROWS: usize = DISPLAY_HEIGHT / ZONE_HEIGHT;
+const DEFAULT_FOREGROUND: u8 = 1;
+
+/// The CHIP-8X colors laid over the monochrome display: one background color,
+/// and a foreground color for lit pixels in each 8x1 block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ColorMap {
+    background: u8,
+    foreground: [[u8; ZONE_COLUMNS]; DISPLAY_HEIGHT],
+}
+
+impl Default for ColorMap {
+    /// A blue background with red pixels.
+    fn default() -> ColorMap {
+        ColorMap {
+            background: 0,
+            foreground: [[DEFAULT_FOREGROUND; ZONE_COLUMNS]; DISPLAY_HEIGHT],
+        }
+    }
+}
+
+impl ColorMap {
+    pub fn background(&self) -> [u8; 3] {
+        BACKGROUND_COLORS[self.background as usize]
+    }
+
+    /// The color pixel (x, y) has when lit.
+    pub fn foreground(&self, x: usize, y: usize) -> [u8; 3] {
+        let color = self.foreground[y % DISPLAY_HEIGHT][(x / ZONE_WIDTH) % ZONE_COLUMNS];
+        FOREGROUND_COLORS[color as usize]
+    }
+
+    pub fn pixel_rgb(&self, display: &FrameBuffer, x: usize, y: usize) -> [u8; 3] {
+        if display.pixel(x, y) {
+            self.foreground(x, y)
+        } else {
+            self.background()
+        }
+    }
+
+    /// `display` in these colors, as an RGB image with each pixel scaled up
+    /// to `scale` x `scale`.
+    pub fn to_rgb(&self, display: &FrameBuffer, scale: usize) -> Vec<u8> {
+        let (width, height) = display.size();
+        let mut image = Vec::with_capacity(width * scale * height * scale * 3);
+        for y in 0..height * scale {
+            for x in 0..width * scale {
+                image.extend_from_slice(&self.pixel_rgb(display, x / scale, y / scale));
+            }
+        }
+        image
+    }
+
+    pub(crate) fn next_background(&mut self) {
+        self.background = (self.background + 1) % BACKGROUND_COLORS.len() as u8;
+    }
+
+    // Colors the zones of 8x4 pixels from (`left`, `top`) to (`right`,
+    // `bottom`), inclusive and in zones, clipped to the display.
+    pub(crate) fn fill_zones(
+        &mut self,
+        (left, top): (usize, usize),
+        (right, bottom): (usize, usize),
+        color: u8,
+    ) {
+        for zone_row in top..=bottom.min(ZONE_ROWS - 1) {
+            for row in &mut self.foreground[zone_row * ZONE_HEIGHT..(zone_row + 1) * ZONE_HEIGHT] {
+                for zone in row.iter_mut().take(right + 1).skip(left) {
+                    *zone = color;
+                }
+            }
+        }
+    }
+
+    // Colors `rows` rows of the 8-pixel block containing (`x`, `y`).
+    pub(crate) fn fill_rows(&mut self, x: usize, y: usize, rows: usize, color: u8) {
+        let column = (x / ZONE_WIDTH) % ZONE_COLUMNS;
+        for row in self
+            .foreground
+            .iter_mut()
+            .skip(y % DISPLAY_HEIGHT)
+            .take(rows)
+        {
+            row[column] = color;
+        }
+    }
+}
@@ -0,0 +1,222 @@
+use crate::palette::PaletteName;
+#[cfg(any(
+    feature = "sdl",
+    feature = "terminal",
+    feature = "pixels",
+    feature = "egui"
+))]
+use crate::palette::Rgb;
+use crate::FontName;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+const CONFIG_DIR: &str = "my-chip8";
+const CONFIG_FILE: &str = "config.toml";
+const DEFAULT_SCALE: u32 = 20;
+const DEFAULT_SCREENSHOT_SCALE: u32 = 10;
+const DEFAULT_RECORDING_SCALE: u32 = 4;
+const DEFAULT_VOLUME: u8 = 50;
+const DEFAULT_SAMPLE_RATE: i32 = 44100;
+// About 12ms at the default rate, so the buzzer starts and stops crisply.
+const DEFAULT_BUFFER_SIZE: u16 = 512;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub palette: PaletteName,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub foreground: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub background: Option<String>,
+    pub phosphor: bool,
+    pub scanlines: bool,
+    pub pixel_grid: bool,
+    pub keypad: bool,
+    pub font: FontName,
+    pub window: WindowConfig,
+    pub screenshot: ScreenshotConfig,
+    pub recording: RecordingConfig,
+    pub roms: RomsConfig,
+    pub audio: AudioConfig,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WindowConfig {
+    pub scale: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub width: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub x: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub y: Option<i32>,
+    pub fullscreen: bool,
+    pub integer_scale: bool,
+}
+
+impl Default for WindowConfig {
+    fn default() -> WindowConfig {
+        WindowConfig {
+            scale: DEFAULT_SCALE,
+            width: None,
+            height: None,
+            x: None,
+            y: None,
+            fullscreen: false,
+            integer_scale: false,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScreenshotConfig {
+    pub scale: u32,
+    pub directory: PathBuf,
+}
+
+impl Default for ScreenshotConfig {
+    fn default() -> ScreenshotConfig {
+        ScreenshotConfig {
+            scale: DEFAULT_SCREENSHOT_SCALE,
+            directory: PathBuf::from("."),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RecordingConfig {
+    pub scale: u32,
+    pub directory: PathBuf,
+}
+
+impl Default for RecordingConfig {
+    fn default() -> RecordingConfig {
+        RecordingConfig {
+            scale: DEFAULT_RECORDING_SCALE,
+            directory: PathBuf::from("."),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RomsConfig {
+    pub directory: PathBuf,
+}
+
+impl Default for RomsConfig {
+    fn default() -> RomsConfig {
+        RomsConfig {
+            directory: PathBuf::from("."),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AudioConfig {
+    // In percent.
+    pub volume: u8,
+    // In Hz.
+    pub sample_rate: i32,
+    // In samples. Larger buffers add latency but survive a busy system
+    // without crackling.
+    pub buffer_size: u16,
+    // A WAV file to play instead of the tone.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sample: Option<PathBuf>,
+}
+
+impl Default for AudioConfig {
+    fn default() -> AudioConfig {
+        AudioConfig {
+            volume: DEFAULT_VOLUME,
+            sample_rate: DEFAULT_SAMPLE_RATE,
+            buffer_size: DEFAULT_BUFFER_SIZE,
+            sample: None,
+        }
+    }
+}
+
+impl Config {
+    pub fn load() -> Config {
+        let Some(path) = config_path() else {
+            return Config::default();
+        };
+
+        match fs::read_to_string(&path) {
+            Ok(contents) => toml::from_str(&contents).unwrap_or_else(|e| {
+                eprintln!("Ignoring invalid config {}: {e}", path.display());
+                Config::default()
+            }),
+            Err(_) => Config::default(),
+        }
+    }
+
+    #[cfg(feature = "sdl")]
+    pub fn save(&self) {
+        let Some(path) = config_path() else {
+            return;
+        };
+
+        let result = toml::to_string_pretty(self)
+            .map_err(|e| e.to_string())
+            .and_then(|contents| {
+                if let Some(dir) = path.parent() {
+                    fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+                }
+                fs::write(&path, contents).map_err(|e| e.to_string())
+            });
+
+        if let Err(e) = result {
+            eprintln!("Failed to save config {}: {e}", path.display());
+        }
+    }
+
+    #[cfg(any(
+        feature = "sdl",
+        feature = "terminal",
+        feature = "pixels",
+        feature = "egui"
+    ))]
+    pub fn foreground(&self) -> Option<Rgb> {
+        parse_color(self.foreground.as_deref())
+    }
+
+    #[cfg(any(
+        feature = "sdl",
+        feature = "terminal",
+        feature = "pixels",
+        feature = "egui"
+    ))]
+    pub fn background(&self) -> Option<Rgb> {
+        parse_color(self.background.as_deref())
+    }
+}
+
+pub fn config_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join(CONFIG_DIR))
+}
+
+pub fn config_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join(CONFIG_FILE))
+}
+
+#[cfg(any(
+    feature = "sdl",
+    feature = "terminal",
+    feature = "pixels",
+    feature = "egui"
+))]
+fn parse_color(color: Option<&str>) -> Option<Rgb> {
+    color.and_then(|c| {
+        c.parse()
+            .map_err(|e| eprintln!("Ignoring config color: {e}"))
+            .ok()
+    })
+}
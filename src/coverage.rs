@@ -0,0 +1,105 @@
+use crate::MEM_SIZE;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+const EXECUTED: u8 = 1 << 0;
+const READ: u8 = 1 << 1;
+const WRITTEN: u8 = 1 << 2;
+// Bytes shown on each row of `to_text`, and the width of `to_rgb`.
+const ROW_LEN: usize = 64;
+
+/// Which bytes of memory have been executed, read, or written since coverage
+/// was turned on with `Chip8::set_coverage`. Reads and writes are the
+/// program's own through I (DXYN, FX33, FX55, FX65), not the frontend's.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Coverage {
+    marks: Vec<u8>,
+}
+
+impl Coverage {
+    pub(crate) fn new() -> Coverage {
+        Coverage {
+            marks: vec![0; MEM_SIZE],
+        }
+    }
+
+    pub(crate) fn mark_executed(&mut self, addr: usize) {
+        self.mark(addr, 2, EXECUTED);
+    }
+
+    pub(crate) fn mark_read(&mut self, addr: usize, len: usize) {
+        self.mark(addr, len, READ);
+    }
+
+    pub(crate) fn mark_written(&mut self, addr: usize, len: usize) {
+        self.mark(addr, len, WRITTEN);
+    }
+
+    fn mark(&mut self, addr: usize, len: usize, mark: u8) {
+        for offset in 0..len {
+            self.marks[(addr + offset) % MEM_SIZE] |= mark;
+        }
+    }
+
+    /// Whether an instruction covering `addr` has run.
+    pub fn executed(&self, addr: u16) -> bool {
+        self.has(addr, EXECUTED)
+    }
+
+    pub fn read(&self, addr: u16) -> bool {
+        self.has(addr, READ)
+    }
+
+    pub fn written(&self, addr: u16) -> bool {
+        self.has(addr, WRITTEN)
+    }
+
+    fn has(&self, addr: u16, mark: u8) -> bool {
+        self.marks
+            .get(addr as usize)
+            .is_some_and(|&marks| marks & mark != 0)
+    }
+
+    /// A map of memory with a row per 64 bytes, each prefixed by its address:
+    /// `X` for executed, `W` for written, `R` for read, and `.` for untouched.
+    /// Executed wins over written, and written over read.
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        for (row, marks) in self.marks.chunks(ROW_LEN).enumerate() {
+            let _ = write!(text, "{:#05x}  ", row * ROW_LEN);
+            text.extend(marks.iter().map(|&marks| match marks {
+                _ if marks & EXECUTED != 0 => 'X',
+                _ if marks & WRITTEN != 0 => 'W',
+                _ if marks & READ != 0 => 'R',
+                _ => '.',
+            }));
+            text.push('\n');
+        }
+        text
+    }
+
+    /// The map as a 64x64 RGB image, a pixel per byte: green for executed,
+    /// red for written, blue for read, and black for untouched. A byte both
+    /// read and written is magenta.
+    pub fn to_rgb(&self) -> Vec<u8> {
+        self.marks
+            .iter()
+            .flat_map(|&marks| {
+                if marks & EXECUTED != 0 {
+                    [0x00, 0xff, 0x00]
+                } else {
+                    let red = if marks & WRITTEN != 0 { 0xff } else { 0x00 };
+                    let blue = if marks & READ != 0 { 0xff } else { 0x00 };
+                    [red, 0x00, blue]
+                }
+            })
+            .collect()
+    }
+
+    /// The width and height of `to_rgb`'s image.
+    pub fn image_size(&self) -> (usize, usize) {
+        (ROW_LEN, MEM_SIZE / ROW_LEN)
+    }
+}
@@ -0,0 +1,32 @@
+use my_chip8::Chip8;
+use std::fs::{self, File};
+use std::io::BufWriter;
+use std::path::Path;
+
+// Writes the coverage map to the --coverage file, if one was given, as a PNG
+// if its name ends in .png and as text otherwise.
+pub fn save(path: Option<&Path>, chip8: &Chip8) {
+    let (Some(path), Some(coverage)) = (path, chip8.coverage()) else {
+        return;
+    };
+    let is_png = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("png"));
+    let result = if is_png {
+        save_png(path, &coverage.to_rgb(), coverage.image_size())
+    } else {
+        fs::write(path, coverage.to_text()).map_err(|e| e.to_string())
+    };
+    if let Err(e) = result {
+        eprintln!("Failed to save coverage map {}: {e}", path.display());
+    }
+}
+
+fn save_png(path: &Path, image: &[u8], (width, height): (usize, usize)) -> Result<(), String> {
+    let file = File::create(path).map_err(|e| e.to_string())?;
+    let mut encoder = png::Encoder::new(BufWriter::new(file), width as u32, height as u32);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(|e| e.to_string())?;
+    writer.write_image_data(image).map_err(|e| e.to_string())
+}
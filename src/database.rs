@@ -0,0 +1,181 @@
+use crate::config;
+use crate::palette::Rgb;
+use my_chip8::{Quirks, Variant};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const DATABASE_DIR: &str = "chip-8-database";
+const PROGRAMS_FILE: &str = "programs.json";
+const HASHES_FILE: &str = "sha1-hashes.json";
+
+// The community CHIP-8 database (github.com/chip-8/chip-8-database), which
+// knows the platform, quirks, speed, and colors many ROMs were written for.
+// Only its programs.json and sha1-hashes.json are read.
+#[derive(Debug, Default)]
+pub struct Database {
+    programs: Vec<Program>,
+    // SHA-1 of a ROM to its index in `programs`.
+    hashes: HashMap<String, usize>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Program {
+    title: String,
+    #[serde(default)]
+    roms: HashMap<String, RomEntry>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct RomEntry {
+    platforms: Vec<String>,
+    tickrate: Option<u32>,
+    quirky_platforms: HashMap<String, QuirkOverrides>,
+    colors: Option<Colors>,
+}
+
+// The database's quirk flags, named for the non-VIP behaviour they enable.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct QuirkOverrides {
+    shift: Option<bool>,
+    memory_increment_by_x: Option<bool>,
+    memory_leave_i_unchanged: Option<bool>,
+    wrap: Option<bool>,
+    jump: Option<bool>,
+    vblank: Option<bool>,
+    logic: Option<bool>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct Colors {
+    // Background first, then the foreground of each plane.
+    pixels: Vec<String>,
+}
+
+// What the database says about one ROM. Anything it doesn't know, or that
+// this emulator can't do, is left as `None`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RomSettings {
+    pub title: String,
+    pub variant: Option<Variant>,
+    pub quirks: Option<Quirks>,
+    pub tickrate: Option<u32>,
+    pub foreground: Option<Rgb>,
+    pub background: Option<Rgb>,
+}
+
+impl Database {
+    // Loads the database from `dir`, or from the config directory when no
+    // directory is given. A missing default database isn't an error.
+    pub fn load(dir: Option<&Path>) -> Result<Option<Database>, String> {
+        let dir = match dir {
+            Some(dir) => dir.to_path_buf(),
+            None => match default_dir() {
+                Some(dir) if dir.is_dir() => dir,
+                _ => return Ok(None),
+            },
+        };
+        Ok(Some(Database {
+            programs: read_json(&dir.join(PROGRAMS_FILE))?,
+            hashes: read_json(&dir.join(HASHES_FILE))?,
+        }))
+    }
+
+    pub fn lookup(&self, rom: &[u8]) -> Option<RomSettings> {
+        let hash = sha1_smol::Sha1::from(rom).digest().to_string();
+        let program = self.programs.get(*self.hashes.get(&hash)?)?;
+        let entry = program.roms.get(&hash)?;
+
+        let platform = entry
+            .platforms
+            .iter()
+            .find(|id| platform_quirks(id).is_some());
+        let quirks = platform.map(|id| {
+            let mut quirks = platform_quirks(id).unwrap();
+            if let Some(overrides) = entry.quirky_platforms.get(id) {
+                overrides.apply(&mut quirks);
+            }
+            quirks
+        });
+        let color = |i: usize| {
+            entry
+                .colors
+                .as_ref()
+                .and_then(|colors| colors.pixels.get(i))
+                .and_then(|color| color.parse().ok())
+        };
+        Some(RomSettings {
+            title: program.title.clone(),
+            variant: platform.and_then(|id| platform_variant(id)),
+            quirks,
+            tickrate: entry.tickrate.filter(|&rate| rate > 0),
+            foreground: color(1),
+            background: color(0),
+        })
+    }
+}
+
+impl QuirkOverrides {
+    fn apply(&self, quirks: &mut Quirks) {
+        if let Some(shift) = self.shift {
+            quirks.shift_vy = !shift;
+        }
+        // I advancing by X rather than X + 1 can't be told apart here.
+        if let Some(true) = self.memory_increment_by_x {
+            quirks.load_store_increments_i = true;
+        }
+        if let Some(unchanged) = self.memory_leave_i_unchanged {
+            quirks.load_store_increments_i = !unchanged;
+        }
+        if let Some(wrap) = self.wrap {
+            quirks.wrap_sprites = wrap;
+        }
+        if let Some(jump) = self.jump {
+            quirks.jump_vx = jump;
+        }
+        if let Some(vblank) = self.vblank {
+            quirks.display_wait = vblank;
+        }
+        if let Some(logic) = self.logic {
+            quirks.logic_resets_vf = logic;
+        }
+    }
+}
+
+pub fn default_dir() -> Option<PathBuf> {
+    config::config_dir().map(|dir| dir.join(DATABASE_DIR))
+}
+
+// The quirks of each database platform this emulator can run ROMs for.
+fn platform_quirks(id: &str) -> Option<Quirks> {
+    match id {
+        "originalChip8" | "hybridVIP" | "chip8x" => Some(Quirks::VIP),
+        "modernChip8" => Some(Quirks {
+            load_store_increments_i: true,
+            ..Quirks::default()
+        }),
+        "chip48" | "superchip1" => Some(Quirks {
+            load_store_increments_i: true,
+            ..Quirks::SCHIP
+        }),
+        "superchip" => Some(Quirks::SCHIP),
+        _ => None,
+    }
+}
+
+fn platform_variant(id: &str) -> Option<Variant> {
+    match id {
+        "chip8x" => Some(Variant::Chip8X),
+        _ => None,
+    }
+}
+
+fn read_json<T: for<'de> Deserialize<'de>>(path: &Path) -> Result<T, String> {
+    let text =
+        fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+    serde_json::from_str(&text).map_err(|e| format!("Invalid database {}: {e}", path.display()))
+}
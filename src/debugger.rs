@@ -0,0 +1,352 @@
+use crate::CpuState;
+use alloc::boxed::Box;
+use alloc::collections::VecDeque;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{self, Write};
+
+/// Where `Chip8::run_cycles` stops, and whether it only stops when a
+/// condition on the registers holds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Breakpoint {
+    pub addr: u16,
+    pub condition: Option<Condition>,
+}
+
+impl Breakpoint {
+    /// Whether the breakpoint stops a machine in `state`.
+    pub fn hit(&self, state: &CpuState) -> bool {
+        state.pc == self.addr
+            && self
+                .condition
+                .as_ref()
+                .is_none_or(|condition| condition.eval(state))
+    }
+}
+
+/// One line of an instruction trace: the machine's state before `opcode`
+/// runs, as `KEY:VALUE` fields in hex.
+///
+/// ```text
+/// PC:0200 OP:6005 I:0000 SP:00 DT:00 ST:00 V0:00 V1:00 ... VF:00
+/// ```
+pub fn trace_line(state: &CpuState, opcode: u16) -> String {
+    let mut line = String::new();
+    let _ = write!(
+        line,
+        "PC:{:04X} OP:{:04X} I:{:04X} SP:{:02X} DT:{:02X} ST:{:02X}",
+        state.pc, opcode, state.i, state.sp, state.delay_timer, state.sound_timer
+    );
+    for (x, v) in state.v.iter().enumerate() {
+        let _ = write!(line, " V{:X}:{:02X}", x, v);
+    }
+    line
+}
+
+// A ring buffer of save states from before each of the last few
+// instructions, for `Chip8::step_back`.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct History {
+    states: VecDeque<Vec<u8>>,
+    depth: usize,
+}
+
+impl History {
+    pub(crate) fn set_depth(&mut self, depth: usize) {
+        self.depth = depth;
+        while self.states.len() > depth {
+            self.states.pop_front();
+        }
+    }
+
+    pub(crate) fn is_recording(&self) -> bool {
+        self.depth > 0
+    }
+
+    pub(crate) fn push(&mut self, state: Vec<u8>) {
+        if self.states.len() == self.depth {
+            self.states.pop_front();
+        }
+        self.states.push_back(state);
+    }
+
+    pub(crate) fn pop(&mut self) -> Option<Vec<u8>> {
+        self.states.pop_back()
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.states.len()
+    }
+}
+
+/// A class of instruction that `Chip8::run_cycles` can stop before, wherever
+/// it is, to find where a program draws or reads input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    /// DXYN.
+    Draw,
+    /// EX9E, EXA1, and FX0A.
+    Key,
+    /// FX18.
+    Sound,
+    /// FX33.
+    Bcd,
+}
+
+impl Event {
+    /// The event `opcode` causes, if any.
+    pub fn of(opcode: u16) -> Option<Event> {
+        match (opcode & 0xf000, opcode & 0x00ff) {
+            (0xd000, _) => Some(Event::Draw),
+            (0xe000, 0x9e | 0xa1) | (0xf000, 0x0a) => Some(Event::Key),
+            (0xf000, 0x18) => Some(Event::Sound),
+            (0xf000, 0x33) => Some(Event::Bcd),
+            _ => None,
+        }
+    }
+}
+
+/// A condition on the registers, such as `V3 == 0x1F && SP > 2`.
+///
+/// Operands are the registers `V0`-`VF`, `I`, `PC`, `SP`, `DT`, and `ST`, and
+/// decimal or `0x` hex numbers. They compare with `==`, `!=`, `<`, `<=`, `>`,
+/// and `>=`, and comparisons combine with `&&`, `||`, `!`, and parentheses.
+/// An operand on its own holds when it isn't zero.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Condition {
+    expr: Expr,
+}
+
+/// Why `Condition::parse` rejected an expression.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConditionError {
+    /// The expression ends where an operand or `)` should be.
+    UnexpectedEnd,
+    /// Something that doesn't belong starts at this byte offset.
+    Unexpected(usize),
+}
+
+impl fmt::Display for ConditionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConditionError::UnexpectedEnd => f.write_str("unexpected end of condition"),
+            ConditionError::Unexpected(at) => write!(f, "unexpected input at offset {at}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Expr {
+    Operand(Operand),
+    Compare(Operand, Comparison, Operand),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operand {
+    V(usize),
+    I,
+    Pc,
+    Sp,
+    Dt,
+    St,
+    Number(u32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Comparison {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Token<'a> {
+    Word(&'a str),
+    Symbol(&'static str),
+}
+
+// Longer symbols first, so `<=` isn't read as `<` then `=`.
+const SYMBOLS: [&str; 11] = ["&&", "||", "==", "!=", "<=", ">=", "<", ">", "!", "(", ")"];
+
+impl Condition {
+    pub fn parse(text: &str) -> Result<Condition, ConditionError> {
+        let mut parser = Parser {
+            tokens: tokenize(text)?,
+            next: 0,
+        };
+        let expr = parser.or()?;
+        match parser.tokens.get(parser.next) {
+            Some(&(at, _)) => Err(ConditionError::Unexpected(at)),
+            None => Ok(Condition { expr }),
+        }
+    }
+
+    pub fn eval(&self, state: &CpuState) -> bool {
+        self.expr.eval(state)
+    }
+}
+
+impl Expr {
+    fn eval(&self, state: &CpuState) -> bool {
+        match self {
+            Expr::Operand(operand) => operand.value(state) != 0,
+            Expr::Compare(left, comparison, right) => {
+                let (left, right) = (left.value(state), right.value(state));
+                match comparison {
+                    Comparison::Eq => left == right,
+                    Comparison::Ne => left != right,
+                    Comparison::Lt => left < right,
+                    Comparison::Le => left <= right,
+                    Comparison::Gt => left > right,
+                    Comparison::Ge => left >= right,
+                }
+            }
+            Expr::Not(expr) => !expr.eval(state),
+            Expr::And(left, right) => left.eval(state) && right.eval(state),
+            Expr::Or(left, right) => left.eval(state) || right.eval(state),
+        }
+    }
+}
+
+impl Operand {
+    fn parse(word: &str) -> Option<Operand> {
+        let upper = word.to_ascii_uppercase();
+        let operand = match upper.as_str() {
+            "I" => Operand::I,
+            "PC" => Operand::Pc,
+            "SP" => Operand::Sp,
+            "DT" => Operand::Dt,
+            "ST" => Operand::St,
+            _ => {
+                if let Some(x) = upper.strip_prefix('V').filter(|x| x.len() == 1) {
+                    Operand::V(usize::from_str_radix(x, 16).ok()?)
+                } else if let Some(hex) = upper.strip_prefix("0X") {
+                    Operand::Number(u32::from_str_radix(hex, 16).ok()?)
+                } else {
+                    Operand::Number(word.parse().ok()?)
+                }
+            }
+        };
+        Some(operand)
+    }
+
+    fn value(self, state: &CpuState) -> u32 {
+        match self {
+            Operand::V(x) => state.v[x] as u32,
+            Operand::I => state.i as u32,
+            Operand::Pc => state.pc as u32,
+            Operand::Sp => state.sp as u32,
+            Operand::Dt => state.delay_timer as u32,
+            Operand::St => state.sound_timer as u32,
+            Operand::Number(n) => n,
+        }
+    }
+}
+
+fn tokenize(text: &str) -> Result<Vec<(usize, Token<'_>)>, ConditionError> {
+    let mut tokens = Vec::new();
+    let mut at = 0;
+    while at < text.len() {
+        let rest = &text[at..];
+        let c = rest.chars().next().unwrap();
+        if c.is_whitespace() {
+            at += c.len_utf8();
+        } else if c.is_ascii_alphanumeric() {
+            let len = rest
+                .find(|c: char| !c.is_ascii_alphanumeric())
+                .unwrap_or(rest.len());
+            tokens.push((at, Token::Word(&rest[..len])));
+            at += len;
+        } else {
+            let symbol = SYMBOLS
+                .iter()
+                .find(|symbol| rest.starts_with(*symbol))
+                .ok_or(ConditionError::Unexpected(at))?;
+            tokens.push((at, Token::Symbol(symbol)));
+            at += symbol.len();
+        }
+    }
+    Ok(tokens)
+}
+
+// A recursive descent parser, loosest binding first: `||`, then `&&`, then
+// `!` and comparisons.
+struct Parser<'a> {
+    tokens: Vec<(usize, Token<'a>)>,
+    next: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn or(&mut self) -> Result<Expr, ConditionError> {
+        let mut expr = self.and()?;
+        while self.eat("||") {
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr, ConditionError> {
+        let mut expr = self.unary()?;
+        while self.eat("&&") {
+            expr = Expr::And(Box::new(expr), Box::new(self.unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> Result<Expr, ConditionError> {
+        if self.eat("!") {
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+        if self.eat("(") {
+            let expr = self.or()?;
+            return match self.advance()? {
+                (_, Token::Symbol(")")) => Ok(expr),
+                (at, _) => Err(ConditionError::Unexpected(at)),
+            };
+        }
+
+        let left = self.operand()?;
+        let comparison = match self.tokens.get(self.next) {
+            Some((_, Token::Symbol("=="))) => Comparison::Eq,
+            Some((_, Token::Symbol("!="))) => Comparison::Ne,
+            Some((_, Token::Symbol("<"))) => Comparison::Lt,
+            Some((_, Token::Symbol("<="))) => Comparison::Le,
+            Some((_, Token::Symbol(">"))) => Comparison::Gt,
+            Some((_, Token::Symbol(">="))) => Comparison::Ge,
+            _ => return Ok(Expr::Operand(left)),
+        };
+        self.next += 1;
+        Ok(Expr::Compare(left, comparison, self.operand()?))
+    }
+
+    fn operand(&mut self) -> Result<Operand, ConditionError> {
+        match self.advance()? {
+            (at, Token::Word(word)) => Operand::parse(word).ok_or(ConditionError::Unexpected(at)),
+            (at, Token::Symbol(_)) => Err(ConditionError::Unexpected(at)),
+        }
+    }
+
+    fn advance(&mut self) -> Result<(usize, Token<'a>), ConditionError> {
+        let token = *self
+            .tokens
+            .get(self.next)
+            .ok_or(ConditionError::UnexpectedEnd)?;
+        self.next += 1;
+        Ok(token)
+    }
+
+    fn eat(&mut self, symbol: &str) -> bool {
+        let found =
+            matches!(self.tokens.get(self.next), Some((_, Token::Symbol(s))) if *s == symbol);
+        if found {
+            self.next += 1;
+        }
+        found
+    }
+}
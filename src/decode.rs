@@ -0,0 +1,158 @@
+use crate::Variant;
+
+// Register operands are stored as u8 to keep the decode cache small.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Inst {
+    // A cache slot that hasn't been decoded since memory there last changed.
+    Undecoded,
+    Unknown,
+    Sys(u16),
+    Cls,
+    Ret,
+    Jump(u16),
+    Call(u16),
+    SkipEqImm(u8, u8),
+    SkipNeqImm(u8, u8),
+    SkipEqReg(u8, u8),
+    SetImm(u8, u8),
+    AddImm(u8, u8),
+    SetReg(u8, u8),
+    Or(u8, u8),
+    And(u8, u8),
+    Xor(u8, u8),
+    AddReg(u8, u8),
+    Sub(u8, u8),
+    ShiftRight(u8, u8),
+    ReverseSub(u8, u8),
+    ShiftLeft(u8, u8),
+    SkipNeqReg(u8, u8),
+    SetI(u16),
+    JumpOffset(u16),
+    Random(u8, u8),
+    Draw(u8, u8, u8),
+    SkipKeyPressed(u8),
+    SkipKeyNotPressed(u8),
+    GetDelayTimer(u8),
+    GetKey(u8),
+    SetDelayTimer(u8),
+    SetSoundTimer(u8),
+    AddI(u8),
+    Font(u8),
+    LargeFont(u8),
+    Bcd(u8),
+    RegDump(u8),
+    RegLoad(u8),
+    SaveFlags(u8),
+    LoadFlags(u8),
+    // CHIP-8X.
+    NextBackground,
+    AddNibbles(u8, u8),
+    ColorZones(u8, u8),
+    ColorRows(u8, u8, u8),
+    SkipKey2Pressed(u8),
+    SkipKey2NotPressed(u8),
+    Output(u8),
+    Input(u8),
+}
+
+pub(crate) fn decode(opcode: u16, variant: Variant) -> Inst {
+    let extension = match variant {
+        Variant::Chip8 => None,
+        Variant::Chip8X => decode_chip8x(opcode),
+        Variant::HiRes => decode_hires(opcode),
+    };
+    if let Some(inst) = extension {
+        return inst;
+    }
+
+    let x = ((opcode & 0x0f00) >> 8) as u8;
+    let y = ((opcode & 0x00f0) >> 4) as u8;
+    let n = (opcode & 0x000f) as u8;
+    let nn = (opcode & 0x00ff) as u8;
+    let nnn = opcode & 0x0fff;
+
+    match (opcode & 0xf000) >> 12 {
+        0x0 => match nnn {
+            0x0e0 => Inst::Cls,
+            0x0ee => Inst::Ret,
+            _ => Inst::Sys(nnn),
+        },
+        0x1 => Inst::Jump(nnn),
+        0x2 => Inst::Call(nnn),
+        0x3 => Inst::SkipEqImm(x, nn),
+        0x4 => Inst::SkipNeqImm(x, nn),
+        0x5 => Inst::SkipEqReg(x, y),
+        0x6 => Inst::SetImm(x, nn),
+        0x7 => Inst::AddImm(x, nn),
+        0x8 => match n {
+            0x0 => Inst::SetReg(x, y),
+            0x1 => Inst::Or(x, y),
+            0x2 => Inst::And(x, y),
+            0x3 => Inst::Xor(x, y),
+            0x4 => Inst::AddReg(x, y),
+            0x5 => Inst::Sub(x, y),
+            0x6 => Inst::ShiftRight(x, y),
+            0x7 => Inst::ReverseSub(x, y),
+            0xe => Inst::ShiftLeft(x, y),
+            _ => Inst::Unknown,
+        },
+        0x9 => Inst::SkipNeqReg(x, y),
+        0xa => Inst::SetI(nnn),
+        0xb => Inst::JumpOffset(nnn),
+        0xc => Inst::Random(x, nn),
+        0xd => Inst::Draw(x, y, n),
+        0xe => match nn {
+            0x9e => Inst::SkipKeyPressed(x),
+            0xa1 => Inst::SkipKeyNotPressed(x),
+            _ => Inst::Unknown,
+        },
+        0xf => match nn {
+            0x07 => Inst::GetDelayTimer(x),
+            0x0a => Inst::GetKey(x),
+            0x15 => Inst::SetDelayTimer(x),
+            0x18 => Inst::SetSoundTimer(x),
+            0x1e => Inst::AddI(x),
+            0x29 => Inst::Font(x),
+            0x30 => Inst::LargeFont(x),
+            0x33 => Inst::Bcd(x),
+            0x55 => Inst::RegDump(x),
+            0x65 => Inst::RegLoad(x),
+            0x75 if x < 8 => Inst::SaveFlags(x),
+            0x85 if x < 8 => Inst::LoadFlags(x),
+            _ => Inst::Unknown,
+        },
+        _ => Inst::Unknown,
+    }
+}
+
+// The instructions CHIP-8X adds or changes, or None for the ones it shares.
+fn decode_chip8x(opcode: u16) -> Option<Inst> {
+    let x = ((opcode & 0x0f00) >> 8) as u8;
+    let y = ((opcode & 0x00f0) >> 4) as u8;
+    let n = (opcode & 0x000f) as u8;
+    let nn = (opcode & 0x00ff) as u8;
+
+    let inst = match ((opcode & 0xf000) >> 12, nn) {
+        (0x0, _) if opcode == 0x02a0 => Inst::NextBackground,
+        (0x5, _) if n == 1 => Inst::AddNibbles(x, y),
+        (0xb, _) if n == 0 => Inst::ColorZones(x, y),
+        (0xb, _) => Inst::ColorRows(x, y, n),
+        (0xe, 0xf2) => Inst::SkipKey2Pressed(x),
+        (0xe, 0xf5) => Inst::SkipKey2NotPressed(x),
+        (0xf, 0xf8) => Inst::Output(x),
+        (0xf, 0xfb) => Inst::Input(x),
+        _ => return None,
+    };
+    Some(inst)
+}
+
+// The hi-res interpreter's entry point and its screen clear. Jumping to 0x260
+// runs the 1802 code there that switches to hi-res, so it goes straight to
+// the program at 0x2C0 instead.
+fn decode_hires(opcode: u16) -> Option<Inst> {
+    match opcode {
+        0x1260 => Some(Inst::Jump(0x2c0)),
+        0x0230 => Some(Inst::Cls),
+        _ => None,
+    }
+}
@@ -0,0 +1,268 @@
+use crate::analysis::{Reference, Trace};
+use crate::START_ADDR;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+// The bytes a DXY0 sprite takes: 16 rows of 16 pixels.
+const LARGE_SPRITE_SIZE: usize = 32;
+
+// What a labelled address is, which also names the label.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Label {
+    Main,
+    Sub,
+    Code,
+    Sprite,
+    Data,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Byte {
+    Data,
+    // The first byte of an instruction reached from the start.
+    Code,
+    // The byte after one.
+    Operand,
+    Sprite,
+}
+
+/// Decompiles a ROM loaded at `START_ADDR` into Octo source that assembles
+/// back to the same bytes.
+///
+/// Code is found by following jumps, calls, and skips from the start;
+/// everything else is written out as data. Jump and call targets get labels,
+/// as do the addresses I is pointed at, and data drawn right after
+/// `i := label` is written as sprite rows in binary. Jump tables (BNNN) can't
+/// be followed, so the code they lead to comes out as data.
+pub fn decompile(rom: &[u8]) -> String {
+    let mut program = Program::new(rom);
+    program.find_sprites();
+    program.emit()
+}
+
+struct Program<'a> {
+    rom: &'a [u8],
+    trace: Trace<'a>,
+    bytes: Vec<Byte>,
+    labels: BTreeMap<usize, Label>,
+}
+
+impl<'a> Program<'a> {
+    fn new(rom: &'a [u8]) -> Program<'a> {
+        let trace = Trace::new(rom);
+        let mut bytes = vec![Byte::Data; rom.len()];
+        for &offset in trace.instructions.keys() {
+            bytes[offset] = Byte::Code;
+            if bytes[offset + 1] != Byte::Code {
+                bytes[offset + 1] = Byte::Operand;
+            }
+        }
+        let mut labels = BTreeMap::new();
+        if !trace.instructions.is_empty() {
+            labels.insert(0, Label::Main);
+        }
+        for (&addr, &reference) in &trace.references {
+            if let Some(offset) = trace.offset(addr) {
+                let label = match reference {
+                    Reference::Call => Label::Sub,
+                    Reference::Jump => Label::Code,
+                    Reference::Load => Label::Data,
+                };
+                labels.entry(offset).or_insert(label);
+            }
+        }
+        Program {
+            rom,
+            trace,
+            bytes,
+            labels,
+        }
+    }
+
+    // Marks the data that `i := label` followed by a draw points at as
+    // sprites. I is only followed within straight-line code.
+    fn find_sprites(&mut self) {
+        let mut sprites = Vec::new();
+        let mut i = None;
+        for offset in 0..self.rom.len() {
+            if self.bytes[offset] != Byte::Code {
+                if self.bytes[offset] != Byte::Operand {
+                    i = None;
+                }
+                continue;
+            }
+            if self.labels.contains_key(&offset) {
+                i = None;
+            }
+            let opcode = self.trace.opcode(offset).unwrap();
+            match opcode >> 12 {
+                0xa => i = self.trace.offset(opcode & 0x0fff),
+                0xd => {
+                    if let Some(start) = i {
+                        let size = match opcode & 0xf {
+                            0 => LARGE_SPRITE_SIZE,
+                            n => n as usize,
+                        };
+                        sprites.push((start, size));
+                    }
+                }
+                0xf if matches!(opcode & 0xff, 0x1e | 0x29 | 0x30 | 0x55 | 0x65) => i = None,
+                _ => {}
+            }
+        }
+        for (start, size) in sprites {
+            let end = (start + size).min(self.rom.len());
+            for byte in &mut self.bytes[start..end] {
+                if *byte == Byte::Data {
+                    *byte = Byte::Sprite;
+                }
+            }
+            if self.labels.get(&start) == Some(&Label::Data) {
+                self.labels.insert(start, Label::Sprite);
+            }
+        }
+    }
+
+    fn emit(&self) -> String {
+        let mut out = String::new();
+        let mut offset = 0;
+        while offset < self.rom.len() {
+            if let Some(&label) = self.labels.get(&offset) {
+                let _ = writeln!(out, "\n: {}", label_name(label, offset));
+            }
+            // Instructions that overlap another or a label are written as
+            // data, so that every label lands on a byte.
+            let is_instruction = self.bytes[offset] == Byte::Code
+                && self.bytes[offset + 1] == Byte::Operand
+                && !self.labels.contains_key(&(offset + 1));
+            if is_instruction {
+                let opcode = self.trace.opcode(offset).unwrap();
+                let _ = writeln!(out, "\t{}", self.instruction(opcode));
+                offset += 2;
+            } else {
+                offset = self.emit_data(&mut out, offset);
+            }
+        }
+        out.trim_start().to_string()
+    }
+
+    // Writes the run of data at `start` up to the next label or instruction,
+    // returning where it ends. Sprites get a row per line.
+    fn emit_data(&self, out: &mut String, start: usize) -> usize {
+        let kind = self.bytes[start];
+        let mut end = start + 1;
+        while end < self.rom.len()
+            && !self.labels.contains_key(&end)
+            && self.bytes[end] != Byte::Code
+            && (self.bytes[end] == Byte::Sprite) == (kind == Byte::Sprite)
+        {
+            end += 1;
+        }
+        if kind == Byte::Sprite {
+            for byte in &self.rom[start..end] {
+                let _ = writeln!(out, "\t0b{byte:08b}");
+            }
+        } else {
+            for line in self.rom[start..end].chunks(8) {
+                let bytes: Vec<String> = line.iter().map(|byte| format!("0x{byte:02X}")).collect();
+                let _ = writeln!(out, "\t{}", bytes.join(" "));
+            }
+        }
+        end
+    }
+
+    // An address operand, by label when the address has one.
+    fn target(&self, addr: u16) -> String {
+        match self
+            .trace
+            .offset(addr)
+            .and_then(|offset| self.labels.get(&offset).map(|&label| (offset, label)))
+        {
+            Some((offset, label)) => label_name(label, offset),
+            None => format!("0x{addr:03X}"),
+        }
+    }
+
+    fn instruction(&self, opcode: u16) -> String {
+        let x = (opcode & 0x0f00) >> 8;
+        let y = (opcode & 0x00f0) >> 4;
+        let n = opcode & 0x000f;
+        let nn = opcode & 0x00ff;
+        let nnn = opcode & 0x0fff;
+
+        match opcode >> 12 {
+            0x0 => match nnn {
+                0x0e0 => "clear".to_string(),
+                0x0ee => "return".to_string(),
+                0x0fb => "scroll-right".to_string(),
+                0x0fc => "scroll-left".to_string(),
+                0x0fd => "exit".to_string(),
+                0x0fe => "lores".to_string(),
+                0x0ff => "hires".to_string(),
+                _ if nnn & 0xff0 == 0x0c0 => format!("scroll-down {n}"),
+                // Calls to machine code have no Octo syntax.
+                _ => format!("0x{:02X} 0x{:02X}", opcode >> 8, nn),
+            },
+            0x1 => format!("jump {}", self.target(nnn)),
+            0x2 => match self.trace.offset(nnn) {
+                Some(_) => self.target(nnn),
+                None => format!(":call 0x{nnn:03X}"),
+            },
+            0x3 => format!("if v{x:x} != 0x{nn:02X} then"),
+            0x4 => format!("if v{x:x} == 0x{nn:02X} then"),
+            0x5 => format!("if v{x:x} != v{y:x} then"),
+            0x6 => format!("v{x:x} := 0x{nn:02X}"),
+            0x7 => format!("v{x:x} += 0x{nn:02X}"),
+            0x8 => {
+                let op = match n {
+                    0x0 => ":=",
+                    0x1 => "|=",
+                    0x2 => "&=",
+                    0x3 => "^=",
+                    0x4 => "+=",
+                    0x5 => "-=",
+                    0x6 => ">>=",
+                    0x7 => "=-",
+                    _ => "<<=",
+                };
+                format!("v{x:x} {op} v{y:x}")
+            }
+            0x9 => format!("if v{x:x} == v{y:x} then"),
+            0xa => format!("i := {}", self.target(nnn)),
+            0xb => format!("jump0 {}", self.target(nnn)),
+            0xc => format!("v{x:x} := random 0x{nn:02X}"),
+            0xd => format!("sprite v{x:x} v{y:x} {n}"),
+            0xe if nn == 0x9e => format!("if v{x:x} -key then"),
+            0xe => format!("if v{x:x} key then"),
+            _ => match nn {
+                0x07 => format!("v{x:x} := delay"),
+                0x0a => format!("v{x:x} := key"),
+                0x15 => format!("delay := v{x:x}"),
+                0x18 => format!("buzzer := v{x:x}"),
+                0x1e => format!("i += v{x:x}"),
+                0x29 => format!("i := hex v{x:x}"),
+                0x30 => format!("i := bighex v{x:x}"),
+                0x33 => format!("bcd v{x:x}"),
+                0x55 => format!("save v{x:x}"),
+                0x65 => format!("load v{x:x}"),
+                0x75 => format!("saveflags v{x:x}"),
+                _ => format!("loadflags v{x:x}"),
+            },
+        }
+    }
+}
+
+fn label_name(label: Label, offset: usize) -> String {
+    let addr = offset + START_ADDR as usize;
+    match label {
+        Label::Main => "main".to_string(),
+        Label::Sub => format!("sub_{addr:03x}"),
+        Label::Code => format!("label_{addr:03x}"),
+        Label::Sprite => format!("sprite_{addr:03x}"),
+        Label::Data => format!("data_{addr:03x}"),
+    }
+}
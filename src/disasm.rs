@@ -0,0 +1,73 @@
+use crate::Symbols;
+use alloc::format;
+use alloc::string::{String, ToString};
+
+pub fn disassemble(opcode: u16) -> String {
+    disassemble_with(opcode, &Symbols::new())
+}
+
+/// Like `disassemble`, but with named addresses shown by name.
+pub fn disassemble_with(opcode: u16, symbols: &Symbols) -> String {
+    let x = (opcode & 0x0f00) >> 8;
+    let y = (opcode & 0x00f0) >> 4;
+    let n = opcode & 0x000f;
+    let nn = opcode & 0x00ff;
+    let nnn = opcode & 0x0fff;
+
+    match (opcode & 0xf000) >> 12 {
+        0x0 => match nnn {
+            0x0e0 => "CLS".to_string(),
+            0x0ee => "RET".to_string(),
+            _ => format!("SYS {}", symbols.label(nnn)),
+        },
+        0x1 => format!("JP {}", symbols.label(nnn)),
+        0x2 => format!("CALL {}", symbols.label(nnn)),
+        0x3 => format!("SE V{:X}, {:#04x}", x, nn),
+        0x4 => format!("SNE V{:X}, {:#04x}", x, nn),
+        0x5 if n == 0 => format!("SE V{:X}, V{:X}", x, y),
+        0x6 => format!("LD V{:X}, {:#04x}", x, nn),
+        0x7 => format!("ADD V{:X}, {:#04x}", x, nn),
+        0x8 => match n {
+            0x0 => format!("LD V{:X}, V{:X}", x, y),
+            0x1 => format!("OR V{:X}, V{:X}", x, y),
+            0x2 => format!("AND V{:X}, V{:X}", x, y),
+            0x3 => format!("XOR V{:X}, V{:X}", x, y),
+            0x4 => format!("ADD V{:X}, V{:X}", x, y),
+            0x5 => format!("SUB V{:X}, V{:X}", x, y),
+            0x6 => format!("SHR V{:X}, V{:X}", x, y),
+            0x7 => format!("SUBN V{:X}, V{:X}", x, y),
+            0xe => format!("SHL V{:X}, V{:X}", x, y),
+            _ => data(opcode),
+        },
+        0x9 if n == 0 => format!("SNE V{:X}, V{:X}", x, y),
+        0xa => format!("LD I, {}", symbols.label(nnn)),
+        0xb => format!("JP V0, {}", symbols.label(nnn)),
+        0xc => format!("RND V{:X}, {:#04x}", x, nn),
+        0xd => format!("DRW V{:X}, V{:X}, {}", x, y, n),
+        0xe => match nn {
+            0x9e => format!("SKP V{:X}", x),
+            0xa1 => format!("SKNP V{:X}", x),
+            _ => data(opcode),
+        },
+        0xf => match nn {
+            0x07 => format!("LD V{:X}, DT", x),
+            0x0a => format!("LD V{:X}, K", x),
+            0x15 => format!("LD DT, V{:X}", x),
+            0x18 => format!("LD ST, V{:X}", x),
+            0x1e => format!("ADD I, V{:X}", x),
+            0x29 => format!("LD F, V{:X}", x),
+            0x30 => format!("LD HF, V{:X}", x),
+            0x33 => format!("LD B, V{:X}", x),
+            0x55 => format!("LD [I], V{:X}", x),
+            0x65 => format!("LD V{:X}, [I]", x),
+            0x75 if x < 8 => format!("LD R, V{:X}", x),
+            0x85 if x < 8 => format!("LD V{:X}, R", x),
+            _ => data(opcode),
+        },
+        _ => data(opcode),
+    }
+}
+
+fn data(opcode: u16) -> String {
+    format!("DW {:#06x}", opcode)
+}
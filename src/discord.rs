@@ -0,0 +1,134 @@
+use serde_json::{json, Value};
+use std::io::{self, Read, Write};
+use std::process;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Frame opcodes of Discord's IPC protocol.
+const HANDSHAKE: u32 = 0;
+const FRAME: u32 = 1;
+// The desktop client listens on the first free one of these.
+const MAX_PIPES: u32 = 10;
+
+trait Pipe: Read + Write {}
+
+impl<T: Read + Write> Pipe for T {}
+
+// Shows the ROM being played and for how long on the user's Discord profile,
+// talking to the desktop client over its local IPC socket as the Discord
+// application `client_id`. Without a running client, or once the connection
+// drops, it does nothing.
+pub struct RichPresence {
+    pipe: Option<Box<dyn Pipe>>,
+}
+
+impl RichPresence {
+    pub fn connect(client_id: &str) -> RichPresence {
+        let mut presence = RichPresence {
+            pipe: open_pipe()
+                .map_err(|e| eprintln!("Failed to connect to Discord: {e}"))
+                .ok(),
+        };
+        let handshake = json!({ "v": 1, "client_id": client_id });
+        presence.send(HANDSHAKE, &handshake);
+        presence
+    }
+
+    // Shows `rom_name` as being played from now, or that a ROM is being
+    // picked if there's no name.
+    pub fn show_rom(&mut self, rom_name: &str) {
+        let start = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.as_secs());
+        let details = match rom_name {
+            "" => "Choosing a ROM".to_string(),
+            name => format!("Playing {name}"),
+        };
+        let command = json!({
+            "cmd": "SET_ACTIVITY",
+            "args": {
+                "pid": process::id(),
+                "activity": {
+                    "details": details,
+                    "timestamps": { "start": start },
+                },
+            },
+            "nonce": start.to_string(),
+        });
+        self.send(FRAME, &command);
+    }
+
+    // Sends a frame and waits for the reply, hanging up on any error.
+    fn send(&mut self, opcode: u32, payload: &Value) {
+        let Some(pipe) = &mut self.pipe else {
+            return;
+        };
+        if let Err(e) = exchange(pipe.as_mut(), opcode, payload) {
+            eprintln!("Lost the connection to Discord: {e}");
+            self.pipe = None;
+        }
+    }
+}
+
+// Frames are a little-endian opcode and length followed by that much JSON.
+fn exchange(pipe: &mut dyn Pipe, opcode: u32, payload: &Value) -> io::Result<()> {
+    let payload = payload.to_string();
+    let mut frame = Vec::with_capacity(8 + payload.len());
+    frame.extend_from_slice(&opcode.to_le_bytes());
+    frame.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    frame.extend_from_slice(payload.as_bytes());
+    pipe.write_all(&frame)?;
+    pipe.flush()?;
+
+    let mut header = [0; 8];
+    pipe.read_exact(&mut header)?;
+    let len = u32::from_le_bytes(header[4..].try_into().unwrap());
+    let mut reply = vec![0; len as usize];
+    pipe.read_exact(&mut reply)?;
+    let reply: Value = serde_json::from_slice(&reply)?;
+    if reply["evt"] == "ERROR" {
+        let message = reply["data"]["message"].as_str().unwrap_or("unknown error");
+        return Err(io::Error::other(message.to_string()));
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn open_pipe() -> io::Result<Box<dyn Pipe>> {
+    use std::os::unix::net::UnixStream;
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    let dirs = ["XDG_RUNTIME_DIR", "TMPDIR", "TMP", "TEMP"]
+        .iter()
+        .filter_map(std::env::var_os)
+        .map(PathBuf::from)
+        .chain([PathBuf::from("/tmp")]);
+    for dir in dirs {
+        for n in 0..MAX_PIPES {
+            if let Ok(stream) = UnixStream::connect(dir.join(format!("discord-ipc-{n}"))) {
+                stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+                return Ok(Box::new(stream));
+            }
+        }
+    }
+    Err(io::Error::new(
+        io::ErrorKind::NotFound,
+        "no Discord client is running",
+    ))
+}
+
+#[cfg(windows)]
+fn open_pipe() -> io::Result<Box<dyn Pipe>> {
+    use std::fs::OpenOptions;
+
+    for n in 0..MAX_PIPES {
+        let path = format!(r"\\.\pipe\discord-ipc-{n}");
+        if let Ok(file) = OpenOptions::new().read(true).write(true).open(path) {
+            return Ok(Box::new(file));
+        }
+    }
+    Err(io::Error::new(
+        io::ErrorKind::NotFound,
+        "no Discord client is running",
+    ))
+}
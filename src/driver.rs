@@ -0,0 +1,83 @@
+use crate::{FRAME_INTERVAL, MAX_BACKLOG};
+use my_chip8::{Chip8, Chip8Error, FrameBuffer};
+use std::time::Instant;
+
+pub trait DisplaySink {
+    fn present(&mut self, display: &FrameBuffer);
+}
+
+// Runs the interpreter in real time independently of how the frontend
+// receives events, and tells it when a new frame should be shown.
+pub struct Driver {
+    chip8: Chip8,
+    next_cycle: Instant,
+    last_present: Instant,
+    dirty: bool,
+}
+
+impl Driver {
+    pub fn new(chip8: Chip8) -> Driver {
+        let now = Instant::now();
+        Driver {
+            chip8,
+            next_cycle: now,
+            last_present: now,
+            dirty: true,
+        }
+    }
+
+    pub fn chip8(&self) -> &Chip8 {
+        &self.chip8
+    }
+
+    pub fn chip8_mut(&mut self) -> &mut Chip8 {
+        &mut self.chip8
+    }
+
+    pub fn next_cycle(&self) -> Instant {
+        self.next_cycle
+    }
+
+    #[cfg(any(feature = "terminal", feature = "pixels"))]
+    pub fn run_due_cycles(&mut self) -> Result<(), Chip8Error> {
+        self.run_due_cycles_until(|_| false).map(drop)
+    }
+
+    // Like `run_due_cycles`, but asks `stop` before each instruction and
+    // returns true without running it if it says to.
+    pub fn run_due_cycles_until(
+        &mut self,
+        mut stop: impl FnMut(&Chip8) -> bool,
+    ) -> Result<bool, Chip8Error> {
+        let now = Instant::now();
+        if now.duration_since(self.next_cycle) > MAX_BACKLOG {
+            self.next_cycle = now;
+        }
+
+        while self.next_cycle <= now {
+            if stop(&self.chip8) {
+                return Ok(true);
+            }
+            self.step()?;
+            self.next_cycle += self.chip8.last_instruction_duration();
+        }
+        Ok(false)
+    }
+
+    // Runs one instruction now, whether or not it's due.
+    pub fn step(&mut self) -> Result<(), Chip8Error> {
+        self.chip8.run_cycle()?;
+        self.dirty |= self.chip8.draw_flag();
+        Ok(())
+    }
+
+    pub fn needs_present(&self) -> bool {
+        self.dirty && self.last_present.elapsed() >= FRAME_INTERVAL
+    }
+
+    pub fn present(&mut self, sink: &mut impl DisplaySink) {
+        sink.present(self.chip8.frame_buffer());
+        self.dirty = false;
+        self.last_present = Instant::now();
+    }
+}
@@ -0,0 +1,370 @@
+use crate::cheats::Cheats;
+use crate::netplay::Netplay;
+#[cfg(feature = "remote")]
+use crate::remote::RemoteServer;
+use crate::replay::ReplayMode;
+#[cfg(feature = "scripting")]
+use crate::script::Script;
+use crate::{CYCLES_PER_FRAME, FRAME_INTERVAL, MAX_BACKLOG};
+use my_chip8::{Chip8, ManualClock, SharedChip8};
+#[cfg(feature = "scripting")]
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender, TryRecvError};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+// The emulation wakes once a frame and runs the instructions that have come
+// due since. Sleeps can wake this late, so the end of each frame's wait
+// yields instead.
+const SPIN_MARGIN: Duration = Duration::from_millis(2);
+
+// What the window tells the emulation thread.
+pub enum Input {
+    Key(u8, bool),
+    // Whether the machine should run, which it doesn't while paused, waiting
+    // for a ROM, or picking one.
+    Running(bool),
+    Speed(f64),
+    // Whether to report each instruction before it runs, for the debug HUD.
+    Trace(bool),
+    ToggleCheats,
+    // The window loaded a new ROM into the machine, with its own cheats.
+    Reset(Option<Cheats>),
+}
+
+// What the emulation thread tells the window.
+pub enum Output {
+    // Instructions run in a frame, whether any of them drew, and how far the
+    // time since the last frame was from a 60th of a second.
+    Ran {
+        cycles: u32,
+        drawn: bool,
+        jitter: Option<Duration>,
+    },
+    // The address and opcode of an instruction about to run, while tracing.
+    Executed(u16, u16),
+    // The emulation stopped on an error or a lost connection.
+    Stopped,
+}
+
+// The emulation thread, which runs the machine against the clock on its own
+// so that slow renders and bursts of window events don't hold it up or
+// throw its timers off. The window shares the machine to draw it and to save
+// and load states, and everything else goes over channels.
+pub struct Emulation {
+    inputs: Sender<Input>,
+    outputs: Receiver<Output>,
+    thread: JoinHandle<ReplayMode>,
+}
+
+impl Emulation {
+    pub fn spawn(
+        chip8: SharedChip8,
+        replay_mode: ReplayMode,
+        netplay: Option<Netplay>,
+        #[cfg(feature = "remote")] remote: Option<RemoteServer>,
+        #[cfg(feature = "scripting")] script: Option<PathBuf>,
+        cheats: Option<Cheats>,
+    ) -> Emulation {
+        let (inputs, receiver) = mpsc::channel();
+        let (sender, outputs) = mpsc::channel();
+        let thread = thread::spawn(move || {
+            // Scripts can't be sent between threads, so this one loads its
+            // own.
+            #[cfg(feature = "scripting")]
+            let script = script.map(|path| {
+                Script::load(&path).unwrap_or_else(|e| {
+                    eprintln!("{e}");
+                    std::process::exit(1);
+                })
+            });
+            // Replays and netplay need the timers to tick on the same
+            // cycles every run, so only free play times them by the clock.
+            let clock = (netplay.is_none() && matches!(replay_mode, ReplayMode::Off))
+                .then(ManualClock::new);
+            if let Some(clock) = &clock {
+                chip8.lock().set_clock(clock.clone());
+            }
+            let mut emulator = Emulator {
+                inputs: receiver,
+                outputs: sender,
+                replay_mode,
+                netplay,
+                #[cfg(feature = "remote")]
+                remote,
+                #[cfg(feature = "scripting")]
+                script,
+                cheats,
+                clock,
+                cycle: 0,
+                local_keys: 0,
+                running: false,
+                speed: 1.0,
+                tracing: false,
+            };
+            emulator.emulate(&chip8);
+            emulator.replay_mode
+        });
+        Emulation {
+            inputs,
+            outputs,
+            thread,
+        }
+    }
+
+    pub fn send(&self, input: Input) {
+        // A stopped thread has already said so with `Output::Stopped`.
+        let _ = self.inputs.send(input);
+    }
+
+    // Waits up to `timeout` for the emulation thread to say something, then
+    // takes whatever else it has said since.
+    pub fn receive(&self, timeout: Duration) -> Vec<Output> {
+        let mut outputs = match self.outputs.recv_timeout(timeout) {
+            Ok(output) => vec![output],
+            Err(RecvTimeoutError::Timeout) => return Vec::new(),
+            Err(RecvTimeoutError::Disconnected) => return vec![Output::Stopped],
+        };
+        loop {
+            match self.outputs.try_recv() {
+                Ok(output) => outputs.push(output),
+                Err(TryRecvError::Empty) => return outputs,
+                Err(TryRecvError::Disconnected) => {
+                    outputs.push(Output::Stopped);
+                    return outputs;
+                }
+            }
+        }
+    }
+
+    // Stops the thread, giving back the replay it was playing or recording.
+    pub fn finish(self) -> ReplayMode {
+        drop(self.inputs);
+        self.thread.join().unwrap()
+    }
+}
+
+struct Emulator {
+    inputs: Receiver<Input>,
+    outputs: Sender<Output>,
+    replay_mode: ReplayMode,
+    netplay: Option<Netplay>,
+    #[cfg(feature = "remote")]
+    remote: Option<RemoteServer>,
+    #[cfg(feature = "scripting")]
+    script: Option<Script>,
+    cheats: Option<Cheats>,
+    // The time the machine's timers go by: how long it has run, scaled by
+    // the speed.
+    clock: Option<ManualClock>,
+    cycle: u64,
+    local_keys: u16,
+    running: bool,
+    speed: f64,
+    tracing: bool,
+}
+
+impl Emulator {
+    // Runs until the window hangs up or the emulation fails.
+    fn emulate(&mut self, chip8: &SharedChip8) {
+        let mut next_cycle = Instant::now();
+        let mut next_frame = Instant::now();
+        let mut last_frame = None;
+        loop {
+            // Take in what the window sent, waiting for it while stopped and
+            // otherwise until the next frame is due.
+            let input = if self.running {
+                match self.wait_until(next_frame) {
+                    Ok(input) => input,
+                    Err(_) => return,
+                }
+            } else {
+                match self.inputs.recv() {
+                    Ok(input) => Some(input),
+                    Err(_) => return,
+                }
+            };
+            if let Some(input) = input {
+                let was_running = self.running;
+                self.handle(input, chip8);
+                if self.running && !was_running {
+                    next_cycle = Instant::now();
+                    next_frame = next_cycle;
+                    last_frame = None;
+                }
+                continue;
+            }
+
+            let now = Instant::now();
+            let elapsed = last_frame.map(|last| now.duration_since(last));
+            let jitter = elapsed.map(|elapsed| elapsed.abs_diff(FRAME_INTERVAL));
+            last_frame = Some(now);
+            if let Some(clock) = &self.clock {
+                let elapsed = elapsed.unwrap_or(Duration::ZERO).min(MAX_BACKLOG);
+                clock.advance(elapsed.mul_f64(self.speed));
+            }
+            next_frame += FRAME_INTERVAL;
+            if next_frame < now {
+                next_frame = now + FRAME_INTERVAL;
+            }
+
+            // Run everything that has come due in one go. Cycles are
+            // scheduled against the clock, so the emulation keeps its speed
+            // however the frame's instructions are bunched together.
+            if now.duration_since(next_cycle) > MAX_BACKLOG {
+                next_cycle = now;
+            }
+            let (mut cycles, mut drawn) = (0, false);
+            while next_cycle <= now {
+                // Netplay waits on the peer, so it swaps keys with the
+                // machine unlocked and the window free to draw it.
+                let keys = match self.exchange_keys() {
+                    Ok(keys) => keys,
+                    Err(e) => {
+                        eprintln!("{e}");
+                        let _ = self.outputs.send(Output::Stopped);
+                        return;
+                    }
+                };
+                let mut machine = chip8.lock();
+                if let Some(keys) = keys {
+                    machine.set_keys(keys);
+                }
+                loop {
+                    if let Err(e) = self.run_cycle(&mut machine) {
+                        eprintln!("{e}");
+                        let _ = self.outputs.send(Output::Stopped);
+                        return;
+                    }
+                    cycles += 1;
+                    drawn |= machine.draw_flag();
+                    next_cycle += machine.last_instruction_duration().div_f64(self.speed);
+                    if next_cycle > now || self.exchange_due() {
+                        break;
+                    }
+                }
+            }
+            let _ = self.outputs.send(Output::Ran {
+                cycles,
+                drawn,
+                jitter,
+            });
+        }
+    }
+
+    // Waits for input until `deadline`, returning None if none came. Sleeps
+    // can wake a millisecond or two late, so the last stretch of the wait
+    // yields instead, which keeps frames well within a millisecond of their
+    // schedule.
+    fn wait_until(&self, deadline: Instant) -> Result<Option<Input>, RecvTimeoutError> {
+        let sleep = deadline
+            .saturating_duration_since(Instant::now())
+            .saturating_sub(SPIN_MARGIN);
+        match self.inputs.recv_timeout(sleep) {
+            Ok(input) => return Ok(Some(input)),
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(e) => return Err(e),
+        }
+        while Instant::now() < deadline {
+            match self.inputs.try_recv() {
+                Ok(input) => return Ok(Some(input)),
+                Err(TryRecvError::Empty) => thread::yield_now(),
+                Err(TryRecvError::Disconnected) => return Err(RecvTimeoutError::Disconnected),
+            }
+        }
+        Ok(None)
+    }
+
+    fn handle(&mut self, input: Input, chip8: &SharedChip8) {
+        match input {
+            Input::Key(key, pressed) => {
+                match &mut self.replay_mode {
+                    ReplayMode::Off => {}
+                    ReplayMode::Record(replay) => replay.record(self.cycle, key, pressed),
+                    ReplayMode::Play(_) => return,
+                }
+                match (&self.netplay, pressed) {
+                    (Some(_), true) => self.local_keys |= 1 << key,
+                    (Some(_), false) => self.local_keys &= !(1 << key),
+                    (None, true) => chip8.key_down(key),
+                    (None, false) => chip8.key_up(key),
+                }
+            }
+            Input::Running(running) => self.running = running,
+            Input::Speed(speed) => self.speed = speed,
+            Input::Trace(tracing) => self.tracing = tracing,
+            Input::ToggleCheats => {
+                if let Some(cheats) = &mut self.cheats {
+                    let state = if cheats.toggle() { "on" } else { "off" };
+                    println!("Cheats {state}");
+                }
+            }
+            Input::Reset(cheats) => {
+                self.cheats = cheats;
+                self.cycle = 0;
+                if let Some(clock) = &self.clock {
+                    chip8.lock().set_clock(clock.clone());
+                }
+            }
+        }
+    }
+
+    // Whether the peers swap keys before the next cycle, which they do every
+    // frame's worth of cycles so both machines see them on the same cycle.
+    fn exchange_due(&self) -> bool {
+        self.netplay.is_some() && self.cycle.is_multiple_of(CYCLES_PER_FRAME)
+    }
+
+    // Swaps keys with the netplay peer if it's time to, returning the keys
+    // both sides hold.
+    fn exchange_keys(&mut self) -> Result<Option<u16>, String> {
+        if !self.exchange_due() {
+            return Ok(None);
+        }
+        let net = self.netplay.as_mut().unwrap();
+        let remote_keys = net
+            .exchange(self.local_keys)
+            .map_err(|e| format!("Netplay connection lost: {e}"))?;
+        Ok(Some(self.local_keys | remote_keys))
+    }
+
+    fn run_cycle(&mut self, chip8: &mut Chip8) -> Result<(), String> {
+        if let ReplayMode::Play(player) = &mut self.replay_mode {
+            player.apply(chip8, self.cycle);
+        }
+        #[cfg(feature = "remote")]
+        if let Some(remote) = &mut self.remote {
+            remote.poll(chip8);
+        }
+        #[cfg(feature = "scripting")]
+        if let Some(running) = self
+            .script
+            .as_mut()
+            .filter(|_| self.cycle.is_multiple_of(CYCLES_PER_FRAME))
+        {
+            if let Err(e) = running.on_frame(chip8) {
+                eprintln!("Script error, disabling it: {e}");
+                self.script = None;
+            }
+        }
+        if self.tracing {
+            let _ = self
+                .outputs
+                .send(Output::Executed(chip8.pc(), chip8.current_opcode()));
+        }
+        chip8
+            .run_cycle()
+            .map_err(|e| format!("Emulation error: {e}"))?;
+        self.cycle += 1;
+        if let Some(cheats) = &self.cheats {
+            cheats.apply(chip8);
+        }
+        #[cfg(feature = "remote")]
+        if chip8.draw_flag() {
+            if let Some(remote) = &mut self.remote {
+                remote.send_display(chip8.display_rows());
+            }
+        }
+        Ok(())
+    }
+}
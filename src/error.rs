@@ -0,0 +1,54 @@
+use core::fmt;
+
+/// An instruction the machine couldn't run, with its state when it failed.
+/// The machine is left on that instruction, so running it again repeats the
+/// error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Chip8Error {
+    pub kind: ErrorKind,
+    /// The address of the failing instruction.
+    pub pc: u16,
+    pub opcode: u16,
+    pub i: u16,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    AddressOutOfBounds(u16),
+    UnknownOpcode(u16),
+    /// A CALL with all 16 stack levels in use, at the given address.
+    StackOverflow(u16),
+    /// A RET with nothing on the stack, at the given address.
+    StackUnderflow(u16),
+    /// A 0NNN call to machine code at the given address, under
+    /// `SysPolicy::Error`.
+    MachineCode(u16),
+}
+
+impl fmt::Display for Chip8Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} at PC {:#05x} (opcode {:04X}, I {:#05x})",
+            self.kind, self.pc, self.opcode, self.i
+        )
+    }
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ErrorKind::AddressOutOfBounds(addr) => {
+                write!(f, "address {addr:#05x} is outside memory")
+            }
+            ErrorKind::UnknownOpcode(_) => f.write_str("unknown opcode"),
+            ErrorKind::StackOverflow(_) => f.write_str("call with a full stack"),
+            ErrorKind::StackUnderflow(_) => f.write_str("return with an empty stack"),
+            ErrorKind::MachineCode(addr) => write!(f, "machine code call to {addr:#05x}"),
+        }
+    }
+}
+
+impl core::error::Error for Chip8Error {}
+
+impl core::error::Error for ErrorKind {}
@@ -0,0 +1,123 @@
+/// The bytes in a font's 4x5 sprites for the hex digits 0–F.
+pub const SMALL_FONT_SIZE: usize = 16 * 5;
+/// The bytes in a font's 8x10 sprites for the decimal digits 0–9.
+pub const LARGE_FONT_SIZE: usize = 10 * 10;
+
+/// The built-in hex digits that FX29 points I at, copied below the program
+/// when a machine is created. SCHIP also has large digits, used by FX30.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FontSet {
+    pub small: [u8; SMALL_FONT_SIZE],
+    pub large: Option<[u8; LARGE_FONT_SIZE]>,
+}
+
+impl FontSet {
+    /// The CHIP-48 and SUPER-CHIP font, used by most modern interpreters.
+    pub const SCHIP: FontSet = FontSet {
+        small: [
+            0xf0, 0x90, 0x90, 0x90, 0xf0, // 0
+            0x20, 0x60, 0x20, 0x20, 0x70, // 1
+            0xf0, 0x10, 0xf0, 0x80, 0xf0, // 2
+            0xf0, 0x10, 0xf0, 0x10, 0xf0, // 3
+            0x90, 0x90, 0xf0, 0x10, 0x10, // 4
+            0xf0, 0x80, 0xf0, 0x10, 0xf0, // 5
+            0xf0, 0x80, 0xf0, 0x90, 0xf0, // 6
+            0xf0, 0x10, 0x20, 0x40, 0x40, // 7
+            0xf0, 0x90, 0xf0, 0x90, 0xf0, // 8
+            0xf0, 0x90, 0xf0, 0x10, 0xf0, // 9
+            0xf0, 0x90, 0xf0, 0x90, 0x90, // A
+            0xe0, 0x90, 0xe0, 0x90, 0xe0, // B
+            0xf0, 0x80, 0x80, 0x80, 0xf0, // C
+            0xe0, 0x90, 0x90, 0x90, 0xe0, // D
+            0xf0, 0x80, 0xf0, 0x80, 0xf0, // E
+            0xf0, 0x80, 0xf0, 0x80, 0x80, // F
+        ],
+        large: Some([
+            0x3c, 0x7e, 0xe7, 0xc3, 0xc3, 0xc3, 0xc3, 0xe7, 0x7e, 0x3c, // 0
+            0x18, 0x38, 0x58, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x3c, // 1
+            0x3e, 0x7f, 0xc3, 0x06, 0x0c, 0x18, 0x30, 0x60, 0xff, 0xff, // 2
+            0x3c, 0x7e, 0xc3, 0x03, 0x0e, 0x0e, 0x03, 0xc3, 0x7e, 0x3c, // 3
+            0x06, 0x0e, 0x1e, 0x36, 0x66, 0xc6, 0xff, 0xff, 0x06, 0x06, // 4
+            0xff, 0xff, 0xc0, 0xc0, 0xfc, 0xfe, 0x03, 0xc3, 0x7e, 0x3c, // 5
+            0x3e, 0x7c, 0xe0, 0xc0, 0xfc, 0xfe, 0xc3, 0xc3, 0x7e, 0x3c, // 6
+            0xff, 0xff, 0x03, 0x06, 0x0c, 0x18, 0x30, 0x60, 0x60, 0x60, // 7
+            0x3c, 0x7e, 0xc3, 0xc3, 0x7e, 0x7e, 0xc3, 0xc3, 0x7e, 0x3c, // 8
+            0x3c, 0x7e, 0xc3, 0xc3, 0x7f, 0x3f, 0x03, 0x03, 0x3e, 0x7c, // 9
+        ]),
+    };
+
+    /// The original COSMAC VIP interpreter's font.
+    pub const VIP: FontSet = FontSet {
+        small: [
+            0xf0, 0x90, 0x90, 0x90, 0xf0, // 0
+            0x60, 0x20, 0x20, 0x20, 0x70, // 1
+            0xf0, 0x10, 0xf0, 0x80, 0xf0, // 2
+            0xf0, 0x10, 0xf0, 0x10, 0xf0, // 3
+            0xa0, 0xa0, 0xf0, 0x20, 0x20, // 4
+            0xf0, 0x80, 0xf0, 0x10, 0xf0, // 5
+            0xf0, 0x80, 0xf0, 0x90, 0xf0, // 6
+            0xf0, 0x10, 0x10, 0x10, 0x10, // 7
+            0xf0, 0x90, 0xf0, 0x90, 0xf0, // 8
+            0xf0, 0x90, 0xf0, 0x10, 0xf0, // 9
+            0xf0, 0x90, 0xf0, 0x90, 0x90, // A
+            0xf0, 0x50, 0x70, 0x50, 0xf0, // B
+            0xf0, 0x80, 0x80, 0x80, 0xf0, // C
+            0xf0, 0x50, 0x50, 0x50, 0xf0, // D
+            0xf0, 0x80, 0xf0, 0x80, 0xf0, // E
+            0xf0, 0x80, 0xf0, 0x80, 0x80, // F
+        ],
+        large: None,
+    };
+
+    /// The Dream 6800's CHIPOS font, three pixels wide.
+    pub const DREAM_6800: FontSet = FontSet {
+        small: [
+            0xe0, 0xa0, 0xa0, 0xa0, 0xe0, // 0
+            0x40, 0x40, 0x40, 0x40, 0x40, // 1
+            0xe0, 0x20, 0xe0, 0x80, 0xe0, // 2
+            0xe0, 0x20, 0xe0, 0x20, 0xe0, // 3
+            0x80, 0xa0, 0xa0, 0xe0, 0x20, // 4
+            0xe0, 0x80, 0xe0, 0x20, 0xe0, // 5
+            0xe0, 0x80, 0xe0, 0xa0, 0xe0, // 6
+            0xe0, 0x20, 0x20, 0x20, 0x20, // 7
+            0xe0, 0xa0, 0xe0, 0xa0, 0xe0, // 8
+            0xe0, 0xa0, 0xe0, 0x20, 0xe0, // 9
+            0xe0, 0xa0, 0xe0, 0xa0, 0xa0, // A
+            0xc0, 0xa0, 0xe0, 0xa0, 0xc0, // B
+            0xe0, 0x80, 0x80, 0x80, 0xe0, // C
+            0xc0, 0xa0, 0xa0, 0xa0, 0xc0, // D
+            0xe0, 0x80, 0xe0, 0x80, 0xe0, // E
+            0xe0, 0x80, 0xc0, 0x80, 0x80, // F
+        ],
+        large: None,
+    };
+
+    /// The ETI-660's font, three pixels wide.
+    pub const ETI_660: FontSet = FontSet {
+        small: [
+            0xe0, 0xa0, 0xa0, 0xa0, 0xe0, // 0
+            0x20, 0x20, 0x20, 0x20, 0x20, // 1
+            0xe0, 0x20, 0xe0, 0x80, 0xe0, // 2
+            0xe0, 0x20, 0xe0, 0x20, 0xe0, // 3
+            0xa0, 0xa0, 0xe0, 0x20, 0x20, // 4
+            0xe0, 0x80, 0xe0, 0x20, 0xe0, // 5
+            0xe0, 0x80, 0xe0, 0xa0, 0xe0, // 6
+            0xe0, 0x20, 0x20, 0x20, 0x20, // 7
+            0xe0, 0xa0, 0xe0, 0xa0, 0xe0, // 8
+            0xe0, 0xa0, 0xe0, 0x20, 0xe0, // 9
+            0xe0, 0xa0, 0xe0, 0xa0, 0xa0, // A
+            0x80, 0x80, 0xe0, 0xa0, 0xe0, // B
+            0xe0, 0x80, 0x80, 0x80, 0xe0, // C
+            0x20, 0x20, 0xe0, 0xa0, 0xe0, // D
+            0xe0, 0x80, 0xe0, 0x80, 0xe0, // E
+            0xe0, 0x80, 0xc0, 0x80, 0x80, // F
+        ],
+        large: None,
+    };
+}
+
+impl Default for FontSet {
+    fn default() -> FontSet {
+        FontSet::SCHIP
+    }
+}
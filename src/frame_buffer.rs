@@ -0,0 +1,172 @@
+use crate::{DISPLAY_HEIGHT, DISPLAY_WIDTH};
+use alloc::vec::Vec;
+use core::iter;
+
+/// The most rows a display can have.
+pub(crate) const MAX_HEIGHT: usize = 64;
+
+/// A monochrome display that knows its own size, stored as one bit per pixel
+/// with a row per word and the leftmost pixel in the most significant bit.
+/// Displays are at most 64 pixels wide.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameBuffer {
+    width: usize,
+    height: usize,
+    rows: [u64; MAX_HEIGHT],
+}
+
+impl FrameBuffer {
+    /// The widest display supported.
+    pub const MAX_WIDTH: usize = 64;
+    /// The tallest display supported.
+    pub const MAX_HEIGHT: usize = MAX_HEIGHT;
+
+    pub(crate) const fn new(width: usize, height: usize) -> FrameBuffer {
+        debug_assert!(width <= Self::MAX_WIDTH && height <= MAX_HEIGHT);
+        FrameBuffer {
+            width,
+            height,
+            rows: [0; MAX_HEIGHT],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The width and height in pixels.
+    pub fn size(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    pub fn pixel(&self, x: usize, y: usize) -> bool {
+        (self.rows[y] >> (63 - x)) & 1 == 1
+    }
+
+    /// Whether the pixel at (x, y) is lit, or false outside the display.
+    pub fn get(&self, x: usize, y: usize) -> bool {
+        x < self.width && y < self.height && self.pixel(x, y)
+    }
+
+    /// Each row as an iterator over its pixels from left to right, whether
+    /// each is lit.
+    pub fn rows(&self) -> impl Iterator<Item = impl Iterator<Item = bool>> + '_ {
+        let width = self.width;
+        self.row_bits()
+            .iter()
+            .map(move |&bits| (0..width).map(move |x| (bits >> (63 - x)) & 1 == 1))
+    }
+
+    /// Every pixel in row-major order as its x, y, and whether it is lit, so
+    /// callers don't have to work positions out from indexes.
+    pub fn iter_pixels(&self) -> impl Iterator<Item = (usize, usize, bool)> + '_ {
+        self.rows()
+            .enumerate()
+            .flat_map(|(y, row)| row.enumerate().map(move |(x, lit)| (x, y, lit)))
+    }
+
+    /// The rows as words, one per row.
+    pub fn row_bits(&self) -> &[u64] {
+        &self.rows[..self.height]
+    }
+
+    pub(crate) fn row_bits_mut(&mut self) -> &mut [u64] {
+        &mut self.rows[..self.height]
+    }
+
+    /// One byte per pixel, 1 for lit and 0 for dark, in row-major order.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut pixels = Vec::with_capacity(self.width * self.height);
+        for &bits in self.row_bits() {
+            pixels.extend((0..self.width).map(|x| (bits >> (63 - x)) as u8 & 1));
+        }
+        pixels
+    }
+
+    /// The pixels that differ from `previous`, an earlier frame, in
+    /// row-major order, each with whether it is now lit. Rows are compared a
+    /// word at a time, so unchanged rows cost a single comparison. If
+    /// `previous` is smaller, the pixels outside it count as having been dark.
+    pub fn diff<'a>(
+        &'a self,
+        previous: &'a FrameBuffer,
+    ) -> impl Iterator<Item = (usize, usize, bool)> + 'a {
+        let width_mask = u64::MAX.checked_shl(64 - self.width as u32).unwrap_or(0);
+        self.row_bits()
+            .iter()
+            .enumerate()
+            .flat_map(move |(y, &bits)| {
+                let before = previous.row_bits().get(y).copied().unwrap_or(0);
+                let mut changed = (bits ^ before) & width_mask;
+                iter::from_fn(move || {
+                    if changed == 0 {
+                        return None;
+                    }
+                    let x = changed.leading_zeros() as usize;
+                    changed &= !(1 << (63 - x));
+                    Some((x, y, (bits >> (63 - x)) & 1 == 1))
+                })
+            })
+    }
+
+    /// Writes the display into `out` as RGBA, four bytes a pixel in row-major
+    /// order, as for a streaming texture. Pixels are expanded four at a time
+    /// from a table of the 16 ways four pixels can be lit, so a row is 16
+    /// copies rather than 64 branches.
+    ///
+    /// # Panics
+    ///
+    /// If `out` is shorter than `width * height * 4` bytes.
+    pub fn write_rgba(&self, foreground: [u8; 4], background: [u8; 4], out: &mut [u8]) {
+        assert!(out.len() >= self.width * self.height * 4);
+        let mut table = [[0; 16]; 16];
+        for (bits, texels) in table.iter_mut().enumerate() {
+            for (n, texel) in texels.chunks_exact_mut(4).enumerate() {
+                let lit = bits & (0b1000 >> n) != 0;
+                texel.copy_from_slice(if lit { &foreground } else { &background });
+            }
+        }
+
+        let whole = self.width / 4 * 4;
+        for (&bits, row) in self
+            .row_bits()
+            .iter()
+            .zip(out.chunks_exact_mut(self.width * 4))
+        {
+            let mut groups = row.chunks_exact_mut(16);
+            for (n, group) in (&mut groups).enumerate() {
+                group.copy_from_slice(&table[(bits >> (60 - 4 * n)) as usize & 0xf]);
+            }
+            // A width that isn't a multiple of four ends in single pixels.
+            for (n, texel) in groups.into_remainder().chunks_exact_mut(4).enumerate() {
+                let lit = (bits >> (63 - whole - n)) & 1 == 1;
+                texel.copy_from_slice(if lit { &foreground } else { &background });
+            }
+        }
+    }
+
+    /// An RGB image with each pixel scaled up to `scale` x `scale`.
+    pub fn to_rgb(&self, foreground: [u8; 3], background: [u8; 3], scale: usize) -> Vec<u8> {
+        let width = self.width * scale;
+        let height = self.height * scale;
+        let mut image = Vec::with_capacity(width * height * 3);
+        for y in 0..height {
+            for x in 0..width {
+                let lit = self.pixel(x / scale, y / scale);
+                image.extend_from_slice(if lit { &foreground } else { &background });
+            }
+        }
+        image
+    }
+}
+
+impl Default for FrameBuffer {
+    /// A blank display of the standard size.
+    fn default() -> FrameBuffer {
+        FrameBuffer::new(DISPLAY_WIDTH, DISPLAY_HEIGHT)
+    }
+}
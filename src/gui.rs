@@ -0,0 +1,423 @@
+use crate::config::Config;
+use crate::driver::{DisplaySink, Driver};
+use crate::headless::add_breakpoint;
+use crate::palette::Palette;
+use crate::stats::SessionStats;
+use crate::{coverage_map, keymap, sym_file, RunArgs};
+use eframe::egui::{self, Color32, ColorImage, Key, RichText, TextureHandle, TextureOptions};
+use my_chip8::{disasm, Chip8, FrameBuffer, Symbols};
+use std::process;
+use std::time::Instant;
+
+const TITLE: &str = "CHIP-8 Debugger";
+// How wide the display is drawn, in points, whatever its resolution.
+const DISPLAY_POINTS: f32 = 512.0;
+// Instructions listed on either side of PC.
+const DISASSEMBLY_CONTEXT: usize = 12;
+const BYTES_PER_ROW: usize = 16;
+
+#[derive(Clone, Copy)]
+enum Panel {
+    Display,
+    Registers,
+    Disassembly,
+    Memory,
+    Breakpoints,
+}
+
+impl Panel {
+    const ALL: [Panel; 5] = [
+        Panel::Display,
+        Panel::Registers,
+        Panel::Disassembly,
+        Panel::Memory,
+        Panel::Breakpoints,
+    ];
+
+    fn title(self) -> &'static str {
+        match self {
+            Panel::Display => "Display",
+            Panel::Registers => "Registers",
+            Panel::Disassembly => "Disassembly",
+            Panel::Memory => "Memory",
+            Panel::Breakpoints => "Breakpoints",
+        }
+    }
+}
+
+// A window of debugger panels around the running machine, each of which can
+// be moved, resized, collapsed, and closed and reopened from the View menu.
+// It drives the machine itself, so it can stop at breakpoints.
+struct Debugger<'a> {
+    driver: &'a mut Driver,
+    stats: &'a mut SessionStats,
+    symbols: Symbols,
+    open: [bool; Panel::ALL.len()],
+    running: bool,
+    // Set when the machine starts running so that it doesn't stop again at
+    // the breakpoint it was paused at.
+    resuming: bool,
+    // Why the machine last stopped by itself, shown next to the controls.
+    status: String,
+    screen: Screen,
+    // The byte picked in the memory panel and the value being typed for it.
+    selected: Option<u16>,
+    new_value: String,
+    new_breakpoint: String,
+}
+
+// The display's texture, updated when the driver has a new frame.
+struct Screen {
+    ctx: egui::Context,
+    palette: Palette,
+    texels: Vec<u8>,
+    texture: Option<TextureHandle>,
+}
+
+impl DisplaySink for Screen {
+    fn present(&mut self, display: &FrameBuffer) {
+        let (width, height) = display.size();
+        self.texels.resize(width * height * 4, 0);
+        let (fg, bg) = (self.palette.foreground, self.palette.background);
+        display.write_rgba(
+            [fg.r, fg.g, fg.b, 0xff],
+            [bg.r, bg.g, bg.b, 0xff],
+            &mut self.texels,
+        );
+        let image = ColorImage::from_rgba_unmultiplied([width, height], &self.texels);
+        match &mut self.texture {
+            Some(texture) => texture.set(image, TextureOptions::NEAREST),
+            None => {
+                let texture = self
+                    .ctx
+                    .load_texture("display", image, TextureOptions::NEAREST);
+                self.texture = Some(texture);
+            }
+        }
+    }
+}
+
+pub fn run(args: &RunArgs, config: &Config, mut chip8: Chip8) {
+    let mut stats = SessionStats::start(args, &mut chip8);
+    let mut driver = Driver::new(chip8);
+    let palette = args.palette(config);
+    let symbols = sym_file::from_args(args);
+    let options = eframe::NativeOptions {
+        viewport: egui::ViewportBuilder::default()
+            .with_title(TITLE)
+            .with_inner_size([1280.0, 800.0]),
+        ..Default::default()
+    };
+    let result = eframe::run_native(
+        TITLE,
+        options,
+        Box::new(|cc| {
+            Ok(Box::new(Debugger {
+                driver: &mut driver,
+                stats: &mut stats,
+                symbols,
+                open: [true; Panel::ALL.len()],
+                running: true,
+                resuming: true,
+                status: String::new(),
+                screen: Screen {
+                    ctx: cc.egui_ctx.clone(),
+                    palette,
+                    texels: Vec::new(),
+                    texture: None,
+                },
+                selected: None,
+                new_value: String::new(),
+                new_breakpoint: String::new(),
+            }))
+        }),
+    );
+    if let Err(e) = result {
+        eprintln!("Failed to open the debugger window: {e}");
+        process::exit(1);
+    }
+    coverage_map::save(args.coverage.as_deref(), driver.chip8());
+    stats.save();
+}
+
+impl eframe::App for Debugger<'_> {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        // Keys go to the game unless a text field has them.
+        if !ctx.wants_keyboard_input() {
+            let mask = ctx.input(|input| {
+                input
+                    .keys_down
+                    .iter()
+                    .filter_map(|key| keymap::name_to_key(key.name()))
+                    .fold(0, |mask, chip8_key| mask | 1 << chip8_key)
+            });
+            self.driver.chip8_mut().set_keys(mask);
+        }
+        if ctx.input(|input| input.key_pressed(Key::F5)) {
+            self.toggle_running();
+        }
+        if ctx.input(|input| input.key_pressed(Key::F10)) {
+            self.step();
+        }
+
+        if self.running {
+            self.run_due_cycles();
+            let wait = self
+                .driver
+                .next_cycle()
+                .saturating_duration_since(Instant::now());
+            ctx.request_repaint_after(wait);
+        }
+        // Paused, the display is redrawn whenever the window is, so steps
+        // show at once.
+        if !self.running || self.driver.needs_present() {
+            self.driver.present(&mut self.screen);
+        }
+
+        egui::TopBottomPanel::top("controls").show(ctx, |ui| self.controls(ui));
+        egui::CentralPanel::default().show(ctx, |_| {});
+        for panel in Panel::ALL {
+            let mut open = self.open[panel as usize];
+            egui::Window::new(panel.title())
+                .open(&mut open)
+                .show(ctx, |ui| match panel {
+                    Panel::Display => self.display(ui),
+                    Panel::Registers => self.registers(ui),
+                    Panel::Disassembly => self.disassembly(ui),
+                    Panel::Memory => self.memory(ui),
+                    Panel::Breakpoints => self.breakpoints(ui),
+                });
+            self.open[panel as usize] = open;
+        }
+        self.stats.frame();
+    }
+}
+
+impl Debugger<'_> {
+    // Runs the instructions that have come due, stopping before one at a
+    // breakpoint or on an error.
+    fn run_due_cycles(&mut self) {
+        let resuming = &mut self.resuming;
+        let at_breakpoint = self.driver.run_due_cycles_until(|chip8| {
+            let stop = !*resuming && chip8.at_breakpoint();
+            *resuming = false;
+            stop
+        });
+        match at_breakpoint {
+            Ok(true) => self.stop(format!(
+                "Breakpoint at {}",
+                self.symbols.label(self.driver.chip8().pc())
+            )),
+            Ok(false) => {}
+            Err(e) => self.stop(format!("Emulation error: {e}")),
+        }
+    }
+
+    fn stop(&mut self, status: String) {
+        self.running = false;
+        self.status = status;
+    }
+
+    fn toggle_running(&mut self) {
+        self.running = !self.running;
+        self.resuming = true;
+        self.status.clear();
+    }
+
+    fn step(&mut self) {
+        self.running = false;
+        self.status = match self.driver.step() {
+            Ok(()) => String::new(),
+            Err(e) => format!("Emulation error: {e}"),
+        };
+    }
+
+    fn controls(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.menu_button("View", |ui| {
+                for panel in Panel::ALL {
+                    ui.checkbox(&mut self.open[panel as usize], panel.title());
+                }
+            });
+            ui.separator();
+            let label = if self.running {
+                "Pause (F5)"
+            } else {
+                "Run (F5)"
+            };
+            if ui.button(label).clicked() {
+                self.toggle_running();
+            }
+            if ui.button("Step (F10)").clicked() {
+                self.step();
+            }
+            ui.label(&self.status);
+        });
+    }
+
+    fn display(&mut self, ui: &mut egui::Ui) {
+        let Some(texture) = &self.screen.texture else {
+            return;
+        };
+        let [width, height] = texture.size().map(|size| size as f32);
+        let scale = DISPLAY_POINTS / width;
+        ui.image((texture.id(), egui::vec2(width * scale, height * scale)));
+    }
+
+    fn registers(&mut self, ui: &mut egui::Ui) {
+        let state = self.driver.chip8().state();
+        egui::Grid::new("registers").striped(true).show(ui, |ui| {
+            let mut row = |name: &str, value: String| {
+                ui.monospace(name);
+                ui.monospace(value);
+                ui.end_row();
+            };
+            row("PC", format!("{:#05x}", state.pc));
+            row("I", format!("{:#05x}", state.i));
+            row("SP", state.sp.to_string());
+            row("DT", state.delay_timer.to_string());
+            row("ST", state.sound_timer.to_string());
+            for (i, v) in state.v.iter().enumerate() {
+                row(&format!("V{i:X}"), format!("{v:#04x}"));
+            }
+            for (depth, addr) in state.stack[..state.sp as usize].iter().enumerate() {
+                row(&format!("Stack {depth}"), self.symbols.label(*addr));
+            }
+        });
+    }
+
+    // The instructions around PC, which can be clicked to set and clear
+    // breakpoints.
+    fn disassembly(&mut self, ui: &mut egui::Ui) {
+        let pc = self.driver.chip8().pc() as usize;
+        let memory = self.driver.chip8().memory();
+        let first = pc.saturating_sub(2 * DISASSEMBLY_CONTEXT);
+        let last = (pc + 2 * DISASSEMBLY_CONTEXT).min(memory.len() - 2);
+        let mut toggled = None;
+        for addr in (first..=last).step_by(2) {
+            let opcode = u16::from_be_bytes([memory[addr], memory[addr + 1]]);
+            let addr = addr as u16;
+            let has_breakpoint = self
+                .driver
+                .chip8()
+                .breakpoints()
+                .iter()
+                .any(|bp| bp.addr == addr);
+            let line = format!(
+                "{} {}  {:04x}  {}",
+                if has_breakpoint { "●" } else { " " },
+                self.symbols.label(addr),
+                opcode,
+                disasm::disassemble_with(opcode, &self.symbols)
+            );
+            let mut text = RichText::new(line).monospace();
+            if addr as usize == pc {
+                text = text
+                    .strong()
+                    .background_color(ui.visuals().selection.bg_fill);
+            }
+            if ui.selectable_label(false, text).clicked() {
+                toggled = Some((addr, has_breakpoint));
+            }
+        }
+        match toggled {
+            Some((addr, true)) => self.driver.chip8_mut().remove_breakpoint(addr),
+            Some((addr, false)) => self.driver.chip8_mut().add_breakpoint(addr),
+            None => {}
+        }
+    }
+
+    // A hexdump with PC and I marked, where a byte can be picked and
+    // overwritten.
+    fn memory(&mut self, ui: &mut egui::Ui) {
+        if let Some(addr) = self.selected {
+            ui.horizontal(|ui| {
+                ui.label(format!("{addr:#05x} ="));
+                let field =
+                    ui.add(egui::TextEdit::singleline(&mut self.new_value).desired_width(32.0));
+                let entered = field.lost_focus() && ui.input(|input| input.key_pressed(Key::Enter));
+                if ui.button("Write").clicked() || entered {
+                    match u8::from_str_radix(self.new_value.trim(), 16) {
+                        Ok(value) => {
+                            if let Err(e) = self.driver.chip8_mut().write_byte(addr, value) {
+                                self.status = format!("Failed to write {addr:#05x}: {e}");
+                            }
+                        }
+                        Err(_) => self.status = format!("{} isn't a hex byte", self.new_value),
+                    }
+                }
+            });
+            ui.separator();
+        }
+
+        let state = self.driver.chip8().state();
+        let (pc, i) = (state.pc as usize, state.i as usize);
+        let rows = self.driver.chip8().memory().len() / BYTES_PER_ROW;
+        let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+        let mut picked = None;
+        egui::ScrollArea::vertical().show_rows(ui, row_height, rows, |ui, range| {
+            let memory = self.driver.chip8().memory();
+            for row in range {
+                let start = row * BYTES_PER_ROW;
+                ui.horizontal(|ui| {
+                    ui.spacing_mut().item_spacing.x = 4.0;
+                    ui.monospace(format!("{start:#05x}"));
+                    for (offset, byte) in memory[start..start + BYTES_PER_ROW].iter().enumerate() {
+                        let addr = start + offset;
+                        let mut text = RichText::new(format!("{byte:02x}")).monospace();
+                        if addr == pc || addr == pc + 1 {
+                            text = text.color(Color32::LIGHT_GREEN);
+                        }
+                        if addr == i {
+                            text = text.underline();
+                        }
+                        let selected = self.selected == Some(addr as u16);
+                        if ui.selectable_label(selected, text).clicked() {
+                            picked = Some(addr as u16);
+                        }
+                    }
+                });
+            }
+        });
+        if let Some(addr) = picked {
+            self.selected = Some(addr);
+            self.new_value = format!("{:02x}", self.driver.chip8().memory()[addr as usize]);
+        }
+    }
+
+    // The breakpoints set, and a field to add one by address or symbol with
+    // an optional condition, as with --break.
+    fn breakpoints(&mut self, ui: &mut egui::Ui) {
+        let mut removed = None;
+        for breakpoint in self.driver.chip8().breakpoints() {
+            ui.horizontal(|ui| {
+                let mut label = self.symbols.label(breakpoint.addr);
+                if breakpoint.condition.is_some() {
+                    label.push_str(" (conditional)");
+                }
+                ui.monospace(label);
+                if ui.small_button("Remove").clicked() {
+                    removed = Some(breakpoint.addr);
+                }
+            });
+        }
+        if let Some(addr) = removed {
+            self.driver.chip8_mut().remove_breakpoint(addr);
+        }
+
+        ui.separator();
+        ui.horizontal(|ui| {
+            let field = ui.add(
+                egui::TextEdit::singleline(&mut self.new_breakpoint)
+                    .hint_text("draw_player if V3 == 0x1f"),
+            );
+            let entered = field.lost_focus() && ui.input(|input| input.key_pressed(Key::Enter));
+            if ui.button("Add").clicked() || entered {
+                match add_breakpoint(self.driver.chip8_mut(), &self.new_breakpoint, &self.symbols) {
+                    Ok(()) => self.new_breakpoint.clear(),
+                    Err(e) => self.status = e,
+                }
+            }
+        });
+    }
+}
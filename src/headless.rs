@@ -0,0 +1,143 @@
+use crate::cheats::Cheats;
+use crate::coverage_map;
+use crate::replay::Player;
+#[cfg(feature = "scripting")]
+use crate::script::Script;
+use crate::stats::SessionStats;
+use crate::sym_file;
+use crate::RunArgs;
+use my_chip8::debugger::{trace_line, Condition};
+use my_chip8::{disasm, Chip8, FrameBuffer, Symbols};
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::process;
+
+pub fn run(args: &RunArgs, mut chip8: Chip8, mut player: Option<Player>) {
+    let cheats = Cheats::from_args(args);
+    let symbols = sym_file::from_args(args);
+    for breakpoint in &args.breakpoints {
+        if let Err(e) = add_breakpoint(&mut chip8, breakpoint, &symbols) {
+            eprintln!("{e}");
+            process::exit(1);
+        }
+    }
+    for event in args.break_events() {
+        chip8.add_event_breakpoint(event);
+    }
+    let stats = SessionStats::start(args, &mut chip8);
+    #[cfg(feature = "scripting")]
+    let mut script = Script::from_args(args);
+    let mut trace = args.trace.as_ref().map(|path| {
+        let file = File::create(path).unwrap_or_else(|e| {
+            eprintln!("Failed to create {}: {e}", path.display());
+            process::exit(1);
+        });
+        BufWriter::new(file)
+    });
+
+    for cycle in 0..args.cycles {
+        if cycle > 0 && chip8.at_breakpoint() {
+            println!(
+                "Stopped at breakpoint {} ({}) after {cycle} cycles",
+                symbols.label(chip8.pc()),
+                disasm::disassemble_with(chip8.current_opcode(), &symbols)
+            );
+            break;
+        }
+        if let Some(player) = &mut player {
+            player.apply(&mut chip8, cycle);
+        }
+        #[cfg(feature = "scripting")]
+        if let Some(script) = script
+            .as_mut()
+            .filter(|_| cycle % crate::CYCLES_PER_FRAME == 0)
+        {
+            if let Err(e) = script.on_frame(&mut chip8) {
+                eprintln!("Script error: {e}");
+                process::exit(1);
+            }
+        }
+        if let Some(trace) = &mut trace {
+            writeln!(
+                trace,
+                "{}",
+                trace_line(&chip8.state(), chip8.current_opcode())
+            )
+            .unwrap_or_else(|e| panic!("Failed to write the trace: {e}"));
+        }
+        if let Err(e) = chip8.run_cycle() {
+            eprintln!("Emulation error at cycle {cycle}: {e}");
+            // Exiting skips the writer's flush on drop.
+            if let Some(trace) = &mut trace {
+                let _ = trace.flush();
+            }
+            coverage_map::save(args.coverage.as_deref(), &chip8);
+            stats.save();
+            print_registers(&chip8, &symbols);
+            process::exit(1);
+        }
+        if let Some(cheats) = &cheats {
+            cheats.apply(&mut chip8);
+        }
+    }
+
+    if let Some(path) = &args.dump_display {
+        fs::write(path, display_to_string(chip8.frame_buffer()))
+            .unwrap_or_else(|e| panic!("Failed to write {}: {e}", path.display()));
+    }
+    coverage_map::save(args.coverage.as_deref(), &chip8);
+    stats.save();
+    print_registers(&chip8, &symbols);
+}
+
+// Adds a breakpoint given as an address or symbol, optionally followed by
+// "if" and a condition, e.g. "draw_player if V3 == 0x1f".
+pub fn add_breakpoint(
+    chip8: &mut Chip8,
+    breakpoint: &str,
+    symbols: &Symbols,
+) -> Result<(), String> {
+    let (target, condition) = match breakpoint.split_once(" if ") {
+        Some((target, condition)) => (target.trim(), Some(condition)),
+        None => (breakpoint.trim(), None),
+    };
+    let addr = symbols
+        .resolve(target)
+        .ok_or_else(|| format!("No symbol or address {target} to break at"))?;
+    match condition {
+        Some(condition) => {
+            let condition = Condition::parse(condition)
+                .map_err(|e| format!("Invalid breakpoint condition \"{condition}\": {e}"))?;
+            chip8.add_conditional_breakpoint(addr, condition);
+        }
+        None => chip8.add_breakpoint(addr),
+    }
+    Ok(())
+}
+
+fn display_to_string(display: &FrameBuffer) -> String {
+    display
+        .to_bytes()
+        .chunks(display.width())
+        .map(|row| {
+            let mut line: String = row
+                .iter()
+                .map(|&pixel| if pixel == 1 { '#' } else { '.' })
+                .collect();
+            line.push('\n');
+            line
+        })
+        .collect()
+}
+
+fn print_registers(chip8: &Chip8, symbols: &Symbols) {
+    let state = chip8.state();
+    match symbols.name(state.pc) {
+        Some(name) => println!("PC: {:#05x} ({name})  I: {:#05x}", state.pc, state.i),
+        None => println!("PC: {:#05x}  I: {:#05x}", state.pc, state.i),
+    }
+    for (i, v) in state.v.iter().enumerate() {
+        print!("V{:X}: {:#04x}", i, v);
+        print!("{}", if i % 8 == 7 { "\n" } else { "  " });
+    }
+}
@@ -0,0 +1,127 @@
+use crate::database::Database;
+use crate::InfoArgs;
+use my_chip8::{disasm, Variant, START_ADDR};
+use std::path::Path;
+use std::process;
+
+// Mega-Chip ROMs switch the machine into Mega-Chip mode (0010) first thing.
+const MEGA_ON: [u8; 2] = [0x00, 0x10];
+
+// An instruction by the name it's usually written with, and whether an
+// opcode is one.
+type Pattern = (&'static str, fn(u16) -> bool);
+
+// Instructions only found in ROMs for one of the extended interpreters.
+const EXTENSIONS: [(&str, &[Pattern]); 3] = [
+    (
+        "XO-CHIP",
+        &[
+            ("00DN", |op| op & 0xfff0 == 0x00d0),
+            ("5XY2", |op| op & 0xf00f == 0x5002),
+            ("5XY3", |op| op & 0xf00f == 0x5003),
+            ("F000", |op| op == 0xf000),
+            ("FN01", |op| op & 0xf0ff == 0xf001),
+            ("F002", |op| op == 0xf002),
+            ("FX3A", |op| op & 0xf0ff == 0xf03a),
+        ],
+    ),
+    (
+        "SUPER-CHIP",
+        &[
+            ("00CN", |op| op & 0xfff0 == 0x00c0),
+            ("00FB", |op| op == 0x00fb),
+            ("00FC", |op| op == 0x00fc),
+            ("00FD", |op| op == 0x00fd),
+            ("00FE", |op| op == 0x00fe),
+            ("00FF", |op| op == 0x00ff),
+            ("FX30", |op| op & 0xf0ff == 0xf030),
+            ("FX75", |op| op & 0xf0ff == 0xf075),
+            ("FX85", |op| op & 0xf0ff == 0xf085),
+        ],
+    ),
+    (
+        "CHIP-8X",
+        &[
+            ("02A0", |op| op == 0x02a0),
+            ("EXF2", |op| op & 0xf0ff == 0xe0f2),
+            ("EXF5", |op| op & 0xf0ff == 0xe0f5),
+            ("FXF8", |op| op & 0xf0ff == 0xf0f8),
+            ("FXFB", |op| op & 0xf0ff == 0xf0fb),
+        ],
+    ),
+];
+
+// Prints what can be told about a ROM without running it.
+pub fn run(args: &InfoArgs) {
+    let rom = std::fs::read(&args.rom).unwrap_or_else(|e| {
+        eprintln!("Failed to load ROM {}: {e}", args.rom.display());
+        process::exit(1);
+    });
+
+    println!("File:      {}", file_name(&args.rom));
+    println!("Size:      {} bytes", rom.len());
+    println!("SHA-1:     {}", sha1_smol::Sha1::from(&rom).digest());
+    println!("CRC32:     {:08x}", crc32(&rom));
+    let known = Database::load(None)
+        .ok()
+        .flatten()
+        .and_then(|database| database.lookup(&rom));
+    if let Some(settings) = known {
+        println!("Title:     {}", settings.title);
+    }
+    println!("Platform:  {}", platform(&rom));
+    match rom.get(..2) {
+        Some(&[high, low]) => {
+            let opcode = u16::from_be_bytes([high, low]);
+            println!(
+                "Entry:     {START_ADDR:#05x}  {opcode:04x}  {}",
+                disasm::disassemble(opcode)
+            );
+        }
+        _ => println!("Entry:     none, the ROM is too short"),
+    }
+}
+
+// Guesses the machine from the extended instructions the ROM seems to use.
+// Code and data are mixed, so data that happens to look like one of them
+// can mislead it.
+fn platform(rom: &[u8]) -> String {
+    if rom.starts_with(&MEGA_ON) {
+        return "Mega-Chip".to_string();
+    }
+    if Variant::detect(rom) == Variant::HiRes {
+        return "two-page hi-res CHIP-8".to_string();
+    }
+
+    let opcodes: Vec<u16> = rom
+        .chunks_exact(2)
+        .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+        .collect();
+    for (name, instructions) in EXTENSIONS {
+        let used: Vec<&str> = instructions
+            .iter()
+            .filter(|(_, matches)| opcodes.iter().any(|&op| matches(op)))
+            .map(|&(pattern, _)| pattern)
+            .collect();
+        if !used.is_empty() {
+            return format!("{name} (uses {})", used.join(", "));
+        }
+    }
+    "CHIP-8".to_string()
+}
+
+// The CRC-32 used by zip and most ROM sets.
+pub fn crc32(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!0, |crc, &byte| {
+        (0..8).fold(crc ^ byte as u32, |crc, _| {
+            (crc >> 1) ^ (0xedb8_8320 & (crc & 1).wrapping_neg())
+        })
+    })
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name().map_or_else(
+        || path.display().to_string(),
+        |name| name.to_string_lossy().into_owned(),
+    )
+}
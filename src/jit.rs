@@ -0,0 +1,332 @@
+use crate::decode::{decode, Inst};
+use crate::{Chip8, Chip8Error, Quirks, SysPolicy, Timing, Variant, MEM_SIZE};
+use cranelift_codegen::ir::condcodes::IntCC;
+use cranelift_codegen::ir::{types, AbiParam, InstBuilder, MemFlagsData};
+use cranelift_codegen::settings::{self, Configurable};
+use cranelift_codegen::Context;
+use cranelift_frontend::{FunctionBuilder, FunctionBuilderContext, Variable};
+use cranelift_jit::{JITBuilder, JITModule};
+use cranelift_module::{default_libcall_names, Module};
+use std::fmt;
+
+const MAX_BLOCK_LEN: usize = 64;
+// Code for blocks whose memory was overwritten stays allocated until the
+// module is rebuilt, which happens once this many have piled up.
+const MAX_STALE_BLOCKS: usize = 1024;
+
+type BlockFn = unsafe extern "C" fn(v: *mut u8, i: *mut u16);
+
+/// An experimental backend that compiles straight-line runs of register
+/// instructions (0NNN, 6XNN, 7XNN, 8XYN, ANNN and FX1E) to native code with
+/// Cranelift. Everything else ends a block and goes through the interpreter,
+/// as does every instruction while a hook is set, coverage or history is
+/// tracked, or under VIP timing.
+///
+/// Blocks remember the bytes they were compiled from and are recompiled if
+/// the program overwrites them or the machine's quirks or variant change.
+/// 0NNN is only compiled while the machine ignores it.
+pub struct Jit {
+    module: JITModule,
+    ctx: Context,
+    func_ctx: FunctionBuilderContext,
+    // The quirks, variant, and 0NNN policy the blocks were compiled for.
+    quirks: Quirks,
+    variant: Variant,
+    sys: SysPolicy,
+    // Compiled blocks by start address.
+    blocks: Vec<Option<Block>>,
+    stale: usize,
+}
+
+// A block with no code marks an address whose first instruction can't be
+// compiled, so it isn't retried until the memory there changes.
+struct Block {
+    code: Option<BlockFn>,
+    len: u32,
+    source: Vec<u8>,
+}
+
+impl fmt::Debug for Jit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Jit")
+            .field("blocks", &self.blocks.iter().flatten().count())
+            .finish()
+    }
+}
+
+impl Jit {
+    pub fn new() -> Result<Jit, String> {
+        let module = new_module()?;
+        let ctx = module.make_context();
+        Ok(Jit {
+            module,
+            ctx,
+            func_ctx: FunctionBuilderContext::new(),
+            quirks: Quirks::default(),
+            variant: Variant::default(),
+            sys: SysPolicy::default(),
+            blocks: (0..MEM_SIZE).map(|_| None).collect(),
+            stale: 0,
+        })
+    }
+
+    /// Runs `cycles` instructions and returns whether any of them drew.
+    pub fn run(&mut self, chip8: &mut Chip8, cycles: u32) -> Result<bool, Chip8Error> {
+        let mut drawn = false;
+        let mut remaining = cycles;
+        while remaining > 0 {
+            // Blocks count one cycle per instruction and don't track
+            // coverage or history, so VIP timing, coverage, history, and
+            // hooks need the interpreter.
+            let interpreted = chip8.pre_hook.is_some()
+                || chip8.post_hook.is_some()
+                || chip8.coverage.is_some()
+                || chip8.history.is_recording()
+                || chip8.timing != Timing::Flat;
+            match self.block(chip8).filter(|_| !interpreted) {
+                Some((code, len)) if len <= remaining => {
+                    // SAFETY: blocks only touch the 16 registers and I.
+                    unsafe { code(chip8.v.as_mut_ptr(), &mut chip8.i) };
+                    for _ in 0..len {
+                        chip8.count_cycle();
+                    }
+                    chip8.pc += 2 * len as u16;
+                    chip8.draw_flag = false;
+                    remaining -= len;
+                }
+                _ => {
+                    chip8.run_cycle()?;
+                    drawn |= chip8.draw_flag;
+                    remaining -= 1;
+                }
+            }
+        }
+        Ok(drawn)
+    }
+
+    fn block(&mut self, chip8: &Chip8) -> Option<(BlockFn, u32)> {
+        if chip8.quirks != self.quirks || chip8.variant != self.variant || chip8.sys != self.sys {
+            self.reset().ok()?;
+            self.quirks = chip8.quirks;
+            self.variant = chip8.variant;
+            self.sys = chip8.sys;
+        }
+        let pc = chip8.pc as usize;
+        let slot = self.blocks.get(pc)?;
+        let current = slot.as_ref().is_some_and(|block| {
+            chip8.memory.get(pc..pc + block.source.len()) == Some(&block.source)
+        });
+        if !current {
+            if slot.as_ref().is_some_and(|block| block.code.is_some()) {
+                self.stale += 1;
+            }
+            if self.stale > MAX_STALE_BLOCKS {
+                self.reset().ok()?;
+            }
+            self.blocks[pc] = Some(self.compile(&chip8.memory, pc));
+        }
+
+        let block = self.blocks[pc].as_ref()?;
+        Some((block.code?, block.len))
+    }
+
+    fn compile(&mut self, memory: &[u8], start: usize) -> Block {
+        let mut insts = Vec::new();
+        let mut addr = start;
+        while insts.len() < MAX_BLOCK_LEN && addr + 1 < MEM_SIZE {
+            let opcode = u16::from_be_bytes([memory[addr], memory[addr + 1]]);
+            let inst = decode(opcode, self.variant);
+            if !compilable(inst, self.sys) {
+                break;
+            }
+            insts.push(inst);
+            addr += 2;
+        }
+
+        let end = if insts.is_empty() {
+            (start + 2).min(MEM_SIZE)
+        } else {
+            addr
+        };
+        let code = if insts.is_empty() {
+            None
+        } else {
+            self.codegen(&insts)
+        };
+        Block {
+            code,
+            len: insts.len() as u32,
+            source: memory[start..end].to_vec(),
+        }
+    }
+
+    // Returns None if Cranelift rejects the function.
+    fn codegen(&mut self, insts: &[Inst]) -> Option<BlockFn> {
+        let pointer = self.module.target_config().pointer_type();
+        let mut signature = self.module.make_signature();
+        signature.params.push(AbiParam::new(pointer));
+        signature.params.push(AbiParam::new(pointer));
+        let id = self.module.declare_anonymous_function(&signature).ok()?;
+        self.ctx.func.signature = signature;
+
+        let mut b = FunctionBuilder::new(&mut self.ctx.func, &mut self.func_ctx);
+        let entry = b.create_block();
+        b.append_block_params_for_function_params(entry);
+        b.switch_to_block(entry);
+        b.seal_block(entry);
+        let (v_ptr, i_ptr) = (b.block_params(entry)[0], b.block_params(entry)[1]);
+
+        // Registers live in variables for the whole block and are written
+        // back at the end.
+        let flags = MemFlagsData::trusted();
+        let v: [Variable; 16] = std::array::from_fn(|x| {
+            let var = b.declare_var(types::I8);
+            let value = b.ins().load(types::I8, flags, v_ptr, x as i32);
+            b.def_var(var, value);
+            var
+        });
+        let i = b.declare_var(types::I16);
+        let value = b.ins().load(types::I16, flags, i_ptr, 0);
+        b.def_var(i, value);
+
+        for &inst in insts {
+            // As in the interpreter, VF is written after VX.
+            match inst {
+                Inst::Sys(_) => {}
+                Inst::SetImm(x, nn) => {
+                    let value = b.ins().iconst(types::I8, nn as i64);
+                    b.def_var(v[x as usize], value);
+                }
+                Inst::AddImm(x, nn) => {
+                    let vx = b.use_var(v[x as usize]);
+                    let value = b.ins().iadd_imm_u(vx, nn as i64);
+                    b.def_var(v[x as usize], value);
+                }
+                Inst::SetReg(x, y) => {
+                    let vy = b.use_var(v[y as usize]);
+                    b.def_var(v[x as usize], vy);
+                }
+                Inst::Or(x, y) | Inst::And(x, y) | Inst::Xor(x, y) => {
+                    let (vx, vy) = (b.use_var(v[x as usize]), b.use_var(v[y as usize]));
+                    let value = match inst {
+                        Inst::Or(..) => b.ins().bor(vx, vy),
+                        Inst::And(..) => b.ins().band(vx, vy),
+                        _ => b.ins().bxor(vx, vy),
+                    };
+                    b.def_var(v[x as usize], value);
+                    if self.quirks.logic_resets_vf {
+                        let zero = b.ins().iconst(types::I8, 0);
+                        b.def_var(v[0xf], zero);
+                    }
+                }
+                Inst::AddReg(x, y) => {
+                    let (vx, vy) = (b.use_var(v[x as usize]), b.use_var(v[y as usize]));
+                    let sum = b.ins().iadd(vx, vy);
+                    let carry = b.ins().icmp(IntCC::UnsignedLessThan, sum, vx);
+                    b.def_var(v[x as usize], sum);
+                    b.def_var(v[0xf], carry);
+                }
+                Inst::Sub(x, y) | Inst::ReverseSub(x, y) => {
+                    let (vx, vy) = (b.use_var(v[x as usize]), b.use_var(v[y as usize]));
+                    let (lhs, rhs) = match inst {
+                        Inst::Sub(..) => (vx, vy),
+                        _ => (vy, vx),
+                    };
+                    let difference = b.ins().isub(lhs, rhs);
+                    let no_borrow = b.ins().icmp(IntCC::UnsignedGreaterThanOrEqual, lhs, rhs);
+                    b.def_var(v[x as usize], difference);
+                    b.def_var(v[0xf], no_borrow);
+                }
+                Inst::ShiftRight(x, y) => {
+                    let source = if self.quirks.shift_vy { y } else { x };
+                    let vs = b.use_var(v[source as usize]);
+                    let flag = b.ins().band_imm_u(vs, 1);
+                    let value = b.ins().ushr_imm_u(vs, 1);
+                    b.def_var(v[x as usize], value);
+                    b.def_var(v[0xf], flag);
+                }
+                Inst::ShiftLeft(x, y) => {
+                    let source = if self.quirks.shift_vy { y } else { x };
+                    let vs = b.use_var(v[source as usize]);
+                    let flag = b.ins().ushr_imm_u(vs, 7);
+                    let value = b.ins().ishl_imm_u(vs, 1);
+                    b.def_var(v[x as usize], value);
+                    b.def_var(v[0xf], flag);
+                }
+                Inst::SetI(nnn) => {
+                    let value = b.ins().iconst(types::I16, nnn as i64);
+                    b.def_var(i, value);
+                }
+                Inst::AddI(x) => {
+                    let vx = b.use_var(v[x as usize]);
+                    let offset = b.ins().uextend(types::I16, vx);
+                    let index = b.use_var(i);
+                    let value = b.ins().iadd(index, offset);
+                    b.def_var(i, value);
+                }
+                _ => unreachable!("{inst:?} is not compilable"),
+            }
+        }
+
+        for (x, &var) in v.iter().enumerate() {
+            let value = b.use_var(var);
+            b.ins().store(flags, value, v_ptr, x as i32);
+        }
+        let value = b.use_var(i);
+        b.ins().store(flags, value, i_ptr, 0);
+        b.ins().return_(&[]);
+        b.finalize(self.module.target_config());
+
+        let defined = self.module.define_function(id, &mut self.ctx);
+        self.module.clear_context(&mut self.ctx);
+        defined.ok()?;
+        self.module.finalize_definitions().ok()?;
+        let code = self.module.get_finalized_function(id);
+        // SAFETY: the function was built with the BlockFn signature above.
+        Some(unsafe { std::mem::transmute::<*const u8, BlockFn>(code) })
+    }
+
+    fn reset(&mut self) -> Result<(), String> {
+        let module = std::mem::replace(&mut self.module, new_module()?);
+        self.blocks.fill_with(|| None);
+        self.stale = 0;
+        // SAFETY: every pointer into the old module was in `blocks`.
+        unsafe { module.free_memory() };
+        Ok(())
+    }
+}
+
+fn new_module() -> Result<JITModule, String> {
+    let mut flags = settings::builder();
+    flags.set("opt_level", "speed").map_err(|e| e.to_string())?;
+    let isa = cranelift_native::builder()
+        .map_err(|e| format!("Unsupported host for the JIT: {e}"))?
+        .finish(settings::Flags::new(flags))
+        .map_err(|e| e.to_string())?;
+    Ok(JITModule::new(JITBuilder::with_isa(
+        isa,
+        default_libcall_names(),
+    )))
+}
+
+fn compilable(inst: Inst, sys: SysPolicy) -> bool {
+    if let Inst::Sys(_) = inst {
+        return sys == SysPolicy::Ignore;
+    }
+    matches!(
+        inst,
+        Inst::SetImm(..)
+            | Inst::AddImm(..)
+            | Inst::SetReg(..)
+            | Inst::Or(..)
+            | Inst::And(..)
+            | Inst::Xor(..)
+            | Inst::AddReg(..)
+            | Inst::Sub(..)
+            | Inst::ShiftRight(..)
+            | Inst::ReverseSub(..)
+            | Inst::ShiftLeft(..)
+            | Inst::SetI(..)
+            | Inst::AddI(..)
+    )
+}
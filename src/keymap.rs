@@ -0,0 +1,45 @@
+// The keyboard keys that stand in for the hex keypad, a 4x4 block on the left
+// of a QWERTY keyboard laid out like the COSMAC VIP's keypad:
+//
+//   1 2 3 4      1 2 3 C
+//   Q W E R  ->  4 5 6 D
+//   A S D F      7 8 9 E
+//   Z X C V      A 0 B F
+//
+// Frontends look their key codes up by these names.
+const KEYS: [(char, u8); 16] = [
+    ('1', 0x1),
+    ('2', 0x2),
+    ('3', 0x3),
+    ('4', 0xc),
+    ('Q', 0x4),
+    ('W', 0x5),
+    ('E', 0x6),
+    ('R', 0xd),
+    ('A', 0x7),
+    ('S', 0x8),
+    ('D', 0x9),
+    ('F', 0xe),
+    ('Z', 0xa),
+    ('X', 0x0),
+    ('C', 0xb),
+    ('V', 0xf),
+];
+
+pub fn char_to_key(c: char) -> Option<u8> {
+    let c = c.to_ascii_uppercase();
+    KEYS.iter()
+        .find(|&&(name, _)| name == c)
+        .map(|&(_, key)| key)
+}
+
+// The key for a keyboard key named by a single character, as SDL, egui and
+// winit name the digit and letter keys.
+#[cfg(any(feature = "sdl", feature = "pixels", feature = "egui"))]
+pub fn name_to_key(name: &str) -> Option<u8> {
+    let mut chars = name.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => char_to_key(c),
+        _ => None,
+    }
+}
@@ -0,0 +1,104 @@
+use crate::palette::Palette;
+use crate::text::{self, TEXT_SCALE, TEXT_WIDTH};
+use my_chip8::{DISPLAY_HEIGHT, DISPLAY_WIDTH};
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::Canvas;
+use sdl2::video::Window;
+
+// The keys in the COSMAC VIP's hex keypad layout.
+const LAYOUT: [[u8; 4]; 4] = [
+    [0x1, 0x2, 0x3, 0xc],
+    [0x4, 0x5, 0x6, 0xd],
+    [0x7, 0x8, 0x9, 0xe],
+    [0xa, 0x0, 0xb, 0xf],
+];
+// Each key is a square a quarter of the display's height, in logical pixels.
+const KEY_SIZE: i32 = DISPLAY_HEIGHT as i32 / 4;
+pub const KEYPAD_WIDTH: u32 = 4 * KEY_SIZE as u32;
+const BORDER: i32 = 2;
+const GAP: i32 = 3;
+const LABEL_SIZE: u32 = 6;
+
+// A 4x4 keypad drawn to the right of the display that can be clicked or
+// tapped instead of using the keyboard.
+#[derive(Debug, Default)]
+pub struct Keypad {
+    pub shown: bool,
+    held: Option<u8>,
+}
+
+impl Keypad {
+    pub fn new(shown: bool) -> Keypad {
+        Keypad { shown, held: None }
+    }
+
+    // The logical size of the window's contents with or without the keypad.
+    pub fn logical_size(&self) -> (u32, u32) {
+        let width = DISPLAY_WIDTH as u32 + if self.shown { KEYPAD_WIDTH } else { 0 };
+        (width, DISPLAY_HEIGHT as u32)
+    }
+
+    // Returns the key under (x, y), in logical pixels, and holds it until
+    // `release` is called.
+    pub fn press(&mut self, x: i32, y: i32) -> Option<u8> {
+        if !self.shown || x < DISPLAY_WIDTH as i32 || y < 0 {
+            return None;
+        }
+        let col = (x - DISPLAY_WIDTH as i32) / KEY_SIZE;
+        let row = y / KEY_SIZE;
+        let key = *LAYOUT.get(row as usize)?.get(col as usize)?;
+        self.held = Some(key);
+        Some(key)
+    }
+
+    pub fn release(&mut self) -> Option<u8> {
+        self.held.take()
+    }
+
+    // Draws the keypad with the keys in `keys` lit, without presenting.
+    pub fn draw(&self, canvas: &mut Canvas<Window>, keys: u16, palette: &Palette) {
+        if !self.shown {
+            return;
+        }
+        let foreground = Color::from(palette.foreground);
+        let background = Color::from(palette.background);
+        let size = KEY_SIZE * TEXT_SCALE as i32;
+        for (row, keys_in_row) in LAYOUT.iter().enumerate() {
+            for (col, &key) in keys_in_row.iter().enumerate() {
+                let x = TEXT_WIDTH + col as i32 * size + GAP;
+                let y = row as i32 * size + GAP;
+                let inner = size - 2 * GAP;
+                let pressed = keys & (1 << key) != 0;
+
+                text::fill_rect(
+                    canvas,
+                    Rect::new(x, y, inner as u32, inner as u32),
+                    foreground,
+                );
+                if !pressed {
+                    text::fill_rect(
+                        canvas,
+                        Rect::new(
+                            x + BORDER,
+                            y + BORDER,
+                            (inner - 2 * BORDER) as u32,
+                            (inner - 2 * BORDER) as u32,
+                        ),
+                        background,
+                    );
+                }
+
+                let label = format!("{key:X}");
+                text::draw(
+                    canvas,
+                    &label,
+                    x + (inner - text::width(&label, LABEL_SIZE)) / 2,
+                    y + (inner - text::height(LABEL_SIZE)) / 2,
+                    LABEL_SIZE,
+                    if pressed { background } else { foreground },
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use std::time::{Duration, Instant};
+
+// Toggles an 8x8 block every time any key is released, since that is when
+// FX0A completes.
+pub const PROBE_ROM: [u8; 22] = [
+    0x00, 0xe0, // 200: CLS
+    0xa2, 0x0e, // 202: LD I, 0x20e
+    0x61, 0x1c, // 204: LD V1, 28
+    0x62, 0x0c, // 206: LD V2, 12
+    0xf0, 0x0a, // 208: LD V0, K
+    0xd1, 0x28, // 20a: DRW V1, V2, 8
+    0x12, 0x08, // 20c: JP 0x208
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, // 20e: sprite
+];
+
+#[derive(Debug, Default)]
+pub struct LatencyMeter {
+    pending: Option<Instant>,
+    samples: Vec<Duration>,
+}
+
+impl LatencyMeter {
+    pub fn key_released(&mut self) {
+        self.pending.get_or_insert_with(Instant::now);
+    }
+
+    pub fn frame_presented(&mut self) {
+        if let Some(start) = self.pending.take() {
+            let latency = start.elapsed();
+            println!("Input latency: {:.2} ms", as_millis(latency));
+            self.samples.push(latency);
+        }
+    }
+
+    pub fn report(&self) {
+        if self.samples.is_empty() {
+            println!("No latency samples recorded");
+            return;
+        }
+
+        let min = self.samples.iter().min().unwrap();
+        let max = self.samples.iter().max().unwrap();
+        let avg = self.samples.iter().sum::<Duration>() / self.samples.len() as u32;
+        println!(
+            "Input latency over {} samples: min {:.2} ms, avg {:.2} ms, max {:.2} ms",
+            self.samples.len(),
+            as_millis(*min),
+            as_millis(avg),
+            as_millis(*max),
+        );
+    }
+}
+
+fn as_millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
@@ -1,104 +1,892 @@
-use rand::Rng;
-use std::fs;
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+// Hosted targets still build the cdylib, which needs std's panic handler and
+// allocator; bare-metal targets only build the rlib.
+#[cfg(all(not(feature = "std"), not(target_os = "none")))]
+extern crate std;
+
+use alloc::boxed::Box;
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use clock::TimerClock;
+use core::fmt;
+use core::time::Duration;
+use debugger::{Breakpoint, Condition, Event, History};
+use decode::{decode, Inst};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+#[cfg(feature = "std")]
+use std::path::PathBuf;
+#[cfg(feature = "std")]
+use std::{fs, io};
+
+pub mod analysis;
+mod builder;
+pub mod builtin;
+mod clock;
+mod color;
+mod coverage;
+pub mod debugger;
+mod decode;
+pub mod decompile;
+pub mod disasm;
+mod error;
+mod fonts;
+mod frame_buffer;
+#[cfg(feature = "jit")]
+mod jit;
+#[cfg(feature = "libretro")]
+mod libretro;
+mod policy;
+#[cfg(feature = "pyo3")]
+mod python;
+mod quirks;
+mod rng;
+#[cfg(feature = "std")]
+mod shared;
+mod state;
+mod symbols;
+mod timing;
+mod variant;
+#[cfg(all(target_arch = "wasm32", feature = "std"))]
+mod wasm;
+
+pub use builder::{BuildError, Chip8Builder};
+#[cfg(feature = "std")]
+pub use clock::SystemClock;
+pub use clock::{Clock, ManualClock};
+pub use color::{ColorMap, BACKGROUND_COLORS, FOREGROUND_COLORS};
+pub use coverage::Coverage;
+pub use error::{Chip8Error, ErrorKind};
+pub use fonts::{FontSet, LARGE_FONT_SIZE, SMALL_FONT_SIZE};
+pub use frame_buffer::FrameBuffer;
+#[cfg(feature = "jit")]
+pub use jit::Jit;
+pub use policy::{BoundsPolicy, SysPolicy};
+pub use quirks::Quirks;
+pub use rng::RandomSource;
+#[cfg(feature = "std")]
+pub use shared::SharedChip8;
+pub use state::{StateError, STATE_SIZE};
+pub use symbols::{SymbolError, Symbols};
+pub use timing::{Timing, VIP_CYCLES_PER_FRAME};
+pub use variant::Variant;
 
 pub const CYCLES_PER_SECOND: f64 = 600.0;
+/// The default number of instructions per 60Hz frame.
+pub const CYCLES_PER_FRAME: u32 = (CYCLES_PER_SECOND / TIMER_FREQ) as u32;
+/// The number of SCHIP persistent flag registers, used by FX75 and FX85.
+pub const FLAG_REGISTERS: usize = 8;
+/// The size of the standard display. Use `Chip8::display_size` for the size
+/// of the current one.
 pub const DISPLAY_WIDTH: usize = 64;
 pub const DISPLAY_HEIGHT: usize = 32;
 const KEYPAD_SIZE: usize = 16;
-const MEM_SIZE: usize = 4096;
+/// The size of memory, the 4K address space.
+pub const MEM_SIZE: usize = 4096;
 const NUM_REGISTERS: usize = 16;
-const SPRITE_END: usize = 0x9f;
 const SPRITE_SIZE: u16 = 5;
 const SPRITE_START: usize = 0x50;
+const LARGE_SPRITE_SIZE: u16 = 10;
+const LARGE_SPRITE_START: usize = SPRITE_START + SMALL_FONT_SIZE;
 const STACK_SIZE: usize = 16;
-const START_ADDR: u16 = 0x200;
+/// Where ROMs are loaded and start running unless `Chip8::set_start_address`
+/// says otherwise.
+pub const START_ADDR: u16 = 0x200;
+/// The start address of ROMs for the ETI-660, which kept its interpreter below
+/// 0x600.
+pub const ETI_660_START_ADDR: u16 = 0x600;
+/// The start address of CHIP-8X ROMs, which sit above the larger interpreter.
+pub const CHIP_8X_START_ADDR: u16 = 0x300;
 const TIMER_FREQ: f64 = 60.0;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CpuState {
+    pub pc: u16,
+    pub i: u16,
+    pub sp: u8,
+    pub v: [u8; NUM_REGISTERS],
+    pub stack: [u16; STACK_SIZE],
+    pub delay_timer: u8,
+    pub sound_timer: u8,
+}
+
+/// What a `run_cycles` batch did.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BatchResult {
+    /// How many instructions ran.
+    pub cycles: u32,
+    /// Whether any of them drew to the display.
+    pub drawn: bool,
+    /// The breakpoint the batch stopped at, if it stopped early.
+    pub breakpoint: Option<u16>,
+}
+
+/// The outcome of one `run_frame`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Frame<'a> {
+    /// The display at the end of the frame, as from `Chip8::frame_buffer`.
+    pub frame_buffer: &'a FrameBuffer,
+    /// Whether any instruction in the frame drew to the display.
+    pub drawn: bool,
+    /// Whether the buzzer should sound until the next frame.
+    pub sound: bool,
+}
+
+impl Frame<'_> {
+    /// The display as one byte per pixel, as from `Chip8::display`.
+    pub fn display(&self) -> Vec<u8> {
+        self.frame_buffer.to_bytes()
+    }
+}
+
+/// A CHIP-8 machine.
+///
+/// Clones and comparisons cover the machine itself: memory, display,
+/// registers, timers, keys, and configuration. Hooks and the 0NNN handler
+/// belong to the host and aren't cloned, and neither they, the RNG,
+/// breakpoints, coverage, nor the rewind history are compared. A clone copies
+/// the RNG, so it draws the same random numbers as the original, and shares
+/// its clock.
 pub struct Chip8 {
     memory: [u8; MEM_SIZE],
-    display: [u8; DISPLAY_WIDTH * DISPLAY_HEIGHT],
+    // Decoded instructions by address, cleared wherever memory is written.
+    decoded: Vec<(u16, Inst)>,
+    display: FrameBuffer,
     pc: u16,
+    // Where ROMs are loaded and PC starts.
+    start_addr: u16,
     i: u16,
     stack: [u16; STACK_SIZE],
     sp: u8,
     delay_timer: u8,
     sound_timer: u8,
     v: [u8; NUM_REGISTERS],
+    // The SCHIP flag registers, also written through to a file if one is set.
+    flags: [u8; FLAG_REGISTERS],
+    #[cfg(feature = "std")]
+    flag_file: Option<PathBuf>,
     draw_flag: bool,
     keypad: [u8; KEYPAD_SIZE],
-    internal_timer: f64,
+    released_keys: u16,
+    waiting_for_key: bool,
+    quirks: Quirks,
+    variant: Variant,
+    // CHIP-8X's colors, second keypad, and the latches on its I/O port.
+    colors: ColorMap,
+    second_keys: u16,
+    io_output: u8,
+    io_input: u8,
+    bounds: BoundsPolicy,
+    sys: SysPolicy,
+    sys_handler: Option<SysHandler>,
+    // Addresses that 0NNN has already warned about.
+    warned_sys: Vec<u16>,
+    // Whether the last instruction was stopped by `BoundsPolicy::Halt`.
+    halted: bool,
+    cycles_per_frame: u32,
+    timing: Timing,
+    // Where the timers take their time from, if not the instruction count.
+    clock: Option<TimerClock>,
+    // Cycles run since the timers last ticked, in the timing's units: one per
+    // instruction, or machine cycles under VIP timing.
+    frame_cycle: u32,
+    // What the last instruction cost, in the same units.
+    last_cost: u32,
+    rng: Rng,
+    pre_hook: Option<Hook>,
+    post_hook: Option<Hook>,
+    sound_hook: Option<SoundHook>,
+    breakpoints: Vec<Breakpoint>,
+    event_breakpoints: Vec<Event>,
+    coverage: Option<Coverage>,
+    history: History,
+}
+
+struct Rng(Box<dyn RandomSource>);
+
+impl Clone for Rng {
+    fn clone(&self) -> Rng {
+        Rng(self.0.boxed_clone())
+    }
+}
+
+type HookFn = dyn FnMut(&Chip8, u16) + Send;
+
+struct Hook(Box<HookFn>);
+
+type SoundHookFn = dyn FnMut(bool) + Send;
+
+struct SoundHook(Box<SoundHookFn>);
+
+type SysHandlerFn = dyn FnMut(&mut Chip8, u16) + Send;
+
+struct SysHandler(Box<SysHandlerFn>);
+
+#[cfg(feature = "std")]
+impl Default for Chip8 {
+    fn default() -> Chip8 {
+        Chip8::new()
+    }
+}
+
+impl Clone for Chip8 {
+    fn clone(&self) -> Chip8 {
+        Chip8 {
+            memory: self.memory,
+            decoded: self.decoded.clone(),
+            display: self.display,
+            pc: self.pc,
+            start_addr: self.start_addr,
+            i: self.i,
+            stack: self.stack,
+            sp: self.sp,
+            delay_timer: self.delay_timer,
+            sound_timer: self.sound_timer,
+            v: self.v,
+            flags: self.flags,
+            #[cfg(feature = "std")]
+            flag_file: self.flag_file.clone(),
+            draw_flag: self.draw_flag,
+            keypad: self.keypad,
+            released_keys: self.released_keys,
+            waiting_for_key: self.waiting_for_key,
+            quirks: self.quirks,
+            variant: self.variant,
+            colors: self.colors,
+            second_keys: self.second_keys,
+            io_output: self.io_output,
+            io_input: self.io_input,
+            bounds: self.bounds,
+            sys: self.sys,
+            sys_handler: None,
+            warned_sys: self.warned_sys.clone(),
+            halted: self.halted,
+            cycles_per_frame: self.cycles_per_frame,
+            timing: self.timing,
+            clock: self.clock.clone(),
+            frame_cycle: self.frame_cycle,
+            last_cost: self.last_cost,
+            rng: self.rng.clone(),
+            pre_hook: None,
+            post_hook: None,
+            sound_hook: None,
+            breakpoints: self.breakpoints.clone(),
+            event_breakpoints: self.event_breakpoints.clone(),
+            coverage: self.coverage.clone(),
+            history: self.history.clone(),
+        }
+    }
+}
+
+impl PartialEq for Chip8 {
+    fn eq(&self, other: &Chip8) -> bool {
+        self.memory == other.memory
+            && self.display == other.display
+            && self.pc == other.pc
+            && self.start_addr == other.start_addr
+            && self.i == other.i
+            && self.stack == other.stack
+            && self.sp == other.sp
+            && self.delay_timer == other.delay_timer
+            && self.sound_timer == other.sound_timer
+            && self.v == other.v
+            && self.flags == other.flags
+            && self.draw_flag == other.draw_flag
+            && self.keypad == other.keypad
+            && self.released_keys == other.released_keys
+            && self.waiting_for_key == other.waiting_for_key
+            && self.quirks == other.quirks
+            && self.variant == other.variant
+            && self.colors == other.colors
+            && self.second_keys == other.second_keys
+            && self.io_output == other.io_output
+            && self.io_input == other.io_input
+            && self.bounds == other.bounds
+            && self.sys == other.sys
+            && self.halted == other.halted
+            && self.cycles_per_frame == other.cycles_per_frame
+            && self.timing == other.timing
+            && self.frame_cycle == other.frame_cycle
+            && self.last_cost == other.last_cost
+    }
+}
+
+impl Eq for Chip8 {}
+
+// The registers and settings, leaving out memory and the display, which are
+// too big to read this way.
+impl fmt::Debug for Chip8 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Chip8")
+            .field("pc", &format_args!("{:#05x}", self.pc))
+            .field("opcode", &format_args!("{:04X}", self.current_opcode()))
+            .field("i", &format_args!("{:#05x}", self.i))
+            .field("v", &format_args!("{:02X?}", self.v))
+            .field(
+                "stack",
+                &format_args!("{:03X?}", &self.stack[..self.sp as usize]),
+            )
+            .field("delay_timer", &self.delay_timer)
+            .field("sound_timer", &self.sound_timer)
+            .field("waiting_for_key", &self.waiting_for_key)
+            .field("halted", &self.halted)
+            .field("variant", &self.variant)
+            .field("quirks", &self.quirks)
+            .field("cycles_per_frame", &self.cycles_per_frame)
+            .field("timing", &self.timing)
+            .finish_non_exhaustive()
+    }
 }
 
 impl Chip8 {
+    #[cfg(feature = "std")]
     pub fn new() -> Chip8 {
-        let mut memory = [0; MEM_SIZE];
-
-        let font = [
-            0xf0, 0x90, 0x90, 0x90, 0xf0, // 0
-            0x20, 0x60, 0x20, 0x20, 0x70, // 1
-            0xf0, 0x10, 0xf0, 0x80, 0xf0, // 2
-            0xf0, 0x10, 0xf0, 0x10, 0xf0, // 3
-            0x90, 0x90, 0xf0, 0x10, 0x10, // 4
-            0xf0, 0x80, 0xf0, 0x10, 0xf0, // 5
-            0xf0, 0x80, 0xf0, 0x90, 0xf0, // 6
-            0xf0, 0x10, 0x20, 0x40, 0x40, // 7
-            0xf0, 0x90, 0xf0, 0x90, 0xf0, // 8
-            0xf0, 0x90, 0xf0, 0x10, 0xf0, // 9
-            0xf0, 0x90, 0xf0, 0x90, 0x90, // A
-            0xe0, 0x90, 0xe0, 0x90, 0xe0, // B
-            0xf0, 0x80, 0x80, 0x80, 0xf0, // C
-            0xe0, 0x90, 0x90, 0x90, 0xe0, // D
-            0xf0, 0x80, 0xf0, 0x80, 0xf0, // E
-            0xf0, 0x80, 0xf0, 0x80, 0x80, // F
-        ];
-
-        (SPRITE_START..=SPRITE_END)
-            .into_iter()
-            .zip(font.iter())
-            .for_each(|(i, &d)| memory[i] = d);
+        Chip8::with_rng(StdRng::from_entropy())
+    }
 
-        Chip8 {
-            memory,
-            display: [0; DISPLAY_WIDTH * DISPLAY_HEIGHT],
+    /// Creates an interpreter whose CXNN results come from a fixed seed.
+    ///
+    /// Two machines created with the same seed that load the same ROM and
+    /// receive the same key input on the same cycles produce identical runs.
+    pub fn with_seed(seed: u64) -> Chip8 {
+        Chip8::with_rng(StdRng::seed_from_u64(seed))
+    }
+
+    /// Creates an interpreter that draws CXNN results from `rng`, which may be
+    /// a closure such as `|| 0x42` for a fixed sequence.
+    pub fn with_rng(rng: impl RandomSource + 'static) -> Chip8 {
+        let mut chip8 = Chip8 {
+            memory: [0; MEM_SIZE],
+            decoded: vec![(0, Inst::Undecoded); MEM_SIZE],
+            display: FrameBuffer::new(DISPLAY_WIDTH, DISPLAY_HEIGHT),
             pc: START_ADDR,
+            start_addr: START_ADDR,
             i: 0,
             stack: [0; STACK_SIZE],
             delay_timer: 0,
             sound_timer: 0,
             v: [0; NUM_REGISTERS],
+            flags: [0; FLAG_REGISTERS],
+            #[cfg(feature = "std")]
+            flag_file: None,
             sp: 0,
             draw_flag: false,
             keypad: [0; KEYPAD_SIZE],
-            internal_timer: 0.0,
+            released_keys: 0,
+            waiting_for_key: false,
+            quirks: Quirks::default(),
+            variant: Variant::default(),
+            colors: ColorMap::default(),
+            second_keys: 0,
+            io_output: 0,
+            io_input: 0,
+            bounds: BoundsPolicy::default(),
+            sys: SysPolicy::default(),
+            sys_handler: None,
+            warned_sys: Vec::new(),
+            halted: false,
+            cycles_per_frame: CYCLES_PER_FRAME,
+            timing: Timing::default(),
+            clock: None,
+            frame_cycle: 0,
+            last_cost: 1,
+            rng: Rng(Box::new(rng)),
+            pre_hook: None,
+            post_hook: None,
+            sound_hook: None,
+            breakpoints: Vec::new(),
+            event_breakpoints: Vec::new(),
+            coverage: None,
+            history: History::default(),
+        };
+        chip8.set_font(FontSet::default());
+        chip8
+    }
+
+    pub fn set_rng(&mut self, rng: impl RandomSource + 'static) {
+        self.rng = Rng(Box::new(rng));
+    }
+
+    /// Sets a callback that runs before each instruction executes, with the
+    /// program counter still pointing at the opcode it is given.
+    pub fn set_hook(&mut self, hook: impl FnMut(&Chip8, u16) + Send + 'static) {
+        self.pre_hook = Some(Hook(Box::new(hook)));
+    }
+
+    /// Sets a callback that runs after each instruction executes.
+    pub fn set_post_hook(&mut self, hook: impl FnMut(&Chip8, u16) + Send + 'static) {
+        self.post_hook = Some(Hook(Box::new(hook)));
+    }
+
+    /// Sets a callback that runs with `true` as soon as the sound timer is
+    /// set from zero, so the buzzer should start, and with `false` as soon as
+    /// it runs out or is cleared, so frontends needn't poll it every frame.
+    pub fn set_sound_hook(&mut self, hook: impl FnMut(bool) + Send + 'static) {
+        self.sound_hook = Some(SoundHook(Box::new(hook)));
+    }
+
+    /// Removes the callbacks set with `set_hook`, `set_post_hook`, and
+    /// `set_sound_hook`.
+    pub fn clear_hooks(&mut self) {
+        self.pre_hook = None;
+        self.post_hook = None;
+        self.sound_hook = None;
+    }
+
+    /// Makes `run_cycles` stop before executing the instruction at `addr`.
+    pub fn add_breakpoint(&mut self, addr: u16) {
+        self.set_breakpoint(addr, None);
+    }
+
+    /// Makes `run_cycles` stop before executing the instruction at `addr`
+    /// when `condition` holds, replacing any breakpoint already there.
+    pub fn add_conditional_breakpoint(&mut self, addr: u16, condition: Condition) {
+        self.set_breakpoint(addr, Some(condition));
+    }
+
+    fn set_breakpoint(&mut self, addr: u16, condition: Option<Condition>) {
+        self.remove_breakpoint(addr);
+        self.breakpoints.push(Breakpoint { addr, condition });
+    }
+
+    pub fn remove_breakpoint(&mut self, addr: u16) {
+        self.breakpoints.retain(|bp| bp.addr != addr);
+    }
+
+    pub fn breakpoints(&self) -> &[Breakpoint] {
+        &self.breakpoints
+    }
+
+    /// Makes `run_cycles` stop before executing any instruction that causes
+    /// `event`, wherever it is.
+    pub fn add_event_breakpoint(&mut self, event: Event) {
+        if !self.event_breakpoints.contains(&event) {
+            self.event_breakpoints.push(event);
         }
     }
 
+    pub fn remove_event_breakpoint(&mut self, event: Event) {
+        self.event_breakpoints.retain(|&e| e != event);
+    }
+
+    pub fn event_breakpoints(&self) -> &[Event] {
+        &self.event_breakpoints
+    }
+
+    /// Whether a breakpoint stops the instruction at PC, its condition
+    /// holding if it has one, or the instruction causes an event with a
+    /// breakpoint.
+    pub fn at_breakpoint(&self) -> bool {
+        let at_event = !self.event_breakpoints.is_empty()
+            && Event::of(self.current_opcode())
+                .is_some_and(|e| self.event_breakpoints.contains(&e));
+        at_event
+            || self
+                .breakpoints
+                .iter()
+                .find(|bp| bp.addr == self.pc)
+                .is_some_and(|bp| bp.hit(&self.state()))
+    }
+
+    /// Starts tracking which bytes of memory the program executes, reads,
+    /// and writes, from scratch, or stops tracking.
+    pub fn set_coverage(&mut self, enabled: bool) {
+        self.coverage = enabled.then(Coverage::new);
+    }
+
+    pub fn coverage(&self) -> Option<&Coverage> {
+        self.coverage.as_ref()
+    }
+
+    /// Keeps the state from before each of the last `depth` instructions, so
+    /// `step_back` can return to them. 0, the default, keeps none.
+    pub fn set_history_depth(&mut self, depth: usize) {
+        self.history.set_depth(depth);
+    }
+
+    /// How many instructions `step_back` can undo.
+    pub fn history_len(&self) -> usize {
+        self.history.len()
+    }
+
+    /// Restores the machine to how it was before the last instruction,
+    /// returning false if no earlier state is kept. Like `load_state`, this
+    /// leaves alone what save states don't hold, such as the RNG, so a CXNN
+    /// run again may draw a different number.
+    pub fn step_back(&mut self) -> bool {
+        match self.history.pop() {
+            Some(state) => {
+                self.load_state(&state)
+                    .expect("history holds this machine's own states");
+                true
+            }
+            None => false,
+        }
+    }
+
+    #[cfg(feature = "std")]
     pub fn load_rom(&mut self, path: &str) {
         let rom = fs::read(path).expect("Failed to load ROM");
-        let start_addr = START_ADDR as usize;
-        self.memory[start_addr..start_addr + rom.len()].copy_from_slice(&rom);
+        self.load_rom_bytes(&rom);
+    }
+
+    /// Copies `rom` into memory at the start address.
+    ///
+    /// # Panics
+    ///
+    /// If the ROM doesn't fit between the start address and the end of memory.
+    pub fn load_rom_bytes(&mut self, rom: &[u8]) {
+        let start_addr = self.start_addr as usize;
+        self.memory[start_addr..start_addr + rom.len()].copy_from_slice(rom);
+        self.invalidate(start_addr, rom.len());
+    }
+
+    /// Replaces the hex digits below the program with `font`. Without large
+    /// digits, the memory FX30 points into is cleared.
+    pub fn set_font(&mut self, font: FontSet) {
+        let large = font.large.unwrap_or([0; LARGE_FONT_SIZE]);
+        self.memory[SPRITE_START..LARGE_SPRITE_START].copy_from_slice(&font.small);
+        self.memory[LARGE_SPRITE_START..LARGE_SPRITE_START + LARGE_FONT_SIZE]
+            .copy_from_slice(&large);
+        self.invalidate(SPRITE_START, SMALL_FONT_SIZE + LARGE_FONT_SIZE);
+    }
+
+    /// Sets where `load_rom_bytes` loads ROMs and moves PC there, for ROMs
+    /// that don't start at `START_ADDR`.
+    ///
+    /// # Panics
+    ///
+    /// If `addr` is outside memory.
+    pub fn set_start_address(&mut self, addr: u16) {
+        assert!(
+            (addr as usize) < MEM_SIZE,
+            "start address {addr:#x} is outside memory"
+        );
+        self.start_addr = addr;
+        self.pc = addr;
+    }
+
+    pub fn start_address(&self) -> u16 {
+        self.start_addr
+    }
+
+    /// The largest ROM that fits between the start address and the end of
+    /// memory.
+    pub fn max_rom_size(&self) -> usize {
+        MEM_SIZE - self.start_addr as usize
+    }
+
+    pub fn set_quirks(&mut self, quirks: Quirks) {
+        self.quirks = quirks;
+    }
+
+    pub fn quirks(&self) -> Quirks {
+        self.quirks
+    }
+
+    /// Switches the instruction set to `variant`'s and resets its colors. The
+    /// display is cleared if its size changes.
+    pub fn set_variant(&mut self, variant: Variant) {
+        self.variant = variant;
+        self.colors = ColorMap::default();
+        self.invalidate(0, MEM_SIZE);
+        let (width, height) = variant.display_size();
+        if self.display.size() != (width, height) {
+            self.display = FrameBuffer::new(width, height);
+            self.draw_flag = true;
+        }
+    }
+
+    pub fn variant(&self) -> Variant {
+        self.variant
+    }
+
+    /// The colors to draw the display in, for CHIP-8X. Other variants are
+    /// monochrome and have none.
+    pub fn color_map(&self) -> Option<&ColorMap> {
+        (self.variant == Variant::Chip8X).then_some(&self.colors)
+    }
+
+    pub fn set_bounds_policy(&mut self, bounds: BoundsPolicy) {
+        self.bounds = bounds;
+    }
+
+    pub fn bounds_policy(&self) -> BoundsPolicy {
+        self.bounds
+    }
+
+    pub fn set_sys_policy(&mut self, sys: SysPolicy) {
+        self.sys = sys;
     }
 
-    pub fn run_cycle(&mut self) {
+    pub fn sys_policy(&self) -> SysPolicy {
+        self.sys
+    }
+
+    /// Sets the routine 0NNN runs under `SysPolicy::Callback`, given the
+    /// address NNN, with PC already past the instruction.
+    pub fn set_sys_handler(&mut self, handler: impl FnMut(&mut Chip8, u16) + Send + 'static) {
+        self.sys_handler = Some(SysHandler(Box::new(handler)));
+    }
+
+    /// Whether the machine is stuck on an instruction that accesses memory
+    /// out of bounds, under `BoundsPolicy::Halt`. It stays halted until the
+    /// instruction no longer faults.
+    pub fn halted(&self) -> bool {
+        self.halted
+    }
+
+    /// Sets how many instructions run per 60Hz tick of the timers; values
+    /// below 1 are treated as 1.
+    pub fn set_cycles_per_frame(&mut self, cycles: u32) {
+        self.cycles_per_frame = cycles.max(1);
+        self.frame_cycle = self.frame_cycle.min(self.frame_length() - 1);
+    }
+
+    pub fn cycles_per_frame(&self) -> u32 {
+        self.cycles_per_frame
+    }
+
+    /// Sets how long instructions take. Under `Timing::Vip`,
+    /// `cycles_per_frame` is ignored.
+    pub fn set_timing(&mut self, timing: Timing) {
+        self.timing = timing;
+        self.frame_cycle = 0;
+        self.last_cost = 1;
+    }
+
+    pub fn timing(&self) -> Timing {
+        self.timing
+    }
+
+    /// Ticks the timers at 60Hz by `clock` from now on, instead of once every
+    /// frame's worth of instructions. Frames still decide when the display
+    /// wait quirk lets DXYN draw.
+    pub fn set_clock(&mut self, clock: impl Clock + 'static) {
+        self.clock = Some(TimerClock::new(Arc::new(clock)));
+    }
+
+    /// Goes back to ticking the timers by the instruction count.
+    pub fn clear_clock(&mut self) {
+        self.clock = None;
+    }
+
+    /// How long the last instruction took in real time, for frontends that
+    /// pace the machine against the clock.
+    pub fn last_instruction_duration(&self) -> Duration {
+        Duration::from_secs_f64(self.last_cost as f64 / self.frame_length() as f64 / TIMER_FREQ)
+    }
+
+    /// Executes one instruction, ticking the timers once it completes a
+    /// frame's worth of time, or as the clock says if one is set. On an
+    /// error the machine is left as it was before the faulting instruction.
+    pub fn run_cycle(&mut self) -> Result<(), Chip8Error> {
+        self.step()?;
+        self.count_cycle();
+        Ok(())
+    }
+
+    /// Executes a frame's worth of instructions, `cycles_per_frame` of them
+    /// under flat timing, and then ticks the timers once, starting a new
+    /// frame. With a clock set, the timers tick as it says instead.
+    /// Breakpoints are ignored.
+    ///
+    /// On an error the timers don't tick and the machine is left as
+    /// `run_cycle` leaves it.
+    pub fn run_frame(&mut self) -> Result<Frame<'_>, Chip8Error> {
+        let mut drawn = false;
+        self.frame_cycle = 0;
+        while self.frame_cycle < self.frame_length() {
+            self.step()?;
+            self.frame_cycle += self.last_cost;
+            self.tick_clock();
+            drawn |= self.draw_flag;
+        }
+        if self.clock.is_none() {
+            self.tick_timers();
+        }
+        self.frame_cycle = 0;
+        Ok(Frame {
+            frame_buffer: &self.display,
+            drawn,
+            sound: self.sound_timer > 0,
+        })
+    }
+
+    // Executes one instruction without touching the timers.
+    fn step(&mut self) -> Result<(), Chip8Error> {
+        if self.history.is_recording() {
+            let state = self.save_state();
+            self.history.push(state);
+        }
         self.draw_flag = false;
+        self.halted = false;
+        // The JIT can also leave PC just past the end.
+        self.wrap_pc();
+        let (opcode, inst) = match self.decoded.get(self.pc as usize) {
+            Some(&(opcode, inst)) if inst != Inst::Undecoded => (opcode, inst),
+            _ => {
+                let opcode = match self.fetch() {
+                    Ok(opcode) => opcode,
+                    Err(kind) => return self.fault(kind),
+                };
+                let inst = decode(opcode, self.variant);
+                // An instruction on the last byte wraps around to the first,
+                // which `invalidate` doesn't track.
+                if (self.pc as usize) < MEM_SIZE - 1 {
+                    self.decoded[self.pc as usize] = (opcode, inst);
+                }
+                (opcode, inst)
+            }
+        };
+        if let Some(coverage) = &mut self.coverage {
+            coverage.mark_executed(self.pc as usize);
+        }
+        let hook = self.pre_hook.take();
+        self.pre_hook = self.call_hook(hook, opcode);
+        self.last_cost = match self.timing {
+            Timing::Flat => 1,
+            Timing::Vip => timing::vip_cycles(inst, &self.v),
+        };
+        self.pc += 2;
+        if let Err(kind) = self.execute_inst(inst, opcode) {
+            self.pc -= 2;
+            return self.fault(kind);
+        }
+        self.wrap_pc();
+        let hook = self.post_hook.take();
+        self.post_hook = self.call_hook(hook, opcode);
+        Ok(())
+    }
 
-        if self.internal_timer > 0.0 {
-            self.internal_timer -= 1.0;
-        } else {
-            self.internal_timer = CYCLES_PER_SECOND / TIMER_FREQ;
-            self.delay_timer = self.delay_timer.saturating_sub(1);
-            self.sound_timer = self.sound_timer.saturating_sub(1);
+    /// Executes up to `n` instructions, stopping early before one at a
+    /// breakpoint. The first instruction always runs, so calling this again
+    /// after a breakpoint resumes from it.
+    ///
+    /// An error ends the batch, with the machine left as `run_cycle` leaves it.
+    pub fn run_cycles(&mut self, n: u32) -> Result<BatchResult, Chip8Error> {
+        let mut batch = BatchResult::default();
+        while batch.cycles < n {
+            if batch.cycles > 0 && self.at_breakpoint() {
+                batch.breakpoint = Some(self.pc);
+                break;
+            }
+            self.run_cycle()?;
+            batch.cycles += 1;
+            batch.drawn |= self.draw_flag;
         }
+        Ok(batch)
+    }
 
-        let opcode = self.fetch_inst();
-        self.execute_inst(opcode);
+    /// The width and height of the display in pixels, which frontends should
+    /// check rather than assuming `DISPLAY_WIDTH` x `DISPLAY_HEIGHT`.
+    pub fn display_size(&self) -> (usize, usize) {
+        self.display.size()
     }
 
-    pub fn display(&self) -> &[u8] {
+    pub fn frame_buffer(&self) -> &FrameBuffer {
         &self.display
     }
 
+    /// The display as one byte per pixel, 1 for lit and 0 for dark, in
+    /// row-major order.
+    pub fn display(&self) -> Vec<u8> {
+        self.display.to_bytes()
+    }
+
+    /// The display as one bit per pixel, a row per word with the leftmost
+    /// pixel in the most significant bit.
+    pub fn display_rows(&self) -> &[u64] {
+        self.display.row_bits()
+    }
+
+    pub fn pixel(&self, x: usize, y: usize) -> bool {
+        self.display.pixel(x, y)
+    }
+
+    pub fn display_rgb(&self, foreground: [u8; 3], background: [u8; 3], scale: usize) -> Vec<u8> {
+        self.display.to_rgb(foreground, background, scale)
+    }
+
+    pub fn memory(&self) -> &[u8] {
+        &self.memory
+    }
+
+    pub fn read_byte(&self, addr: u16) -> Result<u8, Chip8Error> {
+        self.byte_at(addr).map_err(|kind| self.error(kind))
+    }
+
+    pub fn write_byte(&mut self, addr: u16, val: u8) -> Result<(), Chip8Error> {
+        if addr as usize >= MEM_SIZE {
+            return Err(self.error(ErrorKind::AddressOutOfBounds(addr)));
+        }
+        let byte = &mut self.memory[addr as usize];
+        *byte = val;
+        self.invalidate(addr as usize, 1);
+        Ok(())
+    }
+
+    /// The SCHIP flag registers that FX75 saves V0–VX into.
+    pub fn flags(&self) -> [u8; FLAG_REGISTERS] {
+        self.flags
+    }
+
+    pub fn set_flags(&mut self, flags: [u8; FLAG_REGISTERS]) {
+        self.flags = flags;
+    }
+
+    /// Keeps the flag registers in the file at `path`, loading them from it
+    /// now if it exists and rewriting it on every FX75, so that games' high
+    /// scores survive between runs. Errors writing the file later are
+    /// ignored, since the flags still work in memory.
+    #[cfg(feature = "std")]
+    pub fn set_flag_file(&mut self, path: impl Into<PathBuf>) -> io::Result<()> {
+        let path = path.into();
+        match fs::read(&path) {
+            Ok(data) => {
+                let len = data.len().min(FLAG_REGISTERS);
+                self.flags = [0; FLAG_REGISTERS];
+                self.flags[..len].copy_from_slice(&data[..len]);
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+        self.flag_file = Some(path);
+        Ok(())
+    }
+
+    pub fn set_register(&mut self, x: u8, value: u8) {
+        self.v[x as usize] = value;
+    }
+
+    pub fn state(&self) -> CpuState {
+        CpuState {
+            pc: self.pc,
+            i: self.i,
+            sp: self.sp,
+            v: self.v,
+            stack: self.stack,
+            delay_timer: self.delay_timer,
+            sound_timer: self.sound_timer,
+        }
+    }
+
+    pub fn pc(&self) -> u16 {
+        self.pc
+    }
+
+    pub fn sound_timer(&self) -> u8 {
+        self.sound_timer
+    }
+
+    /// The opcode at PC, or 0 if PC is outside memory.
+    pub fn current_opcode(&self) -> u16 {
+        self.fetch().unwrap_or(0)
+    }
+
     pub fn draw_flag(&self) -> bool {
         self.draw_flag
     }
@@ -108,95 +896,295 @@ impl Chip8 {
     }
 
     pub fn key_up(&mut self, key: u8) {
+        if self.keypad[key as usize] != 0 {
+            self.released_keys |= 1 << key;
+        }
         self.keypad[key as usize] = 0;
     }
 
-    fn fetch_inst(&mut self) -> u16 {
-        let byte1 = self.memory[self.pc as usize] as u16;
-        let byte2 = self.memory[self.pc as usize + 1] as u16;
+    // Bit n of the mask is key n.
+    pub fn set_keys(&mut self, mask: u16) {
+        self.released_keys |= self.keys() & !mask;
+        for (key, state) in self.keypad.iter_mut().enumerate() {
+            *state = ((mask >> key) & 1) as u8;
+        }
+    }
 
-        self.pc += 2;
+    pub fn keys(&self) -> u16 {
+        self.keypad
+            .iter()
+            .enumerate()
+            .fold(0, |mask, (key, &state)| mask | ((state as u16) << key))
+    }
+
+    /// Sets the keys held on CHIP-8X's second keypad, which EXF2 and EXF5
+    /// test. Bit n of the mask is key n.
+    pub fn set_second_keys(&mut self, mask: u16) {
+        self.second_keys = mask;
+    }
+
+    pub fn second_keys(&self) -> u16 {
+        self.second_keys
+    }
+
+    /// The last byte CHIP-8X's FXF8 wrote to the I/O port.
+    pub fn io_output(&self) -> u8 {
+        self.io_output
+    }
+
+    /// Sets the byte CHIP-8X's FXFB reads from the I/O port. It reads the
+    /// latest value straight away rather than waiting for a strobe.
+    pub fn set_io_input(&mut self, value: u8) {
+        self.io_input = value;
+    }
+
+    // The hook is taken out while it runs so it can borrow the interpreter.
+    fn call_hook(&self, mut hook: Option<Hook>, opcode: u16) -> Option<Hook> {
+        if let Some(hook) = &mut hook {
+            (hook.0)(self, opcode);
+        }
+        hook
+    }
+
+    fn count_cycle(&mut self) {
+        self.frame_cycle += self.last_cost;
+        if self.frame_cycle >= self.frame_length() {
+            self.frame_cycle = 0;
+            if self.clock.is_none() {
+                self.tick_timers();
+            }
+        }
+        self.tick_clock();
+    }
+
+    fn frame_length(&self) -> u32 {
+        match self.timing {
+            Timing::Flat => self.cycles_per_frame,
+            Timing::Vip => VIP_CYCLES_PER_FRAME,
+        }
+    }
+
+    fn tick_timers(&mut self) {
+        self.delay_timer = self.delay_timer.saturating_sub(1);
+        self.update_sound_timer(self.sound_timer.saturating_sub(1));
+    }
+
+    // Ticks the timers as many times as the clock says are due.
+    fn tick_clock(&mut self) {
+        let Some(clock) = &mut self.clock else {
+            return;
+        };
+        let due = clock.due_ticks().min(u8::MAX as u64) as u8;
+        self.delay_timer = self.delay_timer.saturating_sub(due);
+        self.update_sound_timer(self.sound_timer.saturating_sub(due));
+    }
+
+    // Sets the sound timer, telling the sound hook if the buzzer starts or
+    // stops.
+    fn update_sound_timer(&mut self, value: u8) {
+        let was_sounding = self.sound_timer > 0;
+        self.sound_timer = value;
+        if let Some(hook) = &mut self.sound_hook {
+            if (value > 0) != was_sounding {
+                (hook.0)(value > 0);
+            }
+        }
+    }
+
+    // Halts instead of failing on an out-of-bounds access under
+    // `BoundsPolicy::Halt`, with the machine already left on the instruction.
+    fn fault(&mut self, kind: ErrorKind) -> Result<(), Chip8Error> {
+        if self.bounds == BoundsPolicy::Halt && matches!(kind, ErrorKind::AddressOutOfBounds(_)) {
+            self.halted = true;
+            return Ok(());
+        }
+        Err(self.error(kind))
+    }
 
-        (byte1 << 8) | byte2
-    }
-
-    fn execute_inst(&mut self, opcode: u16) {
-        let x = ((opcode & 0x0f00) >> 8) as usize;
-        let y = ((opcode & 0x00f0) >> 4) as usize;
-        let n = (opcode & 0x000f) as usize;
-        let nn = (opcode & 0x00ff) as u8;
-        let nnn = opcode & 0x0fff;
-
-        match (opcode & 0xf000) >> 12 {
-            0x0 => match nnn {
-                0x0e0 => self.clear_display(),
-                0x0ee => self.ret(),
-                _ => (),
-            },
-            0x1 => self.jump(nnn),
-            0x2 => self.call(nnn),
-            0x3 => self.skip_if_reg_eq_imm(x, nn),
-            0x4 => self.skip_if_reg_neq_imm(x, nn),
-            0x5 => self.skip_if_reg_eq_reg(x, y),
-            0x6 => self.set_reg_to_imm(x, nn),
-            0x7 => self.add_imm_to_reg(x, nn),
-            0x8 => match n {
-                0x0 => self.set_reg_to_reg(x, y),
-                0x1 => self.bitwise_or(x, y),
-                0x2 => self.bitwise_and(x, y),
-                0x3 => self.bitwise_xor(x, y),
-                0x4 => self.add_reg_to_reg(x, y),
-                0x5 => self.sub_reg_from_reg(x, y),
-                0x6 => self.right_shift(x),
-                0x7 => self.rsb_reg_from_reg(x, y),
-                0xe => self.left_shift(x),
-                _ => panic!("Unknown opcode: {:x?}", opcode),
-            },
-            0x9 => self.skip_if_reg_neq_reg(x, y),
-            0xa => self.set_i_to_addr(nnn),
-            0xb => self.jump_with_offset(nnn),
-            0xc => self.set_reg_to_rand(x, nn),
-            0xd => self.draw(x, y, n),
-            0xe => match nn {
-                0x9e => self.skip_if_key_pressed(x),
-                0xa1 => self.skip_if_key_not_pressed(x),
-                _ => panic!("Unknown opcode: {:x?}", opcode),
-            },
-            0xf => match nn {
-                0x07 => self.get_delay_timer(x),
-                0x0a => self.get_key(x),
-                0x15 => self.set_delay_timer(x),
-                0x18 => self.set_sound_timer(x),
-                0x1e => self.add_reg_to_i(x),
-                0x29 => self.set_i_to_font(x),
-                0x33 => self.set_bdc(x),
-                0x55 => self.reg_dump(x),
-                0x65 => self.reg_load(x),
-                _ => panic!("Unknown opcode: {:x?}", opcode),
-            },
-            _ => panic!("Unknown opcode: {:x?}", opcode),
+    // An error with the machine's state, which is left on the instruction
+    // that failed.
+    fn error(&self, kind: ErrorKind) -> Chip8Error {
+        Chip8Error {
+            kind,
+            pc: self.pc,
+            opcode: self.current_opcode(),
+            i: self.i,
+        }
+    }
+
+    fn byte_at(&self, addr: u16) -> Result<u8, ErrorKind> {
+        self.memory
+            .get(addr as usize)
+            .copied()
+            .ok_or(ErrorKind::AddressOutOfBounds(addr))
+    }
+
+    fn wrap_pc(&mut self) {
+        if self.bounds == BoundsPolicy::Wrap {
+            self.pc %= MEM_SIZE as u16;
+        }
+    }
+
+    fn fetch(&self) -> Result<u16, ErrorKind> {
+        let mut next = self.pc.wrapping_add(1);
+        if self.bounds == BoundsPolicy::Wrap {
+            next %= MEM_SIZE as u16;
+        }
+        let byte1 = self.byte_at(self.pc)? as u16;
+        let byte2 = self.byte_at(next)? as u16;
+
+        Ok((byte1 << 8) | byte2)
+    }
+
+    // Checks that the `len` bytes starting at I are inside memory, unless
+    // they wrap around it. Returns the address of the first byte.
+    fn i_range(&self, len: usize) -> Result<usize, ErrorKind> {
+        let start = self.i as usize;
+        if self.bounds == BoundsPolicy::Wrap {
+            return Ok(start % MEM_SIZE);
+        }
+        if start + len > MEM_SIZE {
+            return Err(ErrorKind::AddressOutOfBounds(start.max(MEM_SIZE) as u16));
+        }
+        Ok(start)
+    }
+
+    // Fills `buf` with the bytes starting at I.
+    fn read_at_i(&mut self, buf: &mut [u8]) -> Result<(), ErrorKind> {
+        let start = self.i_range(buf.len())?;
+        for (offset, byte) in buf.iter_mut().enumerate() {
+            *byte = self.memory[(start + offset) % MEM_SIZE];
+        }
+        if let Some(coverage) = &mut self.coverage {
+            coverage.mark_read(start, buf.len());
+        }
+        Ok(())
+    }
+
+    fn write_at_i(&mut self, bytes: &[u8]) -> Result<(), ErrorKind> {
+        let start = self.i_range(bytes.len())?;
+        for (offset, &byte) in bytes.iter().enumerate() {
+            let addr = (start + offset) % MEM_SIZE;
+            self.memory[addr] = byte;
+            self.invalidate(addr, 1);
+        }
+        if let Some(coverage) = &mut self.coverage {
+            coverage.mark_written(start, bytes.len());
+        }
+        Ok(())
+    }
+
+    // Drops the decodes of instructions overlapping the `len` bytes at `addr`,
+    // including one that starts on the byte before.
+    fn invalidate(&mut self, addr: usize, len: usize) {
+        self.decoded[addr.saturating_sub(1)..addr + len].fill((0, Inst::Undecoded));
+    }
+
+    fn execute_inst(&mut self, inst: Inst, opcode: u16) -> Result<(), ErrorKind> {
+        match inst {
+            Inst::Cls => self.clear_display(),
+            Inst::Ret => self.ret()?,
+            Inst::Sys(addr) => self.sys(addr)?,
+            Inst::Jump(nnn) => self.jump(nnn),
+            Inst::Call(nnn) => self.call(nnn)?,
+            Inst::SkipEqImm(x, nn) => self.skip_if_reg_eq_imm(x as usize, nn),
+            Inst::SkipNeqImm(x, nn) => self.skip_if_reg_neq_imm(x as usize, nn),
+            Inst::SkipEqReg(x, y) => self.skip_if_reg_eq_reg(x as usize, y as usize),
+            Inst::SetImm(x, nn) => self.set_reg_to_imm(x as usize, nn),
+            Inst::AddImm(x, nn) => self.add_imm_to_reg(x as usize, nn),
+            Inst::SetReg(x, y) => self.set_reg_to_reg(x as usize, y as usize),
+            Inst::Or(x, y) => self.bitwise_or(x as usize, y as usize),
+            Inst::And(x, y) => self.bitwise_and(x as usize, y as usize),
+            Inst::Xor(x, y) => self.bitwise_xor(x as usize, y as usize),
+            Inst::AddReg(x, y) => self.add_reg_to_reg(x as usize, y as usize),
+            Inst::Sub(x, y) => self.sub_reg_from_reg(x as usize, y as usize),
+            Inst::ShiftRight(x, y) => self.right_shift(x as usize, y as usize),
+            Inst::ReverseSub(x, y) => self.rsb_reg_from_reg(x as usize, y as usize),
+            Inst::ShiftLeft(x, y) => self.left_shift(x as usize, y as usize),
+            Inst::SkipNeqReg(x, y) => self.skip_if_reg_neq_reg(x as usize, y as usize),
+            Inst::SetI(nnn) => self.set_i_to_addr(nnn),
+            Inst::JumpOffset(nnn) => self.jump_with_offset(nnn),
+            Inst::Random(x, nn) => self.set_reg_to_rand(x as usize, nn),
+            Inst::Draw(x, y, n) => self.draw(x as usize, y as usize, n as usize)?,
+            Inst::SkipKeyPressed(x) => self.skip_if_key_pressed(x as usize),
+            Inst::SkipKeyNotPressed(x) => self.skip_if_key_not_pressed(x as usize),
+            Inst::GetDelayTimer(x) => self.get_delay_timer(x as usize),
+            Inst::GetKey(x) => self.get_key(x as usize),
+            Inst::SetDelayTimer(x) => self.set_delay_timer(x as usize),
+            Inst::SetSoundTimer(x) => self.set_sound_timer(x as usize),
+            Inst::AddI(x) => self.add_reg_to_i(x as usize),
+            Inst::Font(x) => self.set_i_to_font(x as usize),
+            Inst::LargeFont(x) => self.set_i_to_large_font(x as usize),
+            Inst::Bcd(x) => self.set_bdc(x as usize)?,
+            Inst::RegDump(x) => self.reg_dump(x as usize)?,
+            Inst::RegLoad(x) => self.reg_load(x as usize)?,
+            Inst::SaveFlags(x) => self.save_flags(x as usize),
+            Inst::LoadFlags(x) => self.load_flags(x as usize),
+            Inst::NextBackground => self.colors.next_background(),
+            Inst::AddNibbles(x, y) => self.add_nibbles(x as usize, y as usize),
+            Inst::ColorZones(x, y) => self.color_zones(x as usize, y as usize),
+            Inst::ColorRows(x, y, n) => self.color_rows(x as usize, y as usize, n as usize),
+            Inst::SkipKey2Pressed(x) => self.skip_if_second_key(x as usize, true),
+            Inst::SkipKey2NotPressed(x) => self.skip_if_second_key(x as usize, false),
+            Inst::Output(x) => self.io_output = self.v[x as usize],
+            Inst::Input(x) => self.v[x as usize] = self.io_input,
+            Inst::Undecoded | Inst::Unknown => return Err(ErrorKind::UnknownOpcode(opcode)),
         };
+        Ok(())
+    }
+
+    fn sys(&mut self, addr: u16) -> Result<(), ErrorKind> {
+        match self.sys {
+            SysPolicy::Ignore => (),
+            SysPolicy::Warn => {
+                if !self.warned_sys.contains(&addr) {
+                    self.warned_sys.push(addr);
+                    log::warn!(
+                        "{:#05x}: ignored call to machine code at {addr:#05x}",
+                        self.pc - 2
+                    );
+                }
+            }
+            SysPolicy::Error => return Err(ErrorKind::MachineCode(addr)),
+            // The handler is taken out while it runs so it can borrow the
+            // machine, and put back unless it set a new one.
+            SysPolicy::Callback => {
+                if let Some(mut handler) = self.sys_handler.take() {
+                    (handler.0)(self, addr);
+                    self.sys_handler.get_or_insert(handler);
+                }
+            }
+        }
+        Ok(())
     }
 
     fn clear_display(&mut self) {
-        self.display.fill(0);
+        self.display.row_bits_mut().fill(0);
         self.draw_flag = true;
     }
 
-    fn ret(&mut self) {
+    fn ret(&mut self) -> Result<(), ErrorKind> {
+        if self.sp == 0 {
+            return Err(ErrorKind::StackUnderflow(self.pc - 2));
+        }
         self.sp -= 1;
         self.pc = self.stack[self.sp as usize];
         self.stack[self.sp as usize] = 0;
+        Ok(())
     }
 
     fn jump(&mut self, addr: u16) {
         self.pc = addr;
     }
 
-    fn call(&mut self, addr: u16) {
+    fn call(&mut self, addr: u16) -> Result<(), ErrorKind> {
+        if self.sp as usize == STACK_SIZE {
+            return Err(ErrorKind::StackOverflow(self.pc - 2));
+        }
         self.stack[self.sp as usize] = self.pc;
         self.pc = addr;
         self.sp += 1;
+        Ok(())
     }
 
     fn skip_if_reg_eq_imm(&mut self, x: usize, nn: u8) {
@@ -231,14 +1219,23 @@ impl Chip8 {
 
     fn bitwise_or(&mut self, x: usize, y: usize) {
         self.v[x] |= self.v[y];
+        self.reset_vf_after_logic();
     }
 
     fn bitwise_and(&mut self, x: usize, y: usize) {
         self.v[x] &= self.v[y];
+        self.reset_vf_after_logic();
     }
 
     fn bitwise_xor(&mut self, x: usize, y: usize) {
         self.v[x] ^= self.v[y];
+        self.reset_vf_after_logic();
+    }
+
+    fn reset_vf_after_logic(&mut self) {
+        if self.quirks.logic_resets_vf {
+            self.v[0xf] = 0;
+        }
     }
 
     fn add_reg_to_reg(&mut self, x: usize, y: usize) {
@@ -253,20 +1250,30 @@ impl Chip8 {
         self.v[0xf] = !overflow as u8;
     }
 
-    fn right_shift(&mut self, x: usize) {
-        self.v[0xf] = self.v[x] & 0x1;
-        self.v[x] >>= 1;
+    fn right_shift(&mut self, x: usize, y: usize) {
+        let value = self.v[self.shift_source(x, y)];
+        self.v[x] = value >> 1;
+        self.v[0xf] = value & 0x1;
     }
 
     fn rsb_reg_from_reg(&mut self, x: usize, y: usize) {
-        let (res, overflow) = self.v[y].overflowing_sub(self.v[y]);
+        let (res, overflow) = self.v[y].overflowing_sub(self.v[x]);
         self.v[x] = res;
         self.v[0xf] = !overflow as u8;
     }
 
-    fn left_shift(&mut self, x: usize) {
-        self.v[0xf] = self.v[x] >> 7;
-        self.v[x] <<= 1;
+    fn left_shift(&mut self, x: usize, y: usize) {
+        let value = self.v[self.shift_source(x, y)];
+        self.v[x] = value << 1;
+        self.v[0xf] = value >> 7;
+    }
+
+    fn shift_source(&self, x: usize, y: usize) -> usize {
+        if self.quirks.shift_vy {
+            y
+        } else {
+            x
+        }
     }
 
     fn skip_if_reg_neq_reg(&mut self, x: usize, y: usize) {
@@ -280,53 +1287,65 @@ impl Chip8 {
     }
 
     fn jump_with_offset(&mut self, addr: u16) {
-        self.pc = addr + self.v[0] as u16;
+        let x = if self.quirks.jump_vx { addr >> 8 } else { 0 };
+        self.pc = addr + self.v[x as usize] as u16;
     }
 
     fn set_reg_to_rand(&mut self, x: usize, nn: u8) {
-        let num: u8 = rand::thread_rng().gen_range(0..=255);
+        let num = self.rng.0.next_byte();
         self.v[x] = num & nn;
     }
 
-    fn draw(&mut self, x: usize, y: usize, n: usize) {
-        let x = (self.v[x] as usize) % DISPLAY_WIDTH;
-        let y = (self.v[y] as usize) % DISPLAY_HEIGHT;
-
-        self.v[0xf] = 0;
-
-        for row in 0..n {
-            let sprite = self.memory[self.i as usize + row];
-            for col in 0..8 {
-                if (sprite & (0x80 >> col)) != 0 {
-                    let index = x + col + ((y + row as usize) * DISPLAY_WIDTH);
-                    if index >= DISPLAY_WIDTH * DISPLAY_HEIGHT {
-                        break;
-                    }
-                    if self.display[index] == 1 {
-                        self.v[0xf] = 1;
-                    }
-                    self.display[index] ^= 1;
-                }
-                if x + col >= DISPLAY_WIDTH {
-                    break;
-                }
-            }
-            if y + row >= DISPLAY_HEIGHT {
+    // The sprite starts at a wrapped position and is clipped at the edges
+    // unless the wrap quirk is on.
+    fn draw(&mut self, x: usize, y: usize, n: usize) -> Result<(), ErrorKind> {
+        let mut buf = [0; 15];
+        let sprite = &mut buf[..n];
+        self.read_at_i(sprite)?;
+        // With the display-wait quirk, DXYN repeats like FX0A until it is the
+        // first instruction of a frame.
+        if self.quirks.display_wait && self.frame_cycle != 0 {
+            self.pc -= 2;
+            return Ok(());
+        }
+        let (width, height) = self.display.size();
+        let x = (self.v[x] as usize) % width;
+        let y = (self.v[y] as usize) % height;
+
+        // The starting position always wraps; the quirk decides whether the
+        // rest of the sprite does too.
+        let wrap = self.quirks.wrap_sprites;
+        let mut collision = 0;
+        for (dy, &byte) in sprite.iter().enumerate() {
+            let y = if wrap { (y + dy) % height } else { y + dy };
+            let Some(row) = self.display.row_bits_mut().get_mut(y) else {
                 break;
-            }
+            };
+            // Rows are exactly as wide as a u64, so rotating wraps at the edge.
+            let bits = (byte as u64) << 56;
+            let bits = if wrap {
+                bits.rotate_right(x as u32)
+            } else {
+                bits >> x
+            };
+            collision |= *row & bits;
+            *row ^= bits;
         }
+        self.v[0xf] = (collision != 0) as u8;
 
         self.draw_flag = true;
+        Ok(())
     }
 
+    // Only the low nibble of VX selects a key, as on the COSMAC VIP.
     fn skip_if_key_pressed(&mut self, x: usize) {
-        if self.keypad[self.v[x] as usize] != 0 {
+        if self.keypad[(self.v[x] & 0xf) as usize] != 0 {
             self.pc += 2;
         }
     }
 
     fn skip_if_key_not_pressed(&mut self, x: usize) {
-        if self.keypad[self.v[x] as usize] == 0 {
+        if self.keypad[(self.v[x] & 0xf) as usize] == 0 {
             self.pc += 2;
         }
     }
@@ -335,18 +1354,22 @@ impl Chip8 {
         self.v[x] = self.delay_timer;
     }
 
+    // Like the COSMAC VIP, FX0A only completes once a key has been pressed
+    // and released after the instruction started waiting.
     fn get_key(&mut self, x: usize) {
-        let mut key_pressed = false;
-        for i in 0..KEYPAD_SIZE {
-            if self.keypad[i] != 0 {
-                self.v[x] = i as u8;
-                key_pressed = true;
-                break;
-            }
+        if !self.waiting_for_key {
+            self.waiting_for_key = true;
+            self.released_keys = 0;
         }
-        if !key_pressed {
+
+        if self.released_keys == 0 {
             self.pc -= 2;
+            return;
         }
+
+        self.v[x] = self.released_keys.trailing_zeros() as u8;
+        self.released_keys = 0;
+        self.waiting_for_key = false;
     }
 
     fn set_delay_timer(&mut self, x: usize) {
@@ -354,34 +1377,88 @@ impl Chip8 {
     }
 
     fn set_sound_timer(&mut self, x: usize) {
-        self.sound_timer = self.v[x];
+        self.update_sound_timer(self.v[x]);
     }
 
     fn add_reg_to_i(&mut self, x: usize) {
-        self.i += self.v[x] as u16;
+        self.i = self.i.wrapping_add(self.v[x] as u16);
     }
 
     fn set_i_to_font(&mut self, x: usize) {
         self.i = self.v[x] as u16 * SPRITE_SIZE + SPRITE_START as u16;
     }
 
-    fn set_bdc(&mut self, x: usize) {
-        let i = self.i as usize;
+    fn set_i_to_large_font(&mut self, x: usize) {
+        self.i = self.v[x] as u16 * LARGE_SPRITE_SIZE + LARGE_SPRITE_START as u16;
+    }
+
+    fn set_bdc(&mut self, x: usize) -> Result<(), ErrorKind> {
         let x = self.v[x];
-        self.memory[i] = x / 100;
-        self.memory[i + 1] = (x / 10) % 10;
-        self.memory[i + 2] = x % 10;
+        self.write_at_i(&[x / 100, (x / 10) % 10, x % 10])
     }
 
-    fn reg_dump(&mut self, x: usize) {
-        for j in 0..=x {
-            self.memory[self.i as usize + j] = self.v[j];
+    fn reg_dump(&mut self, x: usize) -> Result<(), ErrorKind> {
+        let v = self.v;
+        self.write_at_i(&v[..=x])?;
+        self.increment_i_after_load_store(x);
+        Ok(())
+    }
+
+    fn reg_load(&mut self, x: usize) -> Result<(), ErrorKind> {
+        let mut v = self.v;
+        self.read_at_i(&mut v[..=x])?;
+        self.v = v;
+        self.increment_i_after_load_store(x);
+        Ok(())
+    }
+
+    fn increment_i_after_load_store(&mut self, x: usize) {
+        if self.quirks.load_store_increments_i {
+            self.i = self.i.wrapping_add(x as u16 + 1);
         }
     }
 
-    fn reg_load(&mut self, x: usize) {
-        for j in 0..=x {
-            self.v[j] = self.memory[self.i as usize + j];
+    fn save_flags(&mut self, x: usize) {
+        self.flags[..=x].copy_from_slice(&self.v[..=x]);
+        #[cfg(feature = "std")]
+        if let Some(path) = &self.flag_file {
+            let _ = fs::write(path, self.flags);
+        }
+    }
+
+    fn load_flags(&mut self, x: usize) {
+        self.v[..=x].copy_from_slice(&self.flags[..=x]);
+    }
+
+    // CHIP-8X adds each nibble separately, with the carry out of its low
+    // three bits dropped, for the packed coordinates and colors BXYN uses.
+    fn add_nibbles(&mut self, x: usize, y: usize) {
+        self.v[x] = ((self.v[x] & 0x77) + (self.v[y] & 0x77)) & 0x77;
+    }
+
+    // VX and VX+1 give the left and top zone in their low nibbles and how many
+    // more zones to cover in their high nibbles; VY gives the color.
+    fn color_zones(&mut self, x: usize, y: usize) {
+        let (h, v) = (self.v[x], self.v[(x + 1) % NUM_REGISTERS]);
+        let (left, top) = ((h & 0xf) as usize, (v & 0xf) as usize);
+        let right = left + (h >> 4) as usize;
+        let bottom = top + (v >> 4) as usize;
+        self.colors
+            .fill_zones((left, top), (right, bottom), self.v[y] & 0x7);
+        self.draw_flag = true;
+    }
+
+    // Colors N rows of the 8-pixel column at (VX, VX+1) with VY.
+    fn color_rows(&mut self, x: usize, y: usize, n: usize) {
+        let (px, py) = (self.v[x], self.v[(x + 1) % NUM_REGISTERS]);
+        self.colors
+            .fill_rows(px as usize, py as usize, n, self.v[y] & 0x7);
+        self.draw_flag = true;
+    }
+
+    fn skip_if_second_key(&mut self, x: usize, pressed: bool) {
+        if (self.second_keys >> (self.v[x] & 0xf)) & 1 == pressed as u16 {
+            self.pc += 2;
         }
     }
 }
@@ -0,0 +1,369 @@
+use crate::{Chip8, FrameBuffer, Variant, DISPLAY_HEIGHT, DISPLAY_WIDTH};
+use std::ffi::{c_char, c_uint, c_void};
+use std::sync::Mutex;
+
+const RETRO_API_VERSION: c_uint = 1;
+const RETRO_DEVICE_JOYPAD: c_uint = 1;
+const RETRO_DEVICE_KEYBOARD: c_uint = 3;
+const RETRO_ENVIRONMENT_SET_PIXEL_FORMAT: c_uint = 10;
+const RETRO_PIXEL_FORMAT_XRGB8888: c_uint = 1;
+const RETRO_REGION_NTSC: c_uint = 0;
+
+const FPS: f64 = 60.0;
+const SAMPLE_RATE: f64 = 44100.0;
+const SAMPLES_PER_FRAME: usize = (SAMPLE_RATE / FPS) as usize;
+const TONE_HZ: f64 = 440.0;
+const VOLUME: i16 = 4000;
+
+const FOREGROUND: u32 = 0x00ff_ffff;
+const BACKGROUND: u32 = 0x0000_0000;
+
+// Joypad buttons map onto the keys most games use for movement and action.
+const JOYPAD_KEYS: [(c_uint, u8); 6] = [
+    (4, 0x2), // up
+    (5, 0x8), // down
+    (6, 0x4), // left
+    (7, 0x6), // right
+    (8, 0x5), // A
+    (0, 0x0), // B
+];
+
+// The usual QWERTY layout, by retro_key code (lowercase ASCII).
+const KEYBOARD_KEYS: [(c_uint, u8); 16] = [
+    (b'1' as c_uint, 0x1),
+    (b'2' as c_uint, 0x2),
+    (b'3' as c_uint, 0x3),
+    (b'4' as c_uint, 0xc),
+    (b'q' as c_uint, 0x4),
+    (b'w' as c_uint, 0x5),
+    (b'e' as c_uint, 0x6),
+    (b'r' as c_uint, 0xd),
+    (b'a' as c_uint, 0x7),
+    (b's' as c_uint, 0x8),
+    (b'd' as c_uint, 0x9),
+    (b'f' as c_uint, 0xe),
+    (b'z' as c_uint, 0xa),
+    (b'x' as c_uint, 0x0),
+    (b'c' as c_uint, 0xb),
+    (b'v' as c_uint, 0xf),
+];
+
+type EnvironmentFn = unsafe extern "C" fn(cmd: c_uint, data: *mut c_void) -> bool;
+type VideoRefreshFn =
+    unsafe extern "C" fn(data: *const c_void, width: c_uint, height: c_uint, pitch: usize);
+type AudioSampleFn = unsafe extern "C" fn(left: i16, right: i16);
+type AudioSampleBatchFn = unsafe extern "C" fn(data: *const i16, frames: usize) -> usize;
+type InputPollFn = unsafe extern "C" fn();
+type InputStateFn =
+    unsafe extern "C" fn(port: c_uint, device: c_uint, index: c_uint, id: c_uint) -> i16;
+
+#[repr(C)]
+pub struct RetroSystemInfo {
+    library_name: *const c_char,
+    library_version: *const c_char,
+    valid_extensions: *const c_char,
+    need_fullpath: bool,
+    block_extract: bool,
+}
+
+#[repr(C)]
+pub struct RetroGameGeometry {
+    base_width: c_uint,
+    base_height: c_uint,
+    max_width: c_uint,
+    max_height: c_uint,
+    aspect_ratio: f32,
+}
+
+#[repr(C)]
+pub struct RetroSystemTiming {
+    fps: f64,
+    sample_rate: f64,
+}
+
+#[repr(C)]
+pub struct RetroSystemAvInfo {
+    geometry: RetroGameGeometry,
+    timing: RetroSystemTiming,
+}
+
+#[repr(C)]
+pub struct RetroGameInfo {
+    path: *const c_char,
+    data: *const c_void,
+    size: usize,
+    meta: *const c_char,
+}
+
+struct Core {
+    environment: Option<EnvironmentFn>,
+    video_refresh: Option<VideoRefreshFn>,
+    audio_sample_batch: Option<AudioSampleBatchFn>,
+    input_poll: Option<InputPollFn>,
+    input_state: Option<InputStateFn>,
+    chip8: Option<Chip8>,
+    rom: Vec<u8>,
+    frame: Vec<u32>,
+    audio: Vec<i16>,
+    phase: f64,
+}
+
+static CORE: Mutex<Core> = Mutex::new(Core {
+    environment: None,
+    video_refresh: None,
+    audio_sample_batch: None,
+    input_poll: None,
+    input_state: None,
+    chip8: None,
+    rom: Vec::new(),
+    frame: Vec::new(),
+    audio: Vec::new(),
+    phase: 0.0,
+});
+
+fn core() -> std::sync::MutexGuard<'static, Core> {
+    CORE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+#[no_mangle]
+pub extern "C" fn retro_api_version() -> c_uint {
+    RETRO_API_VERSION
+}
+
+#[no_mangle]
+pub extern "C" fn retro_set_environment(callback: EnvironmentFn) {
+    core().environment = Some(callback);
+}
+
+#[no_mangle]
+pub extern "C" fn retro_set_video_refresh(callback: VideoRefreshFn) {
+    core().video_refresh = Some(callback);
+}
+
+// Audio is always sent in batches.
+#[no_mangle]
+pub extern "C" fn retro_set_audio_sample(_callback: AudioSampleFn) {}
+
+#[no_mangle]
+pub extern "C" fn retro_set_audio_sample_batch(callback: AudioSampleBatchFn) {
+    core().audio_sample_batch = Some(callback);
+}
+
+#[no_mangle]
+pub extern "C" fn retro_set_input_poll(callback: InputPollFn) {
+    core().input_poll = Some(callback);
+}
+
+#[no_mangle]
+pub extern "C" fn retro_set_input_state(callback: InputStateFn) {
+    core().input_state = Some(callback);
+}
+
+#[no_mangle]
+pub extern "C" fn retro_set_controller_port_device(_port: c_uint, _device: c_uint) {}
+
+#[no_mangle]
+pub extern "C" fn retro_init() {
+    let mut core = core();
+    core.frame = Vec::with_capacity(FrameBuffer::MAX_WIDTH * FrameBuffer::MAX_HEIGHT);
+    core.audio = Vec::with_capacity(SAMPLES_PER_FRAME * 2);
+}
+
+#[no_mangle]
+pub extern "C" fn retro_deinit() {
+    let mut core = core();
+    core.chip8 = None;
+    core.rom = Vec::new();
+}
+
+/// # Safety
+///
+/// `info` must point to a writable `retro_system_info`.
+#[no_mangle]
+pub unsafe extern "C" fn retro_get_system_info(info: *mut RetroSystemInfo) {
+    *info = RetroSystemInfo {
+        library_name: c"my-chip8".as_ptr(),
+        library_version: concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr().cast(),
+        valid_extensions: c"ch8|c8|rom".as_ptr(),
+        need_fullpath: false,
+        block_extract: false,
+    };
+}
+
+/// # Safety
+///
+/// `info` must point to a writable `retro_system_av_info`.
+#[no_mangle]
+pub unsafe extern "C" fn retro_get_system_av_info(info: *mut RetroSystemAvInfo) {
+    *info = RetroSystemAvInfo {
+        geometry: RetroGameGeometry {
+            base_width: DISPLAY_WIDTH as c_uint,
+            base_height: DISPLAY_HEIGHT as c_uint,
+            max_width: FrameBuffer::MAX_WIDTH as c_uint,
+            max_height: FrameBuffer::MAX_HEIGHT as c_uint,
+            aspect_ratio: DISPLAY_WIDTH as f32 / DISPLAY_HEIGHT as f32,
+        },
+        timing: RetroSystemTiming {
+            fps: FPS,
+            sample_rate: SAMPLE_RATE,
+        },
+    };
+}
+
+/// # Safety
+///
+/// `game` must be null or point to a valid `retro_game_info` whose `data`
+/// holds `size` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn retro_load_game(game: *const RetroGameInfo) -> bool {
+    if game.is_null() || (*game).data.is_null() {
+        return false;
+    }
+    let rom = std::slice::from_raw_parts((*game).data.cast::<u8>(), (*game).size);
+
+    let mut core = core();
+    if let Some(environment) = core.environment {
+        let mut format = RETRO_PIXEL_FORMAT_XRGB8888;
+        let supported = environment(
+            RETRO_ENVIRONMENT_SET_PIXEL_FORMAT,
+            (&mut format as *mut c_uint).cast(),
+        );
+        if !supported {
+            return false;
+        }
+    }
+
+    core.rom = rom.to_vec();
+    core.reset();
+    true
+}
+
+#[no_mangle]
+pub extern "C" fn retro_load_game_special(
+    _game_type: c_uint,
+    _info: *const RetroGameInfo,
+    _num_info: usize,
+) -> bool {
+    false
+}
+
+#[no_mangle]
+pub extern "C" fn retro_unload_game() {
+    core().chip8 = None;
+}
+
+#[no_mangle]
+pub extern "C" fn retro_reset() {
+    core().reset();
+}
+
+#[no_mangle]
+pub extern "C" fn retro_run() {
+    let mut core = core();
+    // SAFETY: the frontend registers valid callbacks before calling retro_run.
+    unsafe { core.run_frame() };
+}
+
+#[no_mangle]
+pub extern "C" fn retro_get_region() -> c_uint {
+    RETRO_REGION_NTSC
+}
+
+// Save states are not supported yet.
+#[no_mangle]
+pub extern "C" fn retro_serialize_size() -> usize {
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn retro_serialize(_data: *mut c_void, _size: usize) -> bool {
+    false
+}
+
+#[no_mangle]
+pub extern "C" fn retro_unserialize(_data: *const c_void, _size: usize) -> bool {
+    false
+}
+
+#[no_mangle]
+pub extern "C" fn retro_cheat_reset() {}
+
+#[no_mangle]
+pub extern "C" fn retro_cheat_set(_index: c_uint, _enabled: bool, _code: *const c_char) {}
+
+#[no_mangle]
+pub extern "C" fn retro_get_memory_data(_id: c_uint) -> *mut c_void {
+    std::ptr::null_mut()
+}
+
+#[no_mangle]
+pub extern "C" fn retro_get_memory_size(_id: c_uint) -> usize {
+    0
+}
+
+impl Core {
+    fn reset(&mut self) {
+        let mut chip8 = Chip8::new();
+        chip8.set_variant(Variant::detect(&self.rom));
+        chip8.load_rom_bytes(&self.rom);
+        self.chip8 = Some(chip8);
+    }
+
+    unsafe fn run_frame(&mut self) {
+        if let Some(input_poll) = self.input_poll {
+            input_poll();
+        }
+        let keys = self.input_state.map_or(0, |input_state| {
+            let joypad = JOYPAD_KEYS
+                .iter()
+                .filter(|&&(id, _)| input_state(0, RETRO_DEVICE_JOYPAD, 0, id) != 0);
+            let keyboard = KEYBOARD_KEYS
+                .iter()
+                .filter(|&&(id, _)| input_state(0, RETRO_DEVICE_KEYBOARD, 0, id) != 0);
+            joypad
+                .chain(keyboard)
+                .fold(0u16, |keys, &(_, key)| keys | (1 << key))
+        });
+
+        let Some(chip8) = &mut self.chip8 else {
+            return;
+        };
+        chip8.set_keys(keys);
+        // A faulting instruction keeps failing, which leaves the game frozen
+        // on its last frame.
+        let _ = chip8.run_frame();
+
+        let (width, height) = chip8.display_size();
+        self.frame.clear();
+        self.frame.extend(chip8.display().into_iter().map(|pixel| {
+            if pixel == 1 {
+                FOREGROUND
+            } else {
+                BACKGROUND
+            }
+        }));
+        if let Some(video_refresh) = self.video_refresh {
+            video_refresh(
+                self.frame.as_ptr().cast(),
+                width as c_uint,
+                height as c_uint,
+                width * 4,
+            );
+        }
+
+        let beeping = chip8.sound_timer() > 0;
+        self.audio.clear();
+        for _ in 0..SAMPLES_PER_FRAME {
+            let sample = match (beeping, self.phase < 0.5) {
+                (false, _) => 0,
+                (true, true) => VOLUME,
+                (true, false) => -VOLUME,
+            };
+            self.audio.extend([sample, sample]);
+            self.phase = (self.phase + TONE_HZ / SAMPLE_RATE).fract();
+        }
+        if let Some(audio_sample_batch) = self.audio_sample_batch {
+            audio_sample_batch(self.audio.as_ptr(), SAMPLES_PER_FRAME);
+        }
+    }
+}
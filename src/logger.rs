@@ -0,0 +1,25 @@
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+// Prints the interpreter's warnings to stderr. Other crates' logs are left
+// out, as the frontends never showed them.
+struct StderrLogger;
+
+impl Log for StderrLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= Level::Warn && metadata.target().starts_with("my_chip8")
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            eprintln!("Warning: {}", record.args());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+pub fn init() {
+    if log::set_logger(&StderrLogger).is_ok() {
+        log::set_max_level(LevelFilter::Warn);
+    }
+}
@@ -1,106 +1,979 @@
-extern crate sdl2;
-
-use my_chip8::Chip8;
-use sdl2::event::Event;
-use sdl2::keyboard::Scancode;
-use sdl2::pixels::Color;
-use sdl2::rect::Point;
-use std::collections::HashMap;
-use std::env;
+#[cfg(feature = "sdl")]
+mod achievements;
+#[cfg(feature = "sdl")]
+mod audio;
+mod bench;
+mod cheats;
+mod config;
+mod coverage_map;
+mod database;
+#[cfg(feature = "discord")]
+mod discord;
+#[cfg(any(feature = "terminal", feature = "pixels", feature = "egui"))]
+mod driver;
+#[cfg(feature = "sdl")]
+mod emulation;
+#[cfg(feature = "egui")]
+mod gui;
+mod headless;
+mod info;
+#[cfg(any(
+    feature = "sdl",
+    feature = "terminal",
+    feature = "pixels",
+    feature = "egui"
+))]
+mod keymap;
+#[cfg(feature = "sdl")]
+mod keypad;
+#[cfg(feature = "sdl")]
+mod latency;
+mod logger;
+#[cfg(feature = "sdl")]
+mod netplay;
+#[cfg(feature = "sdl")]
+mod overlay;
+mod palette;
+mod patch;
+#[cfg(any(feature = "sdl", feature = "terminal"))]
+mod perf;
+#[cfg(feature = "sdl")]
+mod picker;
+#[cfg(feature = "pixels")]
+mod pixels_frontend;
+mod recent;
+#[cfg(feature = "sdl")]
+mod recording;
+#[cfg(feature = "remote")]
+mod remote;
+#[cfg(feature = "sdl")]
+mod render;
+mod replay;
+#[cfg(feature = "sdl")]
+mod screenshot;
+#[cfg(feature = "scripting")]
+mod script;
+#[cfg(feature = "sdl")]
+mod sdl;
+#[cfg(feature = "sdl")]
+mod slots;
+mod stats;
+mod sym_file;
+#[cfg(feature = "terminal")]
+mod terminal;
+#[cfg(feature = "sdl")]
+mod text;
+#[cfg(feature = "terminal")]
+mod tui;
+mod verify;
+#[cfg(feature = "watch")]
+mod watch;
+mod workloads;
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use config::Config;
+use database::{Database, RomSettings};
+use my_chip8::debugger::Event;
+use my_chip8::{
+    builtin, BoundsPolicy, Chip8, FontSet, Quirks, SysPolicy, Timing, Variant, MEM_SIZE,
+};
+#[cfg(any(
+    feature = "sdl",
+    feature = "terminal",
+    feature = "pixels",
+    feature = "egui"
+))]
+use palette::Palette;
+use palette::{PaletteName, Rgb};
+use recent::RecentRoms;
+use replay::{Replay, ReplayMode};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+#[cfg(any(
+    feature = "sdl",
+    feature = "terminal",
+    feature = "pixels",
+    feature = "egui"
+))]
 use std::time::Duration;
 
-const BACKGROUND_COLOR: Color = Color::BLACK;
-const FOREGROUND_COLOR: Color = Color::WHITE;
-const PIXEL_SIZE: f32 = 20.0;
+#[cfg(any(feature = "sdl", feature = "scripting"))]
+const CYCLES_PER_FRAME: u64 = my_chip8::CYCLES_PER_FRAME as u64;
 
-fn main() {
-    let mut chip8 = Chip8::new();
+// How often the windowed and terminal frontends show a frame.
+#[cfg(any(
+    feature = "sdl",
+    feature = "terminal",
+    feature = "pixels",
+    feature = "egui"
+))]
+const FRAME_INTERVAL: Duration = Duration::from_nanos(1_000_000_000 / 60);
+// Don't try to catch up on more than this much time after a stall.
+#[cfg(any(
+    feature = "sdl",
+    feature = "terminal",
+    feature = "pixels",
+    feature = "egui"
+))]
+const MAX_BACKLOG: Duration = Duration::from_millis(100);
+
+const FLAGS_DIR: &str = "flags";
+
+#[derive(Parser)]
+#[command(
+    about = "A simple CHIP-8 interpreter",
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[command(flatten)]
+    run: RunArgs,
+}
+
+// Parsed once at startup, so the size of RunArgs doesn't matter.
+#[allow(clippy::large_enum_variant)]
+#[derive(Subcommand)]
+enum Command {
+    /// Run a ROM (the default when no subcommand is given)
+    Run(RunArgs),
+    /// Measure interpreter speed on built-in workloads and report MIPS
+    Bench(BenchArgs),
+    /// Print a ROM's size, hashes, likely platform, and first instruction
+    Info(InfoArgs),
+    /// Decompile a ROM to Octo source
+    Decompile(DecompileArgs),
+    /// Report a ROM's unreachable code and suspicious control flow
+    Analyze(AnalyzeArgs),
+    /// Run a ROM and stop at the first instruction where its state differs
+    /// from a reference emulator's log
+    Verify(VerifyArgs),
+}
+
+#[derive(Args)]
+pub struct BenchArgs {
+    /// Number of cycles to run for each workload
+    #[arg(long, default_value_t = 10_000_000)]
+    cycles: u64,
+
+    /// Run the workloads with the experimental Cranelift JIT
+    #[cfg(feature = "jit")]
+    #[arg(long)]
+    jit: bool,
+}
+
+#[derive(Args)]
+pub struct InfoArgs {
+    /// Path to the ROM to inspect
+    rom: PathBuf,
+}
+
+#[derive(Args)]
+struct DecompileArgs {
+    /// Path to the ROM to decompile
+    rom: PathBuf,
+
+    /// Write the source to this file instead of printing it
+    #[arg(short, long, value_name = "FILE")]
+    output: Option<PathBuf>,
+}
+
+#[derive(Args)]
+struct AnalyzeArgs {
+    /// Path to the ROM to analyze
+    rom: PathBuf,
+
+    /// Write the control-flow graph to this file in Graphviz's DOT language
+    #[arg(long, value_name = "FILE")]
+    dot: Option<PathBuf>,
+}
+
+#[derive(Args)]
+pub struct VerifyArgs {
+    /// Path to the ROM to run
+    rom: PathBuf,
+
+    /// The reference emulator's log, a line per instruction of "KEY:VALUE"
+    /// fields in hex for the state before it runs, e.g. "PC:0200 OP:6005
+    /// V0:00"; fields this emulator doesn't know are ignored
+    #[arg(long, value_name = "FILE")]
+    against: PathBuf,
+
+    /// Emulate the quirks of this interpreter [default: modern]
+    #[arg(long, value_enum, value_name = "PROFILE")]
+    quirk_profile: Option<QuirkProfile>,
+
+    /// Emulate one quirk on top of the profile; repeat for several
+    #[arg(long = "quirk", value_enum, value_name = "QUIRK")]
+    quirks: Vec<QuirkName>,
+
+    /// Seed for the CXNN random number generator
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+}
+
+#[derive(Args)]
+pub struct RunArgs {
+    /// Path to the ROM to run; without one the SDL window waits for a ROM file
+    /// to be dropped on it
+    #[arg(group = "source")]
+    #[cfg_attr(
+        not(feature = "sdl"),
+        arg(required_unless_present_any = ["recent", "builtin"])
+    )]
+    rom: Option<String>,
+
+    /// Run the Nth most recently opened ROM, counting from 1
+    #[arg(long, value_name = "N", group = "source", value_parser = clap::value_parser!(u8).range(1..=recent::MAX_RECENT as i64))]
+    recent: Option<u8>,
+
+    /// Run a ROM built into the emulator
+    #[arg(long, value_enum, value_name = "NAME", group = "source")]
+    builtin: Option<BuiltinRom>,
+
+    /// Run the built-in latency probe ROM and report input-to-display latency
+    #[cfg(feature = "sdl")]
+    #[arg(long, conflicts_with = "source", group = "frontend")]
+    latency: bool,
+
+    /// Named color palette
+    #[arg(long, value_enum)]
+    palette: Option<PaletteName>,
+
+    /// Foreground color as a hex string, e.g. "#ffcc00"
+    #[arg(long)]
+    foreground: Option<Rgb>,
+
+    /// Background color as a hex string, e.g. "#996600"
+    #[arg(long)]
+    background: Option<Rgb>,
+
+    /// Size of each CHIP-8 pixel in screen pixels, instead of one fitted to
+    /// the display's DPI
+    #[cfg(any(feature = "sdl", feature = "pixels"))]
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    scale: Option<u32>,
+
+    /// Scale the display by whole numbers only, letterboxing what's left of
+    /// the window, so every pixel is the same size
+    #[cfg(feature = "sdl")]
+    #[arg(long)]
+    integer_scale: bool,
+
+    /// Fade pixels out over a few frames like a CRT phosphor
+    #[cfg(feature = "sdl")]
+    #[arg(long)]
+    phosphor: bool,
+
+    /// Darken the lower half of each pixel row like CRT scanlines (toggle with F2)
+    #[cfg(feature = "sdl")]
+    #[arg(long)]
+    scanlines: bool,
+
+    /// Draw a thin border around each pixel (toggle with F3)
+    #[cfg(feature = "sdl")]
+    #[arg(long)]
+    pixel_grid: bool,
+
+    /// Show a clickable hex keypad beside the display (toggle with F7)
+    #[cfg(feature = "sdl")]
+    #[arg(long)]
+    keypad: bool,
+
+    /// Play a WAV file, looped, as the buzzer instead of a tone
+    #[cfg(feature = "sdl")]
+    #[arg(long, value_name = "FILE")]
+    buzzer: Option<PathBuf>,
+
+    /// Seed for the CXNN random number generator, for reproducible runs
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// Emulate the quirks of the interpreter the ROM was written for
+    #[arg(long, value_enum, value_name = "PROFILE")]
+    quirk_profile: Option<QuirkProfile>,
+
+    /// Emulate one quirk that the ROM relies on, on top of the profile;
+    /// repeat for several
+    #[arg(long = "quirk", value_enum, value_name = "QUIRK")]
+    quirks: Vec<QuirkName>,
+
+    /// The interpreter whose built-in hex font to use [default: schip]
+    #[arg(long, value_enum)]
+    font: Option<FontName>,
+
+    /// The machine the ROM was written for [default: detected from the ROM]
+    #[arg(long, value_enum, value_name = "MACHINE")]
+    variant: Option<Machine>,
+
+    /// Hex address the ROM is loaded and started at; ETI-660 ROMs use 600
+    /// [default: 200, or 300 for CHIP-8X]
+    #[arg(long, value_name = "ADDR", value_parser = parse_address)]
+    start_address: Option<u16>,
+
+    /// Apply this IPS or BPS patch to the ROM in memory, leaving the file
+    /// alone; repeat to apply several in order
+    #[arg(long = "patch", value_name = "FILE", requires = "source")]
+    patches: Vec<PathBuf>,
+
+    /// Directory holding the CHIP-8 database's programs.json and
+    /// sha1-hashes.json, used to configure known ROMs
+    /// [default: chip-8-database in the config directory]
+    #[arg(long, value_name = "DIR")]
+    database: Option<PathBuf>,
+
+    /// Don't look the ROM up in the CHIP-8 database
+    #[arg(long, conflicts_with = "database")]
+    no_database: bool,
+
+    #[arg(skip)]
+    known_roms: Option<Database>,
+
+    /// How long each instruction takes
+    #[arg(long, value_enum, value_name = "MODEL", default_value_t = TimingModel::Flat)]
+    timing: TimingModel,
+
+    /// What to do when the ROM reads or writes past the end of memory
+    #[arg(long, value_enum, value_name = "POLICY", default_value_t = Bounds::Error)]
+    bounds: Bounds,
+
+    /// What 0NNN calls to machine code do
+    #[arg(long, value_enum, value_name = "POLICY", default_value_t = Sys::Ignore)]
+    sys: Sys,
+
+    /// Record key input, the ROM hash, and the seed to a replay file
+    #[cfg(feature = "sdl")]
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["replay", "headless"])]
+    record_input: Option<PathBuf>,
+
+    /// Play back key input from a replay file, using its recorded seed
+    #[arg(long, value_name = "FILE", conflicts_with = "keys")]
+    replay: Option<PathBuf>,
+
+    /// Host a two-player netplay session on this port
+    #[cfg(feature = "sdl")]
+    #[arg(long, value_name = "PORT", conflicts_with_all = ["connect", "replay", "record_input", "headless"])]
+    host: Option<u16>,
+
+    /// Join a netplay session at this address, e.g. "192.168.1.5:7000"
+    #[cfg(feature = "sdl")]
+    #[arg(long, value_name = "ADDR", conflicts_with_all = ["seed", "replay", "record_input", "headless"])]
+    connect: Option<String>,
+
+    /// Serve a WebSocket remote-control API on this address, e.g. "127.0.0.1:8765"
+    #[cfg(feature = "remote")]
+    #[arg(long, value_name = "ADDR", conflicts_with = "headless")]
+    remote: Option<String>,
+
+    /// Show the ROM being played on your Discord profile, through the
+    /// Discord application with this ID
+    #[cfg(feature = "discord")]
+    #[arg(long, value_name = "CLIENT_ID", conflicts_with = "headless")]
+    discord: Option<String>,
+
+    /// Continue from where the ROM was left when the window was last closed
+    #[cfg(feature = "sdl")]
+    #[arg(long, requires = "source", conflicts_with_all = ["frontend", "replay", "keys", "record_input", "host", "connect"])]
+    resume: bool,
+
+    /// Reload and reset the machine whenever the ROM file changes
+    #[cfg(feature = "watch")]
+    #[arg(long, conflicts_with_all = ["frontend", "replay", "record_input", "host", "connect"])]
+    watch: bool,
+
+    /// Rhai script whose on_frame() function runs every frame
+    #[cfg(feature = "scripting")]
+    #[arg(long, value_name = "FILE")]
+    script: Option<PathBuf>,
+
+    /// Track which memory the ROM executes, reads, and writes, and save the
+    /// map to this file on exit, as an image if it ends in .png
+    #[arg(long, value_name = "FILE")]
+    coverage: Option<PathBuf>,
+
+    /// Count the instructions run, by opcode, and the frames shown, and save
+    /// them to this file on exit, as CSV if it ends in .csv and JSON otherwise
+    #[arg(long, value_name = "FILE")]
+    stats: Option<PathBuf>,
+
+    /// Keep the state from before each of the last N instructions, so
+    /// comma steps back through them while paused
+    #[cfg(feature = "sdl")]
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    history: usize,
+
+    /// Names for addresses in the ROM, shown in the debug views, one "ADDR
+    /// NAME" per line [default: the ROM's .sym file, if it has one]
+    #[arg(long, value_name = "FILE")]
+    symbols: Option<PathBuf>,
+
+    /// Run without opening a window, then print the final machine state
+    #[arg(long, group = "frontend", requires = "source")]
+    headless: bool,
+
+    /// Render in the terminal with Unicode half blocks instead of an SDL window
+    #[cfg(feature = "terminal")]
+    #[arg(long, group = "frontend", requires = "source")]
+    terminal: bool,
+
+    /// Run the ratatui interface with play and debug views
+    #[cfg(feature = "terminal")]
+    #[arg(long, group = "frontend", requires = "source")]
+    tui: bool,
+
+    /// Open a winit window rendered with pixels instead of SDL
+    #[cfg(feature = "pixels")]
+    #[arg(long, group = "frontend", requires = "source")]
+    pixels: bool,
+
+    /// Open an egui window of debugger panels: the display, registers,
+    /// disassembly, memory, and breakpoints
+    #[cfg(feature = "egui")]
+    #[arg(long, group = "frontend", requires = "source")]
+    egui: bool,
+
+    /// Number of cycles to execute in headless mode
+    #[arg(long, requires = "headless", default_value_t = 100_000)]
+    cycles: u64,
+
+    /// Write the final display to this file in headless mode
+    #[arg(long, requires = "headless")]
+    dump_display: Option<PathBuf>,
+
+    /// Scripted key input for headless mode, one "<cycle> <down|up> <key>" per line
+    #[arg(long, requires = "headless")]
+    keys: Option<PathBuf>,
+
+    /// Write the state before each instruction to this file in headless
+    /// mode, in the format `verify` reads
+    #[arg(long, value_name = "FILE", requires = "headless")]
+    trace: Option<PathBuf>,
+
+    /// Stop headless mode before the instruction at this hex address or
+    /// symbol, optionally only "if" a condition holds, e.g.
+    /// "draw_player if V3 == 0x1f && SP > 2"; repeat for several
+    #[arg(long = "break", value_name = "ADDR|SYMBOL", requires = "headless")]
+    breakpoints: Vec<String>,
+
+    /// Stop headless mode before any instruction of this kind, wherever it
+    /// is; repeat for several
+    #[arg(long, value_enum, value_name = "EVENT", requires = "headless")]
+    break_on: Vec<BreakEvent>,
+}
+
+impl RunArgs {
+    #[cfg(any(
+        feature = "sdl",
+        feature = "terminal",
+        feature = "pixels",
+        feature = "egui"
+    ))]
+    fn palette(&self, config: &Config) -> Palette {
+        let mut palette = Palette::named(self.palette.unwrap_or(config.palette));
+        if let Some(color) = self.foreground.or(config.foreground()) {
+            palette.foreground = color;
+        }
+        if let Some(color) = self.background.or(config.background()) {
+            palette.background = color;
+        }
+        palette
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum BuiltinRom {
+    /// Draws "CHIP-8"
+    Logo,
+    /// Checks the common instructions and draws "OK" or the failing test
+    Test,
+}
+
+impl BuiltinRom {
+    #[cfg(feature = "sdl")]
+    fn name(self) -> &'static str {
+        match self {
+            BuiltinRom::Logo => "logo",
+            BuiltinRom::Test => "test",
+        }
+    }
+
+    fn rom(self) -> &'static [u8] {
+        match self {
+            BuiltinRom::Logo => builtin::LOGO,
+            BuiltinRom::Test => builtin::OPCODE_TEST,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum QuirkProfile {
+    /// This interpreter's own behaviour, shared by most modern ones
+    Modern,
+    /// The original COSMAC VIP interpreter
+    Vip,
+    /// SUPER-CHIP 1.1
+    Schip,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum QuirkName {
+    /// 8XY6 and 8XYE shift VY into VX, as on the COSMAC VIP
+    Shift,
+    /// FX55 and FX65 advance I past the registers, as on the COSMAC VIP
+    LoadStore,
+    /// BXNN jumps to XNN plus VX, as on SCHIP
+    Jump,
+    /// 8XY1, 8XY2, and 8XY3 clear VF, as on the COSMAC VIP
+    VfReset,
+    /// DXYN wraps sprites around the edges of the display, as XO-CHIP does
+    Wrap,
+    /// DXYN waits for the next 60Hz frame, as on the COSMAC VIP
+    DisplayWait,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+enum FontName {
+    /// CHIP-48 and SUPER-CHIP, with large digits for FX30
+    #[default]
+    Schip,
+    /// The COSMAC VIP
+    Vip,
+    /// The Dream 6800
+    Dream6800,
+    /// The ETI-660
+    Eti660,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Machine {
+    /// CHIP-8
+    #[value(name = "chip-8")]
+    Chip8,
+    /// CHIP-8X, with color
+    #[value(name = "chip-8x")]
+    Chip8X,
+    /// Two-page hi-res CHIP-8, with a 64x64 display
+    HiRes,
+}
 
-    let mut args = env::args().into_iter();
-    args.next();
-    match args.next() {
-        Some(path) => chip8.load_rom(&path),
-        None => panic!("Usage: cargo run <path-to-rom>"),
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum TimingModel {
+    /// The same for every instruction, 600 a second
+    Flat,
+    /// The COSMAC VIP's machine cycles for each instruction
+    Vip,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Bounds {
+    /// Stop with an error
+    Error,
+    /// Wrap around to the start of memory
+    Wrap,
+    /// Freeze on the instruction
+    Halt,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum BreakEvent {
+    /// DXYN
+    Draw,
+    /// EX9E, EXA1, and FX0A
+    Key,
+    /// FX18
+    Sound,
+    /// FX33
+    Bcd,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Sys {
+    /// Skip them
+    Ignore,
+    /// Skip them, warning once per address
+    Warn,
+    /// Stop with an error
+    Error,
+}
+
+impl RunArgs {
+    fn font(&self, config: &Config) -> FontSet {
+        match self.font.unwrap_or(config.font) {
+            FontName::Schip => FontSet::SCHIP,
+            FontName::Vip => FontSet::VIP,
+            FontName::Dream6800 => FontSet::DREAM_6800,
+            FontName::Eti660 => FontSet::ETI_660,
+        }
+    }
+
+    // What the CHIP-8 database knows about `rom`, if it was loaded.
+    fn rom_settings(&self, rom: &[u8]) -> Option<RomSettings> {
+        self.known_roms.as_ref()?.lookup(rom)
+    }
+
+    fn variant(&self, rom: &[u8]) -> Variant {
+        match self.variant {
+            None => self
+                .rom_settings(rom)
+                .and_then(|settings| settings.variant)
+                .unwrap_or_else(|| Variant::detect(rom)),
+            Some(Machine::Chip8) => Variant::Chip8,
+            Some(Machine::Chip8X) => Variant::Chip8X,
+            Some(Machine::HiRes) => Variant::HiRes,
+        }
+    }
+
+    fn start_address(&self, rom: &[u8]) -> u16 {
+        self.start_address
+            .unwrap_or_else(|| self.variant(rom).start_address())
+    }
+
+    fn cycles_per_frame(&self, rom: &[u8]) -> u32 {
+        self.rom_settings(rom)
+            .and_then(|settings| settings.tickrate)
+            .unwrap_or(my_chip8::CYCLES_PER_FRAME)
+    }
+
+    fn timing(&self) -> Timing {
+        match self.timing {
+            TimingModel::Flat => Timing::Flat,
+            TimingModel::Vip => Timing::Vip,
+        }
+    }
+
+    fn sys_policy(&self) -> SysPolicy {
+        match self.sys {
+            Sys::Ignore => SysPolicy::Ignore,
+            Sys::Warn => SysPolicy::Warn,
+            Sys::Error => SysPolicy::Error,
+        }
+    }
+
+    fn bounds_policy(&self) -> BoundsPolicy {
+        match self.bounds {
+            Bounds::Error => BoundsPolicy::Error,
+            Bounds::Wrap => BoundsPolicy::Wrap,
+            Bounds::Halt => BoundsPolicy::Halt,
+        }
+    }
+
+    fn break_events(&self) -> impl Iterator<Item = Event> + '_ {
+        self.break_on.iter().map(|event| match event {
+            BreakEvent::Draw => Event::Draw,
+            BreakEvent::Key => Event::Key,
+            BreakEvent::Sound => Event::Sound,
+            BreakEvent::Bcd => Event::Bcd,
+        })
+    }
+
+    fn quirks(&self, rom: &[u8]) -> Quirks {
+        let profile = match self.quirk_profile {
+            None => self
+                .rom_settings(rom)
+                .and_then(|settings| settings.quirks)
+                .unwrap_or_default(),
+            Some(profile) => profile.quirks(),
+        };
+        with_quirks(profile, &self.quirks)
+    }
+
+    // A machine running `rom` with the font, start address, quirks, variant,
+    // timing, and policies given, or what the CHIP-8 database knows about it.
+    fn machine(&self, rom: &[u8], config: &Config, seed: Option<u64>) -> Chip8 {
+        let mut builder = Chip8::builder()
+            .font(self.font(config))
+            .rom_bytes(rom)
+            .quirks(self.quirks(rom))
+            .variant(self.variant(rom))
+            .cycles_per_frame(self.cycles_per_frame(rom))
+            .timing(self.timing())
+            .bounds_policy(self.bounds_policy())
+            .sys_policy(self.sys_policy());
+        if let Some(addr) = self.start_address {
+            builder = builder.start_address(addr);
+        }
+        if let Some(seed) = seed {
+            builder = builder.seed(seed);
+        }
+        let mut chip8 = builder.build().unwrap_or_else(|e| {
+            eprintln!("Failed to load the ROM: {e}");
+            process::exit(1);
+        });
+        chip8.set_coverage(self.coverage.is_some());
+        #[cfg(feature = "sdl")]
+        chip8.set_history_depth(self.history);
+        chip8
+    }
+}
+
+impl QuirkProfile {
+    fn quirks(self) -> Quirks {
+        match self {
+            QuirkProfile::Modern => Quirks::default(),
+            QuirkProfile::Vip => Quirks::VIP,
+            QuirkProfile::Schip => Quirks::SCHIP,
+        }
+    }
+}
+
+// A profile's quirks with the named ones turned on as well.
+fn with_quirks(mut quirks: Quirks, names: &[QuirkName]) -> Quirks {
+    for quirk in names {
+        match quirk {
+            QuirkName::Shift => quirks.shift_vy = true,
+            QuirkName::LoadStore => quirks.load_store_increments_i = true,
+            QuirkName::Jump => quirks.jump_vx = true,
+            QuirkName::VfReset => quirks.logic_resets_vf = true,
+            QuirkName::Wrap => quirks.wrap_sprites = true,
+            QuirkName::DisplayWait => quirks.display_wait = true,
+        }
+    }
+    quirks
+}
+
+fn main() {
+    logger::init();
+    let cli = Cli::parse();
+    let mut args = match cli.command {
+        Some(Command::Run(args)) => args,
+        Some(Command::Bench(args)) => return bench::run(&args),
+        Some(Command::Info(args)) => return info::run(&args),
+        Some(Command::Decompile(args)) => return decompile(&args),
+        Some(Command::Analyze(args)) => return analyze(&args),
+        Some(Command::Verify(args)) => return verify::run(&args),
+        None => cli.run,
     };
+    #[allow(unused_mut, unused_variables)]
+    let mut config = Config::load();
 
-    let sdl_context = sdl2::init().unwrap();
-    let video_subsystem = sdl_context.video().unwrap();
-
-    let window = video_subsystem
-        .window("CHIP-8 Emulator", 1280, 640)
-        .position_centered()
-        .build()
-        .unwrap();
-
-    let mut canvas = window.into_canvas().build().unwrap();
-
-    canvas.set_scale(PIXEL_SIZE, PIXEL_SIZE).unwrap();
-    canvas.set_draw_color(BACKGROUND_COLOR);
-    canvas.clear();
-    canvas.present();
-
-    let scancode_to_key = HashMap::from([
-        (Scancode::Num1, 0x1),
-        (Scancode::Num2, 0x2),
-        (Scancode::Num3, 0x3),
-        (Scancode::Num4, 0xc),
-        (Scancode::Q, 0x4),
-        (Scancode::W, 0x5),
-        (Scancode::E, 0x6),
-        (Scancode::R, 0xd),
-        (Scancode::A, 0x7),
-        (Scancode::S, 0x8),
-        (Scancode::D, 0x9),
-        (Scancode::F, 0xe),
-        (Scancode::Z, 0xa),
-        (Scancode::X, 0x0),
-        (Scancode::C, 0xb),
-        (Scancode::V, 0xf),
-    ]);
-
-    let mut event_pump = sdl_context.event_pump().unwrap();
-    'running: loop {
-        chip8.run_cycle();
-
-        if chip8.draw_flag() {
-            canvas.set_draw_color(BACKGROUND_COLOR);
-            canvas.clear();
-
-            canvas.set_draw_color(FOREGROUND_COLOR);
-            chip8.display().iter().enumerate().for_each(|(i, &pixel)| {
-                if pixel == 1 {
-                    let x = (i % my_chip8::DISPLAY_WIDTH) as i32;
-                    let y = (i / my_chip8::DISPLAY_WIDTH) as i32;
-                    canvas.draw_point(Point::new(x, y)).unwrap();
-                }
+    if !args.no_database {
+        args.known_roms = Database::load(args.database.as_deref()).unwrap_or_else(|e| {
+            eprintln!("{e}");
+            process::exit(1);
+        });
+    }
+
+    let mut recent = RecentRoms::load();
+    if let Some(n) = args.recent {
+        let path = recent.get(n as usize).unwrap_or_else(|| {
+            eprintln!("There is no recent ROM number {n}");
+            process::exit(1);
+        });
+        args.rom = Some(path.to_string_lossy().into_owned());
+    }
+
+    // Without a ROM the SDL window starts empty and waits for one to be dropped.
+    let rom = match &args.rom {
+        Some(path) => {
+            let rom = read_rom(Path::new(path), &args).unwrap_or_else(|e| {
+                eprintln!("{e}");
+                process::exit(1);
             });
+            // Headless runs are usually scripted, so they don't count as opening a ROM.
+            if !args.headless {
+                recent.add(Path::new(path));
+            }
+            rom
+        }
+        #[cfg(feature = "sdl")]
+        None if args.latency => latency::PROBE_ROM.to_vec(),
+        None => match args.builtin {
+            Some(builtin) => builtin.rom().to_vec(),
+            None => Vec::new(),
+        },
+    };
+    // The database's colors suit the ROM better than the configured ones,
+    // but not better than a palette asked for on the command line.
+    if let Some(settings) = args.rom_settings(&rom).filter(|_| args.palette.is_none()) {
+        args.foreground = args.foreground.or(settings.foreground);
+        args.background = args.background.or(settings.background);
+    }
+    #[allow(unused_mut)]
+    let (mut seed, replay_mode) = replay_mode(&args, &rom).unwrap_or_else(|e| {
+        eprintln!("{e}");
+        process::exit(1);
+    });
+    #[cfg(feature = "sdl")]
+    let netplay = netplay::start(&args, &rom, &mut seed).unwrap_or_else(|e| {
+        eprintln!("Netplay failed: {e}");
+        process::exit(1);
+    });
+
+    let mut chip8 = args.machine(&rom, &config, seed);
+
+    // Stored flags would make runs that must be reproducible depend on what
+    // was played before.
+    #[allow(unused_mut)]
+    let mut persist_flags =
+        args.rom.is_some() && !args.headless && matches!(replay_mode, ReplayMode::Off);
+    #[cfg(feature = "sdl")]
+    {
+        persist_flags &= netplay.is_none();
+    }
+    if persist_flags {
+        use_flag_file(&mut chip8, &rom);
+    }
+
+    if args.headless {
+        let player = match replay_mode {
+            ReplayMode::Play(player) => Some(player),
+            _ => None,
+        };
+        return headless::run(&args, chip8, player);
+    }
+
+    #[cfg(feature = "terminal")]
+    if args.terminal {
+        return terminal::run(&args, &config, chip8);
+    } else if args.tui {
+        return tui::run(&args, &config, chip8);
+    }
 
-            canvas.present();
-        }
-
-        for event in event_pump.poll_iter() {
-            match event {
-                Event::KeyDown {
-                    scancode: Some(scancode),
-                    ..
-                } => {
-                    if let Some(&key) = scancode_to_key.get(&scancode) {
-                        chip8.key_down(key);
-                    }
-                }
-                Event::KeyUp {
-                    scancode: Some(scancode),
-                    ..
-                } => {
-                    if let Some(&key) = scancode_to_key.get(&scancode) {
-                        chip8.key_up(key);
-                    }
-                }
-                Event::Quit { .. } => break 'running,
-                _ => {}
+    #[cfg(feature = "pixels")]
+    if args.pixels {
+        pixels_frontend::run(&args, &config, chip8);
+    }
+
+    #[cfg(feature = "egui")]
+    if args.egui {
+        return gui::run(&args, &config, chip8);
+    }
+
+    #[cfg(feature = "sdl")]
+    sdl::run(&args, &mut config, &rom, chip8, replay_mode, netplay);
+
+    #[cfg(not(feature = "sdl"))]
+    {
+        eprintln!("This build has no SDL support; choose another frontend (see --help)");
+        process::exit(1);
+    }
+}
+
+// Reads a ROM, checking that it fits in memory from where `args` start it.
+fn read_rom(path: &Path, args: &RunArgs) -> Result<Vec<u8>, String> {
+    let mut rom =
+        fs::read(path).map_err(|e| format!("Failed to load ROM {}: {e}", path.display()))?;
+    // Patches are for the ROM named on the command line, not ones dropped
+    // on the window later.
+    if args.rom.as_deref().map(Path::new) == Some(path) {
+        for patch in &args.patches {
+            rom = patch::apply_file(&rom, patch)?;
+        }
+    }
+    let max_size = MEM_SIZE - args.start_address(&rom) as usize;
+    if rom.len() > max_size {
+        return Err(format!(
+            "{} is too large to be a CHIP-8 ROM ({} bytes, at most {max_size})",
+            path.display(),
+            rom.len()
+        ));
+    }
+    Ok(rom)
+}
+
+fn decompile(args: &DecompileArgs) {
+    let rom = fs::read(&args.rom).unwrap_or_else(|e| {
+        eprintln!("Failed to load ROM {}: {e}", args.rom.display());
+        process::exit(1);
+    });
+    let source = my_chip8::decompile::decompile(&rom);
+    match &args.output {
+        Some(path) => {
+            if let Err(e) = fs::write(path, source) {
+                eprintln!("Failed to write {}: {e}", path.display());
+                process::exit(1);
             }
         }
+        None => print!("{source}"),
+    }
+}
+
+fn analyze(args: &AnalyzeArgs) {
+    let rom = fs::read(&args.rom).unwrap_or_else(|e| {
+        eprintln!("Failed to load ROM {}: {e}", args.rom.display());
+        process::exit(1);
+    });
+    let analysis = my_chip8::analysis::analyze(&rom);
+
+    let code: usize = analysis
+        .blocks
+        .iter()
+        .map(|block| 2 * block.instructions.len())
+        .sum();
+    println!("{} blocks, {code} bytes of code", analysis.blocks.len());
+    if !analysis.unreached.is_empty() {
+        println!("Unreached:");
+        for region in &analysis.unreached {
+            let len = region.end - region.start;
+            let loaded = if region.loaded { ", loaded into I" } else { "" };
+            println!(
+                "  {:#05x}-{:#05x}  {len} bytes{loaded}",
+                region.start,
+                region.end - 1
+            );
+        }
+    }
+    if !analysis.issues.is_empty() {
+        println!("Issues:");
+        for issue in &analysis.issues {
+            println!("  {issue}");
+        }
+    }
+
+    if let Some(path) = &args.dot {
+        if let Err(e) = fs::write(path, analysis.to_dot()) {
+            eprintln!("Failed to write {}: {e}", path.display());
+            process::exit(1);
+        }
+    }
+}
+
+fn parse_address(s: &str) -> Result<u16, String> {
+    let hex = s.strip_prefix("0x").unwrap_or(s);
+    match u16::from_str_radix(hex, 16) {
+        Ok(addr) if (addr as usize) < MEM_SIZE => Ok(addr),
+        _ => Err(format!("expected a hex address below {MEM_SIZE:#x}")),
+    }
+}
+
+// Keeps the SCHIP flag registers (FX75/FX85) for `rom` in the config
+// directory, so games' high scores persist between runs.
+fn use_flag_file(chip8: &mut Chip8, rom: &[u8]) {
+    let Some(dir) = config::config_dir().map(|dir| dir.join(FLAGS_DIR)) else {
+        return;
+    };
+    let path = dir.join(format!("{:016x}.flags", replay::rom_hash(rom)));
+    if let Err(e) = fs::create_dir_all(&dir).and_then(|()| chip8.set_flag_file(&path)) {
+        eprintln!("Failed to use flag file {}: {e}", path.display());
+    }
+}
+
+// Picks the seed and replay mode, preferring the seed stored in a replay.
+fn replay_mode(args: &RunArgs, rom: &[u8]) -> Result<(Option<u64>, ReplayMode), String> {
+    if let Some(path) = args.replay.as_ref().or(args.keys.as_ref()) {
+        let replay = Replay::load(path)?;
+        if replay
+            .rom_hash
+            .is_some_and(|hash| hash != replay::rom_hash(rom))
+        {
+            eprintln!(
+                "Warning: {} was recorded with a different ROM",
+                path.display()
+            );
+        }
+        return Ok((replay.seed.or(args.seed), ReplayMode::Play(replay.play())));
+    }
 
-        std::thread::sleep(Duration::from_secs_f64(1.0 / my_chip8::CYCLES_PER_SECOND));
+    #[cfg(feature = "sdl")]
+    if args.record_input.is_some() {
+        let seed = args.seed.unwrap_or_else(rand::random);
+        return Ok((Some(seed), ReplayMode::Record(Replay::new(rom, seed))));
     }
+
+    Ok((args.seed, ReplayMode::Off))
 }
@@ -0,0 +1,60 @@
+use crate::replay;
+use crate::RunArgs;
+use std::io::{self, Read, Write};
+use std::net::{TcpListener, TcpStream};
+
+// Both peers run the same ROM with the same seed and swap their local keypad
+// masks once per frame, so each applies the same combined input on the same
+// cycle and the two machines stay in lockstep.
+pub struct Netplay {
+    stream: TcpStream,
+}
+
+// Hosts or joins a session if requested. The host picks the seed (random
+// unless one was given) and sends it to the guest along with the ROM hash.
+pub fn start(args: &RunArgs, rom: &[u8], seed: &mut Option<u64>) -> io::Result<Option<Netplay>> {
+    let stream = if let Some(port) = args.host {
+        let listener = TcpListener::bind(("0.0.0.0", port))?;
+        println!("Waiting for a player to connect on port {port}");
+        let (mut stream, peer) = listener.accept()?;
+        println!("{peer} joined");
+
+        let seed = *seed.get_or_insert_with(rand::random);
+        stream.write_all(&replay::rom_hash(rom).to_be_bytes())?;
+        stream.write_all(&seed.to_be_bytes())?;
+        stream
+    } else if let Some(addr) = &args.connect {
+        let mut stream = TcpStream::connect(addr)?;
+        println!("Connected to {addr}");
+
+        if read_u64(&mut stream)? != replay::rom_hash(rom) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "the host is running a different ROM",
+            ));
+        }
+        *seed = Some(read_u64(&mut stream)?);
+        stream
+    } else {
+        return Ok(None);
+    };
+
+    stream.set_nodelay(true)?;
+    Ok(Some(Netplay { stream }))
+}
+
+impl Netplay {
+    // Sends the local keypad mask and blocks until the peer's arrives.
+    pub fn exchange(&mut self, local_keys: u16) -> io::Result<u16> {
+        self.stream.write_all(&local_keys.to_be_bytes())?;
+        let mut remote_keys = [0; 2];
+        self.stream.read_exact(&mut remote_keys)?;
+        Ok(u16::from_be_bytes(remote_keys))
+    }
+}
+
+fn read_u64(stream: &mut TcpStream) -> io::Result<u64> {
+    let mut bytes = [0; 8];
+    stream.read_exact(&mut bytes)?;
+    Ok(u64::from_be_bytes(bytes))
+}
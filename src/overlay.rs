@@ -0,0 +1,156 @@
+use crate::palette::Palette;
+use crate::perf::PerfCounter;
+use crate::text::{self, TEXT_HEIGHT, TEXT_WIDTH};
+use my_chip8::disasm;
+use my_chip8::{CpuState, Symbols};
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::Canvas;
+use sdl2::video::Window;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+const TEXT_SIZE: u32 = 2;
+const PADDING: i32 = 4;
+const HISTORY_LEN: usize = 6;
+const MESSAGE_TIME: Duration = Duration::from_secs(2);
+
+// Readouts drawn over the game, each toggled with its own key.
+#[derive(Debug, Default)]
+pub struct Overlay {
+    pub perf: bool,
+    pub debug: bool,
+    // Names shown for addresses in the debug HUD.
+    pub symbols: Symbols,
+    history: VecDeque<(u16, u16)>,
+    // A confirmation shown in the bottom-left corner, and when it appeared.
+    message: Option<(String, Instant)>,
+}
+
+impl Overlay {
+    // Notes the instruction about to run, for the debug HUD's history. Does
+    // nothing while the HUD is hidden.
+    pub fn record(&mut self, pc: u16, opcode: u16) {
+        if !self.debug {
+            return;
+        }
+        if self.history.len() == HISTORY_LEN {
+            self.history.pop_front();
+        }
+        self.history.push_back((pc, opcode));
+    }
+
+    pub fn toggle_debug(&mut self) {
+        self.debug = !self.debug;
+        self.history.clear();
+    }
+
+    pub fn show_message(&mut self, message: String) {
+        self.message = Some((message, Instant::now()));
+    }
+
+    // Drops the message once it has been up for long enough, returning true
+    // if the window needs redrawing.
+    pub fn expire_message(&mut self) -> bool {
+        let expired = self
+            .message
+            .as_ref()
+            .is_some_and(|(_, shown)| shown.elapsed() >= MESSAGE_TIME);
+        if expired {
+            self.message = None;
+        }
+        expired
+    }
+
+    pub fn draw(
+        &self,
+        canvas: &mut Canvas<Window>,
+        state: &CpuState,
+        perf: &PerfCounter,
+        palette: &Palette,
+    ) {
+        if self.debug {
+            draw_panel(canvas, &self.debug_lines(state), PADDING, 0, palette);
+        }
+        if self.perf {
+            let mut readout = vec![format!("{} FPS  {} IPS", perf.fps(), perf.ips())];
+            if let Some((average, max)) = perf.jitter() {
+                readout.push(format!(
+                    "JITTER {:.2}/{:.2} MS",
+                    average.as_secs_f64() * 1000.0,
+                    max.as_secs_f64() * 1000.0
+                ));
+            }
+            let width = readout
+                .iter()
+                .map(|line| text::width(line, TEXT_SIZE))
+                .max()
+                .unwrap();
+            draw_panel(canvas, &readout, TEXT_WIDTH - width - PADDING, 0, palette);
+        }
+        if let Some((message, _)) = &self.message {
+            let y = TEXT_HEIGHT - text::height(TEXT_SIZE) - 2 * PADDING;
+            draw_panel(canvas, std::slice::from_ref(message), PADDING, y, palette);
+        }
+    }
+
+    // The registers, timers, and the last few instructions, oldest first.
+    fn debug_lines(&self, state: &CpuState) -> Vec<String> {
+        let mut lines = vec![
+            format!("PC {:#05x}  I {:#05x}", state.pc, state.i),
+            format!(
+                "SP {}  DT {}  ST {}",
+                state.sp, state.delay_timer, state.sound_timer
+            ),
+        ];
+        lines.extend(state.v.chunks(4).enumerate().map(|(row, regs)| {
+            regs.iter()
+                .enumerate()
+                .map(|(col, v)| format!("V{:X} {:02x}", row * 4 + col, v))
+                .collect::<Vec<_>>()
+                .join("  ")
+        }));
+        lines.extend(self.history.iter().map(|&(pc, opcode)| {
+            format!(
+                "{} {:04x} {}",
+                self.symbols.label(pc),
+                opcode,
+                disasm::disassemble_with(opcode, &self.symbols)
+            )
+        }));
+        lines
+    }
+}
+
+// Draws lines of text on a box of the background color, so they stay
+// readable over the game.
+fn draw_panel(canvas: &mut Canvas<Window>, lines: &[String], x: i32, y: i32, palette: &Palette) {
+    let line_height = text::height(TEXT_SIZE) + PADDING;
+    let width = lines
+        .iter()
+        .map(|line| text::width(line, TEXT_SIZE))
+        .max()
+        .unwrap_or(0);
+    let height = lines.len() as i32 * line_height;
+    text::fill_rect(
+        canvas,
+        Rect::new(
+            x - PADDING,
+            y,
+            (width + 2 * PADDING) as u32,
+            (height + PADDING) as u32,
+        ),
+        Color::from(palette.background),
+    );
+    for (n, line) in lines.iter().enumerate() {
+        let top = y + PADDING + n as i32 * line_height;
+        text::draw(
+            canvas,
+            line,
+            x,
+            top,
+            TEXT_SIZE,
+            Color::from(palette.foreground),
+        );
+    }
+}
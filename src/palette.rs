@@ -0,0 +1,92 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rgb {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Rgb {
+    pub const fn new(r: u8, g: u8, b: u8) -> Rgb {
+        Rgb { r, g, b }
+    }
+}
+
+impl FromStr for Rgb {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Rgb, String> {
+        let hex = s.strip_prefix('#').unwrap_or(s);
+        if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(format!("invalid hex color: {s}"));
+        }
+
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).unwrap();
+        Ok(Rgb::new(channel(0), channel(2), channel(4)))
+    }
+}
+
+impl std::fmt::Display for Rgb {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+}
+
+impl From<[u8; 3]> for Rgb {
+    fn from([r, g, b]: [u8; 3]) -> Rgb {
+        Rgb { r, g, b }
+    }
+}
+
+#[cfg(feature = "sdl")]
+impl From<Rgb> for sdl2::pixels::Color {
+    fn from(rgb: Rgb) -> sdl2::pixels::Color {
+        sdl2::pixels::Color::RGB(rgb.r, rgb.g, rgb.b)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PaletteName {
+    #[default]
+    HighContrast,
+    Green,
+    Amber,
+    Octo,
+}
+
+#[cfg(any(
+    feature = "sdl",
+    feature = "terminal",
+    feature = "pixels",
+    feature = "egui"
+))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Palette {
+    pub background: Rgb,
+    pub foreground: Rgb,
+}
+
+#[cfg(any(
+    feature = "sdl",
+    feature = "terminal",
+    feature = "pixels",
+    feature = "egui"
+))]
+impl Palette {
+    pub fn named(name: PaletteName) -> Palette {
+        let (background, foreground) = match name {
+            PaletteName::HighContrast => (Rgb::new(0x00, 0x00, 0x00), Rgb::new(0xff, 0xff, 0xff)),
+            PaletteName::Green => (Rgb::new(0x0a, 0x1a, 0x0a), Rgb::new(0x33, 0xff, 0x66)),
+            PaletteName::Amber => (Rgb::new(0x1a, 0x10, 0x00), Rgb::new(0xff, 0xb0, 0x00)),
+            PaletteName::Octo => (Rgb::new(0x99, 0x66, 0x00), Rgb::new(0xff, 0xcc, 0x00)),
+        };
+        Palette {
+            background,
+            foreground,
+        }
+    }
+}
@@ -0,0 +1,188 @@
+use crate::info::crc32;
+use std::fs;
+use std::path::Path;
+
+const IPS_MAGIC: &[u8] = b"PATCH";
+const IPS_EOF: &[u8] = b"EOF";
+const BPS_MAGIC: &[u8] = b"BPS1";
+
+// Applies an IPS or BPS patch file to a ROM image, telling them apart by
+// their headers.
+pub fn apply_file(rom: &[u8], path: &Path) -> Result<Vec<u8>, String> {
+    let patch = fs::read(path).map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+    let patched = if patch.starts_with(IPS_MAGIC) {
+        apply_ips(rom, &patch)
+    } else if patch.starts_with(BPS_MAGIC) {
+        apply_bps(rom, &patch)
+    } else {
+        Err("not an IPS or BPS patch".to_string())
+    };
+    patched.map_err(|e| format!("Failed to apply {}: {e}", path.display()))
+}
+
+// An IPS patch is a list of records, each writing bytes or a run of one byte
+// at an offset, growing the ROM if needed. After the end marker a patch may
+// give the size to cut the ROM to.
+pub fn apply_ips(rom: &[u8], patch: &[u8]) -> Result<Vec<u8>, String> {
+    let mut reader = Reader::new(&patch[IPS_MAGIC.len()..]);
+    let mut rom = rom.to_vec();
+    loop {
+        let offset = reader.bytes(3)?;
+        if offset == IPS_EOF {
+            break;
+        }
+        let offset = be(offset);
+        let size = be(reader.bytes(2)?);
+        let (len, data) = match size {
+            // A run of the same byte.
+            0 => {
+                let len = be(reader.bytes(2)?);
+                (len, vec![reader.byte()?; len])
+            }
+            len => (len, reader.bytes(len)?.to_vec()),
+        };
+        if rom.len() < offset + len {
+            rom.resize(offset + len, 0);
+        }
+        rom[offset..offset + len].copy_from_slice(&data);
+    }
+    if let Ok(size) = reader.bytes(3) {
+        rom.truncate(be(size));
+    }
+    Ok(rom)
+}
+
+// A BPS patch builds the target from copies of the source, of the target so
+// far, and of data in the patch, and carries checksums of all three.
+pub fn apply_bps(rom: &[u8], patch: &[u8]) -> Result<Vec<u8>, String> {
+    const FOOTER_LEN: usize = 12;
+    if patch.len() < BPS_MAGIC.len() + FOOTER_LEN {
+        return Err("the patch is cut short".to_string());
+    }
+    let (body, footer) = patch.split_at(patch.len() - FOOTER_LEN);
+    let checksum = |n: usize| u32::from_le_bytes(footer[4 * n..4 * n + 4].try_into().unwrap());
+    if crc32(&patch[..patch.len() - 4]) != checksum(2) {
+        return Err("the patch is corrupt".to_string());
+    }
+    if crc32(rom) != checksum(0) {
+        return Err("the patch is for a different ROM".to_string());
+    }
+
+    let mut reader = Reader::new(&body[BPS_MAGIC.len()..]);
+    let source_size = reader.number()?;
+    let target_size = reader.number()?;
+    let metadata_size = reader.number()?;
+    reader.bytes(metadata_size)?;
+    if source_size != rom.len() {
+        return Err("the patch is for a different ROM".to_string());
+    }
+
+    let mut target = Vec::with_capacity(target_size);
+    let mut source_offset = 0;
+    let mut target_offset = 0;
+    let invalid = || "the patch copies from outside the ROM".to_string();
+    while !reader.is_empty() {
+        let action = reader.number()?;
+        let len = (action >> 2) + 1;
+        match action & 3 {
+            // Copy from the source at the same offset as the output.
+            0 => {
+                let start = target.len();
+                let copied = rom
+                    .get(start..)
+                    .and_then(|rest| rest.get(..len))
+                    .ok_or_else(invalid)?;
+                target.extend_from_slice(copied);
+            }
+            // Copy from the patch.
+            1 => target.extend_from_slice(reader.bytes(len)?),
+            // Copy from anywhere in the source.
+            2 => {
+                source_offset = reader.offset(source_offset)?;
+                let copied = rom
+                    .get(source_offset..)
+                    .and_then(|rest| rest.get(..len))
+                    .ok_or_else(invalid)?;
+                target.extend_from_slice(copied);
+                source_offset += len;
+            }
+            // Copy from earlier in the target, which may overlap what's
+            // being written, so byte by byte.
+            _ => {
+                target_offset = reader.offset(target_offset)?;
+                for _ in 0..len {
+                    let byte = *target.get(target_offset).ok_or_else(invalid)?;
+                    target.push(byte);
+                    target_offset += 1;
+                }
+            }
+        }
+    }
+
+    if target.len() != target_size || crc32(&target) != checksum(1) {
+        return Err("the patched ROM doesn't match the patch's checksum".to_string());
+    }
+    Ok(target)
+}
+
+fn be(bytes: &[u8]) -> usize {
+    bytes.iter().fold(0, |n, &byte| n << 8 | byte as usize)
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Reader<'a> {
+        Reader { data }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8], String> {
+        if self.data.len() < len {
+            return Err("the patch is cut short".to_string());
+        }
+        let (bytes, rest) = self.data.split_at(len);
+        self.data = rest;
+        Ok(bytes)
+    }
+
+    fn byte(&mut self) -> Result<u8, String> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    // BPS's variable-length numbers, seven bits to a byte with the top bit
+    // marking the last.
+    fn number(&mut self) -> Result<usize, String> {
+        let mut number = 0usize;
+        let mut shift = 1usize;
+        loop {
+            let byte = self.byte()?;
+            number = ((byte & 0x7f) as usize)
+                .checked_mul(shift)
+                .and_then(|bits| number.checked_add(bits))
+                .ok_or("the patch is corrupt")?;
+            if byte & 0x80 != 0 {
+                return Ok(number);
+            }
+            shift = shift.checked_mul(128).ok_or("the patch is corrupt")?;
+            number = number.checked_add(shift).ok_or("the patch is corrupt")?;
+        }
+    }
+
+    // A BPS copy's new offset, moved forwards or backwards from `from`.
+    fn offset(&mut self, from: usize) -> Result<usize, String> {
+        let data = self.number()?;
+        let distance = data >> 1;
+        let offset = if data & 1 == 0 {
+            from.checked_add(distance)
+        } else {
+            from.checked_sub(distance)
+        };
+        offset.ok_or_else(|| "the patch copies from outside the ROM".to_string())
+    }
+}
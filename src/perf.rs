@@ -0,0 +1,95 @@
+use std::time::{Duration, Instant};
+
+const INTERVAL: Duration = Duration::from_secs(1);
+
+// Counts presented frames and executed instructions, and turns them into
+// per-second rates once every interval. Also keeps how far the emulation's
+// frames strayed from their schedule, on average and at worst.
+#[derive(Debug)]
+pub struct PerfCounter {
+    start: Instant,
+    frames: u32,
+    cycles: u32,
+    fps: u32,
+    ips: u32,
+    jitter_total: Duration,
+    jitter_samples: u32,
+    jitter_max: Duration,
+    jitter: (Duration, Duration),
+}
+
+impl Default for PerfCounter {
+    fn default() -> PerfCounter {
+        PerfCounter {
+            start: Instant::now(),
+            frames: 0,
+            cycles: 0,
+            fps: 0,
+            ips: 0,
+            jitter_total: Duration::ZERO,
+            jitter_samples: 0,
+            jitter_max: Duration::ZERO,
+            jitter: (Duration::ZERO, Duration::ZERO),
+        }
+    }
+}
+
+impl PerfCounter {
+    pub fn frame(&mut self) {
+        self.frames += 1;
+    }
+
+    #[cfg(feature = "terminal")]
+    pub fn cycle(&mut self) {
+        self.cycles += 1;
+    }
+
+    #[cfg(feature = "sdl")]
+    pub fn add_cycles(&mut self, cycles: u32) {
+        self.cycles += cycles;
+    }
+
+    #[cfg(feature = "sdl")]
+    pub fn frame_jitter(&mut self, jitter: Duration) {
+        self.jitter_total += jitter;
+        self.jitter_samples += 1;
+        self.jitter_max = self.jitter_max.max(jitter);
+    }
+
+    pub fn fps(&self) -> u32 {
+        self.fps
+    }
+
+    pub fn ips(&self) -> u32 {
+        self.ips
+    }
+
+    // The average and largest frame jitter over the last interval, if any
+    // frames were paced.
+    #[cfg(feature = "sdl")]
+    pub fn jitter(&self) -> Option<(Duration, Duration)> {
+        (self.jitter.1 > Duration::ZERO).then_some(self.jitter)
+    }
+
+    // Recomputes the rates once the interval has passed, returning whether it
+    // did.
+    pub fn update(&mut self) -> bool {
+        let elapsed = self.start.elapsed();
+        if elapsed < INTERVAL {
+            return false;
+        }
+
+        let seconds = elapsed.as_secs_f64();
+        self.fps = (self.frames as f64 / seconds).round() as u32;
+        self.ips = (self.cycles as f64 / seconds).round() as u32;
+        (self.frames, self.cycles) = (0, 0);
+        self.jitter = match self.jitter_samples {
+            0 => (Duration::ZERO, Duration::ZERO),
+            samples => (self.jitter_total / samples, self.jitter_max),
+        };
+        (self.jitter_total, self.jitter_samples) = (Duration::ZERO, 0);
+        self.jitter_max = Duration::ZERO;
+        self.start = Instant::now();
+        true
+    }
+}
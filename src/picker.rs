@@ -0,0 +1,162 @@
+use crate::palette::Palette;
+use crate::text::{self, TEXT_HEIGHT, TEXT_WIDTH};
+use sdl2::keyboard::Scancode;
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::Canvas;
+use sdl2::video::Window;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const ROM_EXTENSIONS: [&str; 2] = ["ch8", "c8"];
+const TEXT_SIZE: u32 = 2;
+const LINE_HEIGHT: i32 = 14;
+const MARGIN: i32 = 8;
+// The title takes the first line.
+const VISIBLE_ROWS: usize = ((TEXT_HEIGHT - 2 * MARGIN) / LINE_HEIGHT - 1) as usize;
+const MAX_NAME_CHARS: usize = ((TEXT_WIDTH - 2 * MARGIN) / (4 * TEXT_SIZE as i32)) as usize;
+
+pub enum PickerAction {
+    None,
+    Load(PathBuf),
+    Close,
+}
+
+// A keyboard-driven list of ROMs. Numbered lists can also be picked from
+// with the number keys.
+pub struct RomPicker {
+    title: &'static str,
+    roms: Vec<PathBuf>,
+    numbered: bool,
+    selected: usize,
+    scroll: usize,
+}
+
+impl RomPicker {
+    // Lists the ROMs in `dir` by name; fails if there are none.
+    pub fn open(dir: &Path) -> Result<RomPicker, String> {
+        let entries = fs::read_dir(dir)
+            .map_err(|e| format!("Failed to list ROMs in {}: {e}", dir.display()))?;
+        let mut roms: Vec<PathBuf> = entries
+            .filter_map(|entry| Some(entry.ok()?.path()))
+            .filter(|path| {
+                path.extension()
+                    .and_then(|ext| ext.to_str())
+                    .is_some_and(|ext| ROM_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()))
+            })
+            .collect();
+        if roms.is_empty() {
+            return Err(format!("No ROMs found in {}", dir.display()));
+        }
+        roms.sort();
+
+        Ok(RomPicker {
+            title: "Select a ROM",
+            roms,
+            numbered: false,
+            selected: 0,
+            scroll: 0,
+        })
+    }
+
+    pub fn recent(roms: &[PathBuf]) -> Result<RomPicker, String> {
+        if roms.is_empty() {
+            return Err("No recent ROMs".to_string());
+        }
+
+        Ok(RomPicker {
+            title: "Recent ROMs",
+            roms: roms.to_vec(),
+            numbered: true,
+            selected: 0,
+            scroll: 0,
+        })
+    }
+
+    pub fn handle_key(&mut self, scancode: Scancode) -> PickerAction {
+        let last = self.roms.len() - 1;
+        self.selected = match scancode {
+            Scancode::Up => self.selected.saturating_sub(1),
+            Scancode::Down => (self.selected + 1).min(last),
+            Scancode::PageUp => self.selected.saturating_sub(VISIBLE_ROWS),
+            Scancode::PageDown => (self.selected + VISIBLE_ROWS).min(last),
+            Scancode::Home => 0,
+            Scancode::End => last,
+            Scancode::Return | Scancode::KpEnter => {
+                return PickerAction::Load(self.roms[self.selected].clone());
+            }
+            Scancode::Escape => return PickerAction::Close,
+            _ if self.numbered => {
+                return match number_key(scancode).and_then(|n| self.roms.get(n - 1)) {
+                    Some(rom) => PickerAction::Load(rom.clone()),
+                    None => PickerAction::None,
+                };
+            }
+            _ => return PickerAction::None,
+        };
+
+        if self.selected < self.scroll {
+            self.scroll = self.selected;
+        } else if self.selected >= self.scroll + VISIBLE_ROWS {
+            self.scroll = self.selected + 1 - VISIBLE_ROWS;
+        }
+        PickerAction::None
+    }
+
+    pub fn render(&self, canvas: &mut Canvas<Window>, palette: &Palette) {
+        canvas.set_draw_color(Color::from(palette.background));
+        canvas.clear();
+
+        // The selected row is highlighted by drawing it inverted.
+        let row_top = |row: usize| MARGIN + (row as i32 + 1) * LINE_HEIGHT;
+        let selected_row = self.selected - self.scroll;
+        text::fill_rect(
+            canvas,
+            Rect::new(
+                0,
+                row_top(selected_row) - (LINE_HEIGHT - text::height(TEXT_SIZE)) / 2,
+                TEXT_WIDTH as u32,
+                LINE_HEIGHT as u32,
+            ),
+            Color::from(palette.foreground),
+        );
+
+        let title = format!("{} ({}/{})", self.title, self.selected + 1, self.roms.len());
+        let foreground = Color::from(palette.foreground);
+        text::draw(canvas, &title, MARGIN, MARGIN, TEXT_SIZE, foreground);
+        for (row, rom) in self.roms[self.scroll..]
+            .iter()
+            .take(VISIBLE_ROWS)
+            .enumerate()
+        {
+            let mut name = rom.file_name().unwrap_or_default().to_string_lossy();
+            if self.numbered {
+                name = format!("{} {name}", self.scroll + row + 1).into();
+            }
+            let name: String = name.chars().take(MAX_NAME_CHARS).collect();
+            let color = if row == selected_row {
+                Color::from(palette.background)
+            } else {
+                foreground
+            };
+            text::draw(canvas, &name, MARGIN, row_top(row), TEXT_SIZE, color);
+        }
+
+        canvas.present();
+    }
+}
+
+fn number_key(scancode: Scancode) -> Option<usize> {
+    let keys = [
+        Scancode::Num1,
+        Scancode::Num2,
+        Scancode::Num3,
+        Scancode::Num4,
+        Scancode::Num5,
+        Scancode::Num6,
+        Scancode::Num7,
+        Scancode::Num8,
+        Scancode::Num9,
+    ];
+    keys.iter().position(|&key| key == scancode).map(|i| i + 1)
+}
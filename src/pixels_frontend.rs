@@ -0,0 +1,116 @@
+use crate::config::Config;
+use crate::driver::{DisplaySink, Driver};
+use crate::palette::Palette;
+use crate::stats::SessionStats;
+use crate::{keymap, RunArgs};
+use my_chip8::{Chip8, FrameBuffer};
+use pixels::{Pixels, SurfaceTexture};
+use winit::dpi::LogicalSize;
+use winit::event::{ElementState, Event, KeyboardInput, VirtualKeyCode, WindowEvent};
+use winit::event_loop::EventLoop;
+use winit::window::WindowBuilder;
+
+struct PixelsSink {
+    pixels: Pixels,
+    palette: Palette,
+}
+
+impl DisplaySink for PixelsSink {
+    fn present(&mut self, display: &FrameBuffer) {
+        let (width, height) = display.size();
+        let texture = self.pixels.texture();
+        if (texture.width(), texture.height()) != (width as u32, height as u32) {
+            self.pixels
+                .resize_buffer(width as u32, height as u32)
+                .unwrap();
+        }
+        let (fg, bg) = (self.palette.foreground, self.palette.background);
+        display.write_rgba(
+            [fg.r, fg.g, fg.b, 0xff],
+            [bg.r, bg.g, bg.b, 0xff],
+            self.pixels.frame_mut(),
+        );
+        self.pixels.render().unwrap();
+    }
+}
+
+pub fn run(args: &RunArgs, config: &Config, mut chip8: Chip8) -> ! {
+    let scale = args.scale.unwrap_or(config.window.scale) as f64;
+    let (width, height) = chip8.display_size();
+    let event_loop = EventLoop::new();
+    let window = WindowBuilder::new()
+        .with_title("CHIP-8 Emulator")
+        .with_inner_size(LogicalSize::new(
+            width as f64 * scale,
+            height as f64 * scale,
+        ))
+        .with_min_inner_size(LogicalSize::new(width as f64, height as f64))
+        .build(&event_loop)
+        .unwrap();
+
+    let size = window.inner_size();
+    let surface = SurfaceTexture::new(size.width, size.height, &window);
+    let mut sink = PixelsSink {
+        pixels: Pixels::new(width as u32, height as u32, surface).unwrap(),
+        palette: args.palette(config),
+    };
+    let mut stats = SessionStats::start(args, &mut chip8);
+    let mut driver = Driver::new(chip8);
+    let coverage = args.coverage.clone();
+
+    event_loop.run(move |event, _, control_flow| match event {
+        Event::WindowEvent { event, .. } => match event {
+            WindowEvent::CloseRequested => control_flow.set_exit(),
+            WindowEvent::Resized(size) => {
+                sink.pixels.resize_surface(size.width, size.height).unwrap();
+                window.request_redraw();
+            }
+            WindowEvent::KeyboardInput {
+                input:
+                    KeyboardInput {
+                        virtual_keycode: Some(code),
+                        state,
+                        ..
+                    },
+                ..
+            } => {
+                if code == VirtualKeyCode::Escape {
+                    control_flow.set_exit();
+                } else if let Some(key) = keycode_to_key(code) {
+                    match state {
+                        ElementState::Pressed => driver.chip8_mut().key_down(key),
+                        ElementState::Released => driver.chip8_mut().key_up(key),
+                    }
+                }
+            }
+            _ => {}
+        },
+        Event::MainEventsCleared => {
+            if let Err(e) = driver.run_due_cycles() {
+                eprintln!("Emulation error: {e}");
+                control_flow.set_exit_with_code(1);
+                return;
+            }
+            if driver.needs_present() {
+                window.request_redraw();
+            }
+            control_flow.set_wait_until(driver.next_cycle());
+        }
+        Event::RedrawRequested(_) => {
+            driver.present(&mut sink);
+            stats.frame();
+        }
+        Event::LoopDestroyed => {
+            crate::coverage_map::save(coverage.as_deref(), driver.chip8());
+            stats.save();
+        }
+        _ => {}
+    })
+}
+
+// winit names the digit keys Key0 to Key9 and the letter keys by their
+// letters.
+fn keycode_to_key(code: VirtualKeyCode) -> Option<u8> {
+    let name = format!("{code:?}");
+    keymap::name_to_key(name.strip_prefix("Key").unwrap_or(&name))
+}
@@ -0,0 +1,30 @@
+/// What happens when an instruction reads or writes past the end of memory,
+/// through I or by running off the end with PC.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BoundsPolicy {
+    /// The instruction fails with `ErrorKind::AddressOutOfBounds`.
+    #[default]
+    Error,
+    /// Addresses wrap around to the start of memory.
+    Wrap,
+    /// The machine stops on the instruction without failing, as if it were
+    /// spinning in place, and `Chip8::halted` reports it.
+    Halt,
+}
+
+/// What 0NNN does. It called machine code on the original interpreters, so
+/// ROMs that use it can't run here unless the host provides the routine.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SysPolicy {
+    /// The instruction does nothing.
+    #[default]
+    Ignore,
+    /// The instruction does nothing, but logs a warning the first time each
+    /// address is called.
+    Warn,
+    /// The instruction fails with `ErrorKind::MachineCode`.
+    Error,
+    /// The instruction runs the handler set with `Chip8::set_sys_handler`,
+    /// or does nothing if there is none.
+    Callback,
+}
@@ -0,0 +1,119 @@
+use crate::{Chip8, DISPLAY_HEIGHT, DISPLAY_WIDTH};
+use pyo3::exceptions::{PyIOError, PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use std::fs;
+
+#[pyclass(name = "Chip8", module = "my_chip8", unsendable)]
+struct PyChip8 {
+    chip8: Chip8,
+}
+
+#[pymethods]
+impl PyChip8 {
+    #[classattr]
+    const DISPLAY_WIDTH: usize = DISPLAY_WIDTH;
+    #[classattr]
+    const DISPLAY_HEIGHT: usize = DISPLAY_HEIGHT;
+
+    #[new]
+    #[pyo3(signature = (seed = None))]
+    fn new(seed: Option<u64>) -> PyChip8 {
+        let chip8 = match seed {
+            Some(seed) => Chip8::with_seed(seed),
+            None => Chip8::new(),
+        };
+        PyChip8 { chip8 }
+    }
+
+    fn load_rom(&mut self, path: &str) -> PyResult<()> {
+        let rom = fs::read(path).map_err(|e| PyIOError::new_err(format!("{path}: {e}")))?;
+        self.load_rom_bytes(&rom)
+    }
+
+    fn load_rom_bytes(&mut self, rom: &[u8]) -> PyResult<()> {
+        if rom.len() > self.chip8.max_rom_size() {
+            return Err(PyValueError::new_err("ROM is too large"));
+        }
+        self.chip8.load_rom_bytes(rom);
+        Ok(())
+    }
+
+    /// Runs `cycles` instructions and returns whether the display changed.
+    /// Raises RuntimeError if an instruction faults.
+    #[pyo3(signature = (cycles = 1))]
+    fn step(&mut self, cycles: u32) -> PyResult<bool> {
+        let batch = self
+            .chip8
+            .run_cycles(cycles)
+            .map_err(|e| PyRuntimeError::new_err(e.to_string()))?;
+        Ok(batch.drawn)
+    }
+
+    /// The display as width * height bytes of 0 or 1, e.g.
+    /// `numpy.frombuffer(chip8.display(), numpy.uint8).reshape(height, width)`.
+    fn display<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new(py, &self.chip8.display())
+    }
+
+    /// The display's current (width, height) in pixels.
+    fn display_size(&self) -> (usize, usize) {
+        self.chip8.display_size()
+    }
+
+    fn memory<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new(py, self.chip8.memory())
+    }
+
+    fn registers<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new(py, &self.chip8.state().v)
+    }
+
+    #[getter]
+    fn pc(&self) -> u16 {
+        self.chip8.pc()
+    }
+
+    #[getter]
+    fn index(&self) -> u16 {
+        self.chip8.state().i
+    }
+
+    #[getter]
+    fn sound_timer(&self) -> u8 {
+        self.chip8.sound_timer()
+    }
+
+    fn key_down(&mut self, key: u8) -> PyResult<()> {
+        self.chip8.key_down(check_key(key)?);
+        Ok(())
+    }
+
+    fn key_up(&mut self, key: u8) -> PyResult<()> {
+        self.chip8.key_up(check_key(key)?);
+        Ok(())
+    }
+
+    #[setter]
+    fn set_keys(&mut self, mask: u16) {
+        self.chip8.set_keys(mask);
+    }
+
+    #[getter]
+    fn keys(&self) -> u16 {
+        self.chip8.keys()
+    }
+}
+
+fn check_key(key: u8) -> PyResult<u8> {
+    if key < 16 {
+        Ok(key)
+    } else {
+        Err(PyValueError::new_err(format!("invalid key: {key}")))
+    }
+}
+
+#[pymodule]
+fn my_chip8(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyChip8>()
+}
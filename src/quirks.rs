@@ -0,0 +1,46 @@
+/// Behaviours that differ between CHIP-8 interpreters, for running ROMs
+/// written against a particular one. The defaults keep this interpreter's
+/// original behaviour.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Quirks {
+    /// 8XY6 and 8XYE shift VY and store the result in VX, as on the COSMAC
+    /// VIP, instead of shifting VX in place.
+    pub shift_vy: bool,
+    /// FX55 and FX65 leave I pointing past the last register stored or
+    /// loaded, as on the COSMAC VIP, instead of leaving it unchanged.
+    pub load_store_increments_i: bool,
+    /// BXNN jumps to XNN plus VX, as on SCHIP, instead of BNNN jumping to
+    /// NNN plus V0.
+    pub jump_vx: bool,
+    /// 8XY1, 8XY2, and 8XY3 clear VF, as on the COSMAC VIP, instead of
+    /// leaving it alone.
+    pub logic_resets_vf: bool,
+    /// DXYN wraps the parts of sprites that cross an edge of the display
+    /// around to the opposite edge, as XO-CHIP does, instead of clipping them.
+    pub wrap_sprites: bool,
+    /// DXYN waits for the start of the next 60Hz frame before drawing, as on
+    /// the COSMAC VIP, which limits games to one sprite per frame.
+    pub display_wait: bool,
+}
+
+impl Quirks {
+    /// The original COSMAC VIP interpreter.
+    pub const VIP: Quirks = Quirks {
+        shift_vy: true,
+        load_store_increments_i: true,
+        jump_vx: false,
+        logic_resets_vf: true,
+        wrap_sprites: false,
+        display_wait: true,
+    };
+
+    /// SUPER-CHIP 1.1 on the HP48 calculators.
+    pub const SCHIP: Quirks = Quirks {
+        shift_vy: false,
+        load_store_increments_i: false,
+        jump_vx: true,
+        logic_resets_vf: false,
+        wrap_sprites: false,
+        display_wait: false,
+    };
+}
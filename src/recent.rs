@@ -0,0 +1,71 @@
+use crate::config;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const RECENT_FILE: &str = "recent.txt";
+pub const MAX_RECENT: usize = 9;
+
+// The most recently opened ROMs, newest first, kept as one absolute path per
+// line in the config directory.
+#[derive(Debug, Default)]
+pub struct RecentRoms {
+    paths: Vec<PathBuf>,
+}
+
+impl RecentRoms {
+    pub fn load() -> RecentRoms {
+        let paths = recent_path()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .map(|text| {
+                text.lines()
+                    .filter(|line| !line.is_empty())
+                    .map(PathBuf::from)
+                    .take(MAX_RECENT)
+                    .collect()
+            })
+            .unwrap_or_default();
+        RecentRoms { paths }
+    }
+
+    #[cfg(feature = "sdl")]
+    pub fn paths(&self) -> &[PathBuf] {
+        &self.paths
+    }
+
+    // The nth most recent ROM, counting from 1.
+    pub fn get(&self, n: usize) -> Option<&Path> {
+        self.paths.get(n.checked_sub(1)?).map(PathBuf::as_path)
+    }
+
+    // Moves `rom` to the front of the list and saves it.
+    pub fn add(&mut self, rom: &Path) {
+        let rom = fs::canonicalize(rom).unwrap_or_else(|_| rom.to_path_buf());
+        self.paths.retain(|path| *path != rom);
+        self.paths.insert(0, rom);
+        self.paths.truncate(MAX_RECENT);
+        self.save();
+    }
+
+    fn save(&self) {
+        let Some(path) = recent_path() else {
+            return;
+        };
+
+        let mut text = String::new();
+        for rom in &self.paths {
+            text.push_str(&rom.to_string_lossy());
+            text.push('\n');
+        }
+        let result = path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|()| fs::write(&path, text));
+        if let Err(e) = result {
+            eprintln!("Failed to save recent ROMs to {}: {e}", path.display());
+        }
+    }
+}
+
+fn recent_path() -> Option<PathBuf> {
+    config::config_dir().map(|dir| dir.join(RECENT_FILE))
+}
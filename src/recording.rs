@@ -0,0 +1,94 @@
+use crate::palette::Palette;
+use crate::screenshot;
+use my_chip8::FrameBuffer;
+use std::fs::File;
+use std::io::BufWriter;
+use std::iter;
+use std::path::{Path, PathBuf};
+
+const FRAMES_PER_SECOND: u64 = 60;
+
+pub struct Recorder {
+    frames: Vec<FrameBuffer>,
+}
+
+impl Recorder {
+    pub fn new() -> Recorder {
+        Recorder { frames: Vec::new() }
+    }
+
+    pub fn capture(&mut self, display: &FrameBuffer) {
+        self.frames.push(*display);
+    }
+
+    pub fn save(&self, palette: &Palette, scale: u32, dir: &Path) -> Result<PathBuf, String> {
+        let scale = scale.max(1) as usize;
+        // The GIF is sized for the largest display recorded, and frames from
+        // smaller ones sit in its top-left corner.
+        let width = self.frames.iter().map(|f| f.width()).max().unwrap_or(0) * scale;
+        let height = self.frames.iter().map(|f| f.height()).max().unwrap_or(0) * scale;
+        let color_map = [
+            palette.background.r,
+            palette.background.g,
+            palette.background.b,
+            palette.foreground.r,
+            palette.foreground.g,
+            palette.foreground.b,
+        ];
+
+        let path = screenshot::timestamped_path(dir, "gif")?;
+        let file = File::create(&path).map_err(|e| e.to_string())?;
+        let mut encoder = gif::Encoder::new(
+            BufWriter::new(file),
+            width as u16,
+            height as u16,
+            &color_map,
+        )
+        .map_err(|e| e.to_string())?;
+        encoder
+            .set_repeat(gif::Repeat::Infinite)
+            .map_err(|e| e.to_string())?;
+
+        // GIF delays are in centiseconds, so round each frame's timestamp and
+        // merge runs of identical frames into a single longer one.
+        let mut start = 0;
+        while start < self.frames.len() {
+            let mut end = start + 1;
+            while end < self.frames.len() && self.frames[end] == self.frames[start] {
+                end += 1;
+            }
+
+            let display = &self.frames[start];
+            let pixels = scale_frame(display, scale);
+            let mut frame = gif::Frame::from_indexed_pixels(
+                (display.width() * scale) as u16,
+                (display.height() * scale) as u16,
+                pixels,
+                None,
+            );
+            frame.delay = (centiseconds(end) - centiseconds(start)) as u16;
+            encoder.write_frame(&frame).map_err(|e| e.to_string())?;
+
+            start = end;
+        }
+
+        Ok(path)
+    }
+}
+
+fn centiseconds(frame: usize) -> u64 {
+    (frame as u64 * 100 + FRAMES_PER_SECOND / 2) / FRAMES_PER_SECOND
+}
+
+fn scale_frame(display: &FrameBuffer, scale: usize) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(display.width() * display.height() * scale * scale);
+    for row in display.rows() {
+        let line: Vec<u8> = row
+            .flat_map(|lit| iter::repeat_n(lit as u8, scale))
+            .collect();
+        for _ in 0..scale {
+            frame.extend_from_slice(&line);
+        }
+    }
+    frame
+}
@@ -0,0 +1,146 @@
+use my_chip8::Chip8;
+use std::io::{self, ErrorKind};
+use std::net::{TcpListener, TcpStream};
+use std::time::Duration;
+use tungstenite::{Error, Message, WebSocket};
+
+// Clients send text commands:
+//
+//   key down <hex>    key up <hex>    registers    memory <hex addr> <len>
+//
+// and receive binary messages whose first byte says what follows:
+//
+//   0x01 display: 256 bytes, one bit per pixel, row-major, MSB first
+//   0x02 registers: pc (2) i (2) sp dt st v0..vf (16) stack (32), big endian
+//   0x03 memory: address (2) followed by the requested bytes
+//
+// Malformed commands get a text reply starting with "error:".
+const TAG_DISPLAY: u8 = 0x01;
+const TAG_REGISTERS: u8 = 0x02;
+const TAG_MEMORY: u8 = 0x03;
+
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(1);
+
+pub struct RemoteServer {
+    listener: TcpListener,
+    clients: Vec<WebSocket<TcpStream>>,
+}
+
+impl RemoteServer {
+    pub fn bind(addr: &str) -> io::Result<RemoteServer> {
+        let listener = TcpListener::bind(addr)?;
+        listener.set_nonblocking(true)?;
+        println!(
+            "Remote control listening on ws://{}",
+            listener.local_addr()?
+        );
+        Ok(RemoteServer {
+            listener,
+            clients: Vec::new(),
+        })
+    }
+
+    // Accepts new clients and applies any commands they have sent.
+    pub fn poll(&mut self, chip8: &mut Chip8) {
+        while let Ok((stream, peer)) = self.listener.accept() {
+            match handshake(stream) {
+                Ok(socket) => self.clients.push(socket),
+                Err(e) => eprintln!("Remote client {peer} failed to connect: {e}"),
+            }
+        }
+
+        self.clients.retain_mut(|socket| loop {
+            let command = match socket.read() {
+                Ok(Message::Text(command)) => command,
+                Ok(Message::Close(_)) => return false,
+                Ok(_) => continue,
+                Err(Error::Io(e)) if e.kind() == ErrorKind::WouldBlock => return true,
+                Err(_) => return false,
+            };
+            let reply = match handle_command(chip8, &command) {
+                Ok(Some(reply)) => Message::Binary(reply),
+                Ok(None) => continue,
+                Err(e) => Message::Text(format!("error: {e}")),
+            };
+            if !send(socket, reply) {
+                return false;
+            }
+        });
+    }
+
+    pub fn send_display(&mut self, rows: &[u64]) {
+        if self.clients.is_empty() {
+            return;
+        }
+
+        let mut message = vec![TAG_DISPLAY];
+        message.extend(rows.iter().flat_map(|row| row.to_be_bytes()));
+        self.clients
+            .retain_mut(|socket| send(socket, Message::Binary(message.clone())));
+    }
+}
+
+fn handshake(stream: TcpStream) -> Result<WebSocket<TcpStream>, String> {
+    stream.set_nonblocking(false).map_err(|e| e.to_string())?;
+    stream
+        .set_read_timeout(Some(HANDSHAKE_TIMEOUT))
+        .map_err(|e| e.to_string())?;
+    let socket = tungstenite::accept(stream).map_err(|e| e.to_string())?;
+    socket
+        .get_ref()
+        .set_nonblocking(true)
+        .map_err(|e| e.to_string())?;
+    Ok(socket)
+}
+
+// Returns whether the client is still connected. A full send buffer only
+// queues the message, so it is not treated as a disconnect.
+fn send(socket: &mut WebSocket<TcpStream>, message: Message) -> bool {
+    match socket.send(message) {
+        Ok(()) => true,
+        Err(Error::Io(e)) => e.kind() == ErrorKind::WouldBlock,
+        Err(_) => false,
+    }
+}
+
+fn handle_command(chip8: &mut Chip8, command: &str) -> Result<Option<Vec<u8>>, String> {
+    let fields: Vec<&str> = command.split_whitespace().collect();
+    match fields[..] {
+        ["key", action, key] => {
+            let key = u8::from_str_radix(key, 16)
+                .ok()
+                .filter(|&key| key < 16)
+                .ok_or_else(|| format!("invalid key: {key}"))?;
+            match action {
+                "down" => chip8.key_down(key),
+                "up" => chip8.key_up(key),
+                _ => return Err(format!("invalid key action: {action}")),
+            }
+            Ok(None)
+        }
+        ["registers"] => {
+            let state = chip8.state();
+            let mut reply = vec![TAG_REGISTERS];
+            reply.extend(state.pc.to_be_bytes());
+            reply.extend(state.i.to_be_bytes());
+            reply.extend([state.sp, state.delay_timer, state.sound_timer]);
+            reply.extend(state.v);
+            reply.extend(state.stack.iter().flat_map(|addr| addr.to_be_bytes()));
+            Ok(Some(reply))
+        }
+        ["memory", addr, len] => {
+            let invalid = || format!("invalid memory range: {addr} {len}");
+            let addr = u16::from_str_radix(addr, 16).map_err(|_| invalid())?;
+            let len: usize = len.parse().map_err(|_| invalid())?;
+            let bytes = chip8
+                .memory()
+                .get(addr as usize..addr as usize + len)
+                .ok_or_else(invalid)?;
+            let mut reply = vec![TAG_MEMORY];
+            reply.extend(addr.to_be_bytes());
+            reply.extend(bytes);
+            Ok(Some(reply))
+        }
+        _ => Err(format!("unknown command: {command}")),
+    }
+}
@@ -0,0 +1,236 @@
+use crate::palette::{Palette, Rgb};
+use my_chip8::{ColorMap, FrameBuffer, DISPLAY_HEIGHT, DISPLAY_WIDTH};
+use sdl2::pixels::{Color, PixelFormatEnum};
+use sdl2::rect::Rect;
+use sdl2::render::{BlendMode, Canvas, Texture, TextureCreator};
+use sdl2::video::{Window, WindowContext};
+use std::time::Instant;
+
+const PHOSPHOR_DECAY_PER_FRAME: f32 = 0.5;
+const PHOSPHOR_CUTOFF: f32 = 0.05;
+const PHOSPHOR_FRAME_RATE: f32 = 60.0;
+
+// Each CHIP-8 pixel is covered by a CELL_SIZE x CELL_SIZE block of overlay texels.
+const CELL_SIZE: usize = 8;
+const SCANLINE_ALPHA: u8 = 0x60;
+const GRID_ALPHA: u8 = 0x90;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RenderOptions {
+    pub phosphor: bool,
+    pub scanlines: bool,
+    pub pixel_grid: bool,
+}
+
+// Displays of any size are stretched over the area a standard one covers.
+// The display is drawn into a texture a texel per pixel, which the GPU scales
+// up to fill the window.
+pub struct Renderer<'a> {
+    palette: Palette,
+    size: (usize, usize),
+    texture_creator: &'a TextureCreator<WindowContext>,
+    // RGBA texels for the display texture.
+    pixels: Vec<u8>,
+    display: Texture<'a>,
+    phosphor: Option<Phosphor>,
+    scanlines: bool,
+    pixel_grid: bool,
+    scanline_overlay: Texture<'a>,
+    grid_overlay: Texture<'a>,
+}
+
+impl<'a> Renderer<'a> {
+    pub fn new(
+        palette: Palette,
+        options: RenderOptions,
+        texture_creator: &'a TextureCreator<WindowContext>,
+    ) -> Renderer<'a> {
+        let size = (DISPLAY_WIDTH, DISPLAY_HEIGHT);
+        Renderer {
+            palette,
+            size,
+            texture_creator,
+            pixels: vec![0; size.0 * size.1 * 4],
+            display: display_texture(texture_creator, size),
+            phosphor: options.phosphor.then(|| Phosphor::new(size)),
+            scanlines: options.scanlines,
+            pixel_grid: options.pixel_grid,
+            scanline_overlay: scanline_texture(texture_creator, size),
+            grid_overlay: grid_texture(texture_creator, size),
+        }
+    }
+
+    pub fn toggle_scanlines(&mut self) {
+        self.scanlines = !self.scanlines;
+    }
+
+    pub fn toggle_pixel_grid(&mut self) {
+        self.pixel_grid = !self.pixel_grid;
+    }
+
+    pub fn is_fading(&self) -> bool {
+        self.phosphor.as_ref().is_some_and(Phosphor::is_fading)
+    }
+
+    // Draws the display without presenting it, so more can be drawn on top.
+    // CHIP-8X's colors replace the palette's when given.
+    pub fn draw(
+        &mut self,
+        canvas: &mut Canvas<Window>,
+        display: &FrameBuffer,
+        colors: Option<&ColorMap>,
+    ) {
+        if display.size() != self.size {
+            self.resize(display.size());
+        }
+        canvas.set_draw_color(Color::from(self.palette.background));
+        canvas.clear();
+
+        let background = colors.map_or(self.palette.background, |c| c.background().into());
+        let foreground =
+            |x, y| colors.map_or(self.palette.foreground, |c| c.foreground(x, y).into());
+        let width = self.size.0;
+        let texels = self.pixels.chunks_exact_mut(4);
+        match &mut self.phosphor {
+            Some(phosphor) => {
+                phosphor.update(display);
+                let pixels = display.iter_pixels().zip(&phosphor.intensity);
+                for (texel, ((x, y, _), &intensity)) in texels.zip(pixels) {
+                    let color = blend(background, foreground(x, y), intensity);
+                    texel.copy_from_slice(&rgba(color));
+                }
+            }
+            None if colors.is_none() => {
+                let (foreground, background) = (self.palette.foreground, background);
+                display.write_rgba(rgba(foreground), rgba(background), &mut self.pixels);
+            }
+            None => {
+                for (texel, (x, y, lit)) in texels.zip(display.iter_pixels()) {
+                    let color = if lit { foreground(x, y) } else { background };
+                    texel.copy_from_slice(&rgba(color));
+                }
+            }
+        }
+        self.display.update(None, &self.pixels, width * 4).unwrap();
+
+        // The window may show more than the display, e.g. the keypad.
+        let area = Rect::new(0, 0, DISPLAY_WIDTH as u32, DISPLAY_HEIGHT as u32);
+        canvas.copy(&self.display, None, area).unwrap();
+        if self.scanlines {
+            canvas.copy(&self.scanline_overlay, None, area).unwrap();
+        }
+        if self.pixel_grid {
+            canvas.copy(&self.grid_overlay, None, area).unwrap();
+        }
+    }
+
+    fn resize(&mut self, size: (usize, usize)) {
+        self.size = size;
+        self.pixels = vec![0; size.0 * size.1 * 4];
+        self.display = display_texture(self.texture_creator, size);
+        if self.phosphor.is_some() {
+            self.phosphor = Some(Phosphor::new(size));
+        }
+        self.scanline_overlay = scanline_texture(self.texture_creator, size);
+        self.grid_overlay = grid_texture(self.texture_creator, size);
+    }
+}
+
+struct Phosphor {
+    intensity: Vec<f32>,
+    last_update: Instant,
+}
+
+impl Phosphor {
+    fn new((width, height): (usize, usize)) -> Phosphor {
+        Phosphor {
+            intensity: vec![0.0; width * height],
+            last_update: Instant::now(),
+        }
+    }
+
+    fn is_fading(&self) -> bool {
+        self.intensity.iter().any(|&i| i > 0.0 && i < 1.0)
+    }
+
+    fn update(&mut self, display: &FrameBuffer) {
+        let frames = self.last_update.elapsed().as_secs_f32() * PHOSPHOR_FRAME_RATE;
+        let decay = PHOSPHOR_DECAY_PER_FRAME.powf(frames);
+        self.last_update = Instant::now();
+
+        for (intensity, (_, _, lit)) in self.intensity.iter_mut().zip(display.iter_pixels()) {
+            if lit {
+                *intensity = 1.0;
+            } else {
+                *intensity *= decay;
+                if *intensity < PHOSPHOR_CUTOFF {
+                    *intensity = 0.0;
+                }
+            }
+        }
+    }
+}
+
+fn display_texture(
+    texture_creator: &TextureCreator<WindowContext>,
+    (width, height): (usize, usize),
+) -> Texture<'_> {
+    texture_creator
+        .create_texture_streaming(PixelFormatEnum::RGBA32, width as u32, height as u32)
+        .unwrap()
+}
+
+fn scanline_texture(
+    texture_creator: &TextureCreator<WindowContext>,
+    size: (usize, usize),
+) -> Texture<'_> {
+    overlay_texture(texture_creator, size, |_, row| {
+        (row >= CELL_SIZE / 2).then_some(SCANLINE_ALPHA)
+    })
+}
+
+fn grid_texture(
+    texture_creator: &TextureCreator<WindowContext>,
+    size: (usize, usize),
+) -> Texture<'_> {
+    overlay_texture(texture_creator, size, |col, row| {
+        (col == 0 || row == 0).then_some(GRID_ALPHA)
+    })
+}
+
+// Builds an overlay for a display of `size` where `shade(col, row)` gives the
+// alpha of the black texel at that position within each pixel cell.
+fn overlay_texture(
+    texture_creator: &TextureCreator<WindowContext>,
+    (width, height): (usize, usize),
+    shade: impl Fn(usize, usize) -> Option<u8>,
+) -> Texture<'_> {
+    let width = width * CELL_SIZE;
+    let height = height * CELL_SIZE;
+    let mut pixels = vec![0; width * height * 4];
+    for (i, texel) in pixels.chunks_exact_mut(4).enumerate() {
+        if let Some(alpha) = shade(i % width % CELL_SIZE, i / width % CELL_SIZE) {
+            texel[3] = alpha;
+        }
+    }
+
+    let mut texture = texture_creator
+        .create_texture_static(PixelFormatEnum::RGBA32, width as u32, height as u32)
+        .unwrap();
+    texture.update(None, &pixels, width * 4).unwrap();
+    texture.set_blend_mode(BlendMode::Blend);
+    texture
+}
+
+fn blend(background: Rgb, foreground: Rgb, intensity: f32) -> Rgb {
+    let mix = |bg: u8, fg: u8| (bg as f32 + (fg as f32 - bg as f32) * intensity) as u8;
+    Rgb::new(
+        mix(background.r, foreground.r),
+        mix(background.g, foreground.g),
+        mix(background.b, foreground.b),
+    )
+}
+
+fn rgba(color: Rgb) -> [u8; 4] {
+    [color.r, color.g, color.b, 0xff]
+}
@@ -0,0 +1,141 @@
+use my_chip8::Chip8;
+#[cfg(feature = "sdl")]
+use std::fmt::Write as _;
+use std::fs;
+use std::iter::Peekable;
+use std::path::Path;
+use std::vec;
+
+// A replay is a key script with optional header lines naming the ROM and
+// seed it was recorded with:
+//
+//   rom 8d5c1e0f6a3b2c49
+//   seed 12345
+//   120 down 5
+//   131 up 5
+pub struct Replay {
+    pub rom_hash: Option<u64>,
+    pub seed: Option<u64>,
+    events: Vec<KeyEvent>,
+}
+
+struct KeyEvent {
+    cycle: u64,
+    key: u8,
+    pressed: bool,
+}
+
+pub enum ReplayMode {
+    Off,
+    #[cfg(feature = "sdl")]
+    Record(Replay),
+    Play(Player),
+}
+
+pub struct Player {
+    events: Peekable<vec::IntoIter<KeyEvent>>,
+}
+
+impl Replay {
+    #[cfg(feature = "sdl")]
+    pub fn new(rom: &[u8], seed: u64) -> Replay {
+        Replay {
+            rom_hash: Some(rom_hash(rom)),
+            seed: Some(seed),
+            events: Vec::new(),
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Replay, String> {
+        let script = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+
+        let mut replay = Replay {
+            rom_hash: None,
+            seed: None,
+            events: Vec::new(),
+        };
+        for (n, line) in script.lines().enumerate() {
+            let line = line.split('#').next().unwrap().trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let invalid = || format!("{}:{}: invalid line: {line}", path.display(), n + 1);
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            match fields[..] {
+                ["rom", hash] => {
+                    replay.rom_hash = Some(u64::from_str_radix(hash, 16).map_err(|_| invalid())?);
+                }
+                ["seed", seed] => replay.seed = Some(seed.parse().map_err(|_| invalid())?),
+                [cycle, action, key] => {
+                    let cycle = cycle.parse().map_err(|_| invalid())?;
+                    let pressed = match action {
+                        "down" => true,
+                        "up" => false,
+                        _ => return Err(invalid()),
+                    };
+                    let key = u8::from_str_radix(key, 16)
+                        .ok()
+                        .filter(|&key| key < 16)
+                        .ok_or_else(invalid)?;
+                    replay.record(cycle, key, pressed);
+                }
+                _ => return Err(invalid()),
+            }
+        }
+        replay.events.sort_by_key(|event| event.cycle);
+        Ok(replay)
+    }
+
+    #[cfg(feature = "sdl")]
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let mut script = String::new();
+        if let Some(hash) = self.rom_hash {
+            writeln!(script, "rom {hash:016x}").unwrap();
+        }
+        if let Some(seed) = self.seed {
+            writeln!(script, "seed {seed}").unwrap();
+        }
+        for event in &self.events {
+            let action = if event.pressed { "down" } else { "up" };
+            writeln!(script, "{} {action} {:x}", event.cycle, event.key).unwrap();
+        }
+        fs::write(path, script).map_err(|e| format!("Failed to write {}: {e}", path.display()))
+    }
+
+    // Records a key event that takes effect before cycle `cycle` runs.
+    pub fn record(&mut self, cycle: u64, key: u8, pressed: bool) {
+        self.events.push(KeyEvent {
+            cycle,
+            key,
+            pressed,
+        });
+    }
+
+    pub fn play(self) -> Player {
+        Player {
+            events: self.events.into_iter().peekable(),
+        }
+    }
+}
+
+impl Player {
+    // Applies every event due before cycle `cycle` runs.
+    pub fn apply(&mut self, chip8: &mut Chip8, cycle: u64) {
+        while let Some(event) = self.events.next_if(|event| event.cycle <= cycle) {
+            if event.pressed {
+                chip8.key_down(event.key);
+            } else {
+                chip8.key_up(event.key);
+            }
+        }
+    }
+}
+
+// 64-bit FNV-1a, which is stable across builds unlike std's hasher.
+pub fn rom_hash(rom: &[u8]) -> u64 {
+    rom.iter().fold(0xcbf29ce484222325, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
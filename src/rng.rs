@@ -0,0 +1,36 @@
+use alloc::boxed::Box;
+use rand::rngs::StdRng;
+use rand::Rng;
+
+/// A source of random bytes for the CXNN instruction.
+///
+/// Implemented for [`StdRng`] and for any cloneable `FnMut() -> u8` closure,
+/// so tests can feed a fixed sequence and embedded targets can wrap a
+/// hardware RNG.
+pub trait RandomSource: Send {
+    fn next_byte(&mut self) -> u8;
+
+    /// A copy that goes on to produce the same bytes, for cloning the machine
+    /// it belongs to.
+    fn boxed_clone(&self) -> Box<dyn RandomSource>;
+}
+
+impl RandomSource for StdRng {
+    fn next_byte(&mut self) -> u8 {
+        self.gen()
+    }
+
+    fn boxed_clone(&self) -> Box<dyn RandomSource> {
+        Box::new(self.clone())
+    }
+}
+
+impl<F: FnMut() -> u8 + Clone + Send + 'static> RandomSource for F {
+    fn next_byte(&mut self) -> u8 {
+        self()
+    }
+
+    fn boxed_clone(&self) -> Box<dyn RandomSource> {
+        Box::new(self.clone())
+    }
+}
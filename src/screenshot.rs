@@ -0,0 +1,48 @@
+use crate::palette::Palette;
+use my_chip8::Chip8;
+use std::fs::{self, File};
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub fn save(chip8: &Chip8, palette: &Palette, scale: u32, dir: &Path) -> Result<PathBuf, String> {
+    let scale = scale.max(1) as usize;
+    let foreground = [
+        palette.foreground.r,
+        palette.foreground.g,
+        palette.foreground.b,
+    ];
+    let background = [
+        palette.background.r,
+        palette.background.g,
+        palette.background.b,
+    ];
+    let image = match chip8.color_map() {
+        Some(colors) => colors.to_rgb(chip8.frame_buffer(), scale),
+        None => chip8.display_rgb(foreground, background, scale),
+    };
+    let (width, height) = chip8.display_size();
+
+    let path = timestamped_path(dir, "png")?;
+    let file = File::create(&path).map_err(|e| e.to_string())?;
+    let mut encoder = png::Encoder::new(
+        BufWriter::new(file),
+        (width * scale) as u32,
+        (height * scale) as u32,
+    );
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(|e| e.to_string())?;
+    writer.write_image_data(&image).map_err(|e| e.to_string())?;
+
+    Ok(path)
+}
+
+pub fn timestamped_path(dir: &Path, extension: &str) -> Result<PathBuf, String> {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    Ok(dir.join(format!("chip8-{timestamp}.{extension}")))
+}
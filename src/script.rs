@@ -0,0 +1,187 @@
+use crate::RunArgs;
+use my_chip8::{Chip8, FrameBuffer};
+use rhai::{CallFnOptions, Engine, EvalAltResult, Scope, AST};
+use std::cell::RefCell;
+use std::path::Path;
+use std::process;
+use std::rc::Rc;
+
+type ScriptResult<T> = Result<T, Box<EvalAltResult>>;
+
+// Scripts define `fn on_frame()`, which runs once per 60Hz frame and can call:
+//
+//   peek(addr)  poke(addr, value)  reg(x)  set_reg(x, value)
+//   pixel(x, y)  key_down(key)  key_up(key)  frame()
+//
+// The functions work on a copy of the machine taken before the callback, and
+// writes are applied to the interpreter once it returns.
+pub struct Script {
+    engine: Engine,
+    ast: AST,
+    scope: Scope<'static>,
+    state: Rc<RefCell<State>>,
+}
+
+#[derive(Default)]
+struct State {
+    memory: Vec<u8>,
+    v: [u8; 16],
+    display: FrameBuffer,
+    frame: i64,
+    actions: Vec<Action>,
+}
+
+enum Action {
+    Poke(u16, u8),
+    SetReg(u8, u8),
+    KeyDown(u8),
+    KeyUp(u8),
+}
+
+impl Script {
+    pub fn from_args(args: &RunArgs) -> Option<Script> {
+        let path = args.script.as_deref()?;
+        match Script::load(path) {
+            Ok(script) => Some(script),
+            Err(e) => {
+                eprintln!("{e}");
+                process::exit(1);
+            }
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Script, String> {
+        let state = Rc::new(RefCell::new(State::default()));
+        let mut engine = Engine::new();
+        register_api(&mut engine, &state);
+
+        let ast = engine
+            .compile_file(path.into())
+            .map_err(|e| format!("Failed to load {}: {e}", path.display()))?;
+        let mut scope = Scope::new();
+        engine
+            .run_ast_with_scope(&mut scope, &ast)
+            .map_err(|e| format!("{}: {e}", path.display()))?;
+
+        Ok(Script {
+            engine,
+            ast,
+            scope,
+            state,
+        })
+    }
+
+    // Runs the script's on_frame() and applies its writes. A script error
+    // still applies the writes made before it.
+    pub fn on_frame(&mut self, chip8: &mut Chip8) -> Result<(), String> {
+        {
+            let mut state = self.state.borrow_mut();
+            let cpu = chip8.state();
+            state.memory.clear();
+            state.memory.extend_from_slice(chip8.memory());
+            state.v = cpu.v;
+            state.display = *chip8.frame_buffer();
+        }
+
+        let options = CallFnOptions::new().eval_ast(false).rewind_scope(false);
+        let result = self.engine.call_fn_with_options::<()>(
+            options,
+            &mut self.scope,
+            &self.ast,
+            "on_frame",
+            (),
+        );
+
+        let mut state = self.state.borrow_mut();
+        state.frame += 1;
+        for action in state.actions.drain(..) {
+            match action {
+                Action::Poke(addr, value) => chip8.write_byte(addr, value).unwrap(),
+                Action::SetReg(x, value) => chip8.set_register(x, value),
+                Action::KeyDown(key) => chip8.key_down(key),
+                Action::KeyUp(key) => chip8.key_up(key),
+            }
+        }
+        result.map_err(|e| e.to_string())
+    }
+}
+
+fn register_api(engine: &mut Engine, state: &Rc<RefCell<State>>) {
+    let s = state.clone();
+    engine.register_fn("peek", move |addr: i64| -> ScriptResult<i64> {
+        let state = s.borrow();
+        let addr = address(&state, addr)?;
+        Ok(state.memory[addr as usize] as i64)
+    });
+
+    let s = state.clone();
+    engine.register_fn("poke", move |addr: i64, value: i64| -> ScriptResult<()> {
+        let mut state = s.borrow_mut();
+        let addr = address(&state, addr)?;
+        let value = byte(value)?;
+        state.memory[addr as usize] = value;
+        state.actions.push(Action::Poke(addr, value));
+        Ok(())
+    });
+
+    let s = state.clone();
+    engine.register_fn("reg", move |x: i64| -> ScriptResult<i64> {
+        Ok(s.borrow().v[nibble(x, "register")? as usize] as i64)
+    });
+
+    let s = state.clone();
+    engine.register_fn("set_reg", move |x: i64, value: i64| -> ScriptResult<()> {
+        let mut state = s.borrow_mut();
+        let x = nibble(x, "register")?;
+        let value = byte(value)?;
+        state.v[x as usize] = value;
+        state.actions.push(Action::SetReg(x, value));
+        Ok(())
+    });
+
+    let s = state.clone();
+    engine.register_fn("pixel", move |x: i64, y: i64| -> bool {
+        let display = s.borrow().display;
+        let (width, height) = display.size();
+        display.pixel(
+            x.rem_euclid(width as i64) as usize,
+            y.rem_euclid(height as i64) as usize,
+        )
+    });
+
+    let s = state.clone();
+    engine.register_fn("key_down", move |key: i64| -> ScriptResult<()> {
+        let key = nibble(key, "key")?;
+        s.borrow_mut().actions.push(Action::KeyDown(key));
+        Ok(())
+    });
+
+    let s = state.clone();
+    engine.register_fn("key_up", move |key: i64| -> ScriptResult<()> {
+        let key = nibble(key, "key")?;
+        s.borrow_mut().actions.push(Action::KeyUp(key));
+        Ok(())
+    });
+
+    let s = state.clone();
+    engine.register_fn("frame", move || s.borrow().frame);
+}
+
+fn address(state: &State, addr: i64) -> ScriptResult<u16> {
+    if (0..state.memory.len() as i64).contains(&addr) {
+        Ok(addr as u16)
+    } else {
+        Err(format!("address out of range: {addr:#x}").into())
+    }
+}
+
+fn byte(value: i64) -> ScriptResult<u8> {
+    u8::try_from(value).map_err(|_| format!("value out of range: {value}").into())
+}
+
+fn nibble(value: i64, what: &str) -> ScriptResult<u8> {
+    match value {
+        0..=15 => Ok(value as u8),
+        _ => Err(format!("{what} out of range: {value}").into()),
+    }
+}
@@ -0,0 +1,863 @@
+use crate::achievements::Achievements;
+use crate::audio::Buzzer;
+use crate::cheats::Cheats;
+use crate::config::Config;
+#[cfg(feature = "discord")]
+use crate::discord::RichPresence;
+use crate::emulation::{Emulation, Input, Output};
+use crate::keypad::{Keypad, KEYPAD_WIDTH};
+use crate::latency::LatencyMeter;
+use crate::netplay::Netplay;
+use crate::overlay::Overlay;
+use crate::palette::Palette;
+use crate::perf::PerfCounter;
+use crate::picker::{PickerAction, RomPicker};
+use crate::recent::RecentRoms;
+use crate::recording::Recorder;
+#[cfg(feature = "remote")]
+use crate::remote::RemoteServer;
+use crate::render::{RenderOptions, Renderer};
+use crate::replay::ReplayMode;
+use crate::slots::SaveSlots;
+use crate::stats::SessionStats;
+use crate::text::{self, TEXT_HEIGHT, TEXT_WIDTH};
+#[cfg(feature = "watch")]
+use crate::watch::RomWatcher;
+use crate::{keymap, screenshot, sym_file, RunArgs, FRAME_INTERVAL};
+use my_chip8::{Chip8, FrameBuffer, SharedChip8, DISPLAY_HEIGHT};
+use sdl2::event::{Event, WindowEvent};
+use sdl2::keyboard::{Mod, Scancode};
+use sdl2::mouse::MouseButton;
+use sdl2::pixels::Color;
+use sdl2::render::Canvas;
+use sdl2::video::{FullscreenType, Window, WindowPos};
+use sdl2::VideoSubsystem;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+// How long to wait on the emulation thread before checking for events again.
+const EVENT_INTERVAL: Duration = Duration::from_millis(1);
+const MIN_SPEED: f64 = 0.25;
+const MAX_SPEED: f64 = 64.0;
+const TITLE: &str = "CHIP-8 Emulator";
+const DROP_TEXT_SIZE: u32 = 4;
+// The DPI desktops treat as unscaled.
+const BASE_DPI: f32 = 96.0;
+
+pub fn run(
+    args: &RunArgs,
+    config: &mut Config,
+    rom: &[u8],
+    mut chip8: Chip8,
+    replay_mode: ReplayMode,
+    netplay: Option<Netplay>,
+) {
+    let palette = args.palette(config);
+    let render_options = RenderOptions {
+        phosphor: args.phosphor || config.phosphor,
+        scanlines: args.scanlines || config.scanlines,
+        pixel_grid: args.pixel_grid || config.pixel_grid,
+    };
+
+    let mut latency_meter = args.latency.then(LatencyMeter::default);
+
+    #[cfg(feature = "remote")]
+    let remote = args.remote.as_deref().map(|addr| {
+        RemoteServer::bind(addr).unwrap_or_else(|e| {
+            eprintln!("Failed to start remote control on {addr}: {e}");
+            std::process::exit(1);
+        })
+    });
+
+    // Draw at the full resolution of high-DPI displays on Windows, which
+    // otherwise stretches a low-resolution window to fit.
+    sdl2::hint::set("SDL_WINDOWS_DPI_AWARENESS", "permonitorv2");
+    sdl2::hint::set("SDL_WINDOWS_DPI_SCALING", "1");
+    let sdl_context = sdl2::init().unwrap();
+    let video_subsystem = sdl_context.video().unwrap();
+
+    if let Some(scale) = args.scale {
+        if scale != config.window.scale {
+            config.window.width = None;
+            config.window.height = None;
+        }
+        config.window.scale = scale;
+    }
+    let keypad = Keypad::new(args.keypad || config.keypad);
+    let scale = config.window.scale;
+    let width = config
+        .window
+        .width
+        .unwrap_or(keypad.logical_size().0 * scale);
+    let height = config
+        .window
+        .height
+        .unwrap_or(my_chip8::DISPLAY_HEIGHT as u32 * scale);
+
+    let mut window_builder = video_subsystem.window(TITLE, width, height);
+    match (config.window.x, config.window.y) {
+        (Some(x), Some(y)) => window_builder.position(x, y),
+        _ => window_builder.position_centered(),
+    };
+    if config.window.fullscreen {
+        window_builder.fullscreen_desktop();
+    }
+    window_builder.resizable().allow_highdpi();
+    let window = window_builder.build().unwrap();
+
+    let mut canvas = window.into_canvas().build().unwrap();
+    // A remembered size is already right for the display.
+    if config.window.width.is_none() && !config.window.fullscreen {
+        let centered = config.window.x.is_none() || config.window.y.is_none();
+        fit_to_display(
+            &mut canvas,
+            &video_subsystem,
+            args.scale.is_some(),
+            centered,
+        );
+    }
+
+    let (logical_width, logical_height) = keypad.logical_size();
+    canvas
+        .set_logical_size(logical_width, logical_height)
+        .unwrap();
+    canvas
+        .set_integer_scale(args.integer_scale || config.window.integer_scale)
+        .unwrap();
+    canvas.set_draw_color(Color::from(palette.background));
+    canvas.clear();
+    canvas.present();
+
+    let texture_creator = canvas.texture_creator();
+    let mut view = View {
+        renderer: Renderer::new(palette, render_options, &texture_creator),
+        palette,
+        // Nothing runs until a ROM is picked or dropped on the window.
+        waiting: args.rom.is_none() && !args.latency && args.builtin.is_none(),
+        picker: None,
+        overlay: Overlay::default(),
+        perf: PerfCounter::default(),
+        stats: SessionStats::start(args, &mut chip8),
+        keypad,
+    };
+    view.overlay.symbols = sym_file::from_args(args);
+
+    let mut buzzer = Buzzer::open(
+        &sdl_context,
+        &config.audio,
+        args.buzzer.as_deref().or(config.audio.sample.as_deref()),
+    );
+    let mut event_pump = sdl_context.event_pump().unwrap();
+    let mut last_render = Instant::now();
+    let mut recorder: Option<Recorder> = None;
+    let mut last_capture = Instant::now();
+
+    // Netplay and replays are tied to the ROM they started with.
+    let can_switch_rom = netplay.is_none() && matches!(replay_mode, ReplayMode::Off);
+    let netplay_on = netplay.is_some();
+    let playing_replay = matches!(replay_mode, ReplayMode::Play(_));
+    let mut requested_rom: Option<PathBuf> = None;
+    let mut recent = RecentRoms::load();
+    let mut slots = SaveSlots::for_rom(rom);
+    let mut achievements = Achievements::from_args(args, rom);
+    chip8.set_sound_hook(buzzer.sound_hook());
+    let chip8 = SharedChip8::new(chip8);
+    if args.resume {
+        match slots.resume(&mut chip8.lock()) {
+            Ok(true) => view.overlay.show_message("Resumed".to_string()),
+            Ok(false) => view.overlay.show_message("Nothing to resume".to_string()),
+            Err(e) => {
+                eprintln!("{e}");
+                std::process::exit(1);
+            }
+        }
+        view.render(&mut canvas, &chip8);
+    }
+    #[cfg(feature = "watch")]
+    let mut watcher = args
+        .rom
+        .as_deref()
+        .filter(|_| args.watch)
+        .and_then(|rom| watch_rom(Path::new(rom)));
+    if view.waiting {
+        view.picker = RomPicker::open(&config.roms.directory).ok();
+        view.render(&mut canvas, &chip8);
+    }
+
+    let mut rom_name = match &args.rom {
+        Some(path) => file_name(Path::new(path)),
+        None if args.latency => "latency probe".to_string(),
+        None => match args.builtin {
+            Some(builtin) => format!("built-in {}", builtin.name()),
+            None => String::new(),
+        },
+    };
+    let mut paused = false;
+    let mut speed = 1.0;
+    update_title(&mut canvas, &rom_name, speed, paused, false);
+    #[cfg(feature = "discord")]
+    let mut presence = args.discord.as_deref().map(RichPresence::connect);
+    #[cfg(feature = "discord")]
+    if let Some(presence) = &mut presence {
+        presence.show_rom(&rom_name);
+    }
+
+    let emulation = Emulation::spawn(
+        chip8.clone(),
+        replay_mode,
+        netplay,
+        #[cfg(feature = "remote")]
+        remote,
+        #[cfg(feature = "scripting")]
+        args.script.clone(),
+        Cheats::from_args(args),
+    );
+
+    let mut running = false;
+    // Set when a key changes, to show it on the keypad once the emulation
+    // thread has taken it.
+    let mut keys_changed = false;
+    'running: loop {
+        let should_run = !view.waiting && !paused && view.picker.is_none();
+        if should_run != running {
+            running = should_run;
+            emulation.send(Input::Running(running));
+            buzzer.set_running(running);
+        }
+
+        let (mut drawn, mut unlocked) = (false, Vec::new());
+        for output in emulation.receive(EVENT_INTERVAL) {
+            match output {
+                Output::Ran {
+                    cycles,
+                    drawn: ran_drawn,
+                    jitter,
+                } => {
+                    view.perf.add_cycles(cycles);
+                    if let Some(jitter) = jitter {
+                        view.perf.frame_jitter(jitter);
+                    }
+                    drawn |= ran_drawn;
+                    if let Some(achievements) = &mut achievements {
+                        unlocked.extend(achievements.check(&chip8.lock()));
+                    }
+                }
+                Output::Executed(pc, opcode) => view.overlay.record(pc, opcode),
+                Output::Stopped => break 'running,
+            }
+        }
+
+        if !unlocked.is_empty() {
+            view.overlay
+                .show_message(format!("Achievement unlocked: {}", unlocked.join(", ")));
+        }
+        if drawn {
+            view.render(&mut canvas, &chip8);
+            last_render = Instant::now();
+
+            if let Some(meter) = &mut latency_meter {
+                meter.frame_presented();
+            }
+        } else if (running
+            && (view.renderer.is_fading() || view.overlay.debug)
+            && last_render.elapsed() >= FRAME_INTERVAL)
+            || (keys_changed && view.keypad.shown)
+            || !unlocked.is_empty()
+        {
+            view.render(&mut canvas, &chip8);
+            last_render = Instant::now();
+        }
+        keys_changed = false;
+
+        if let Some(recorder) = recorder.as_mut().filter(|_| running) {
+            if last_capture.elapsed() >= FRAME_INTERVAL {
+                recorder.capture(chip8.lock().frame_buffer());
+                last_capture += FRAME_INTERVAL;
+            }
+        }
+
+        for event in event_pump.poll_iter() {
+            // Key presses from the keyboard or the on-screen keypad.
+            let mut key_event = None;
+            if let (
+                Some(open),
+                Event::KeyDown {
+                    scancode: Some(scancode),
+                    ..
+                },
+            ) = (&mut view.picker, &event)
+            {
+                match open.handle_key(*scancode) {
+                    PickerAction::None => {}
+                    PickerAction::Load(path) => {
+                        view.picker = None;
+                        requested_rom = Some(path);
+                    }
+                    PickerAction::Close => view.picker = None,
+                }
+                view.render(&mut canvas, &chip8);
+                continue;
+            }
+
+            // Shift+N saves to slot N and Ctrl+N loads from it.
+            if let Event::KeyDown {
+                scancode: Some(scancode),
+                keymod,
+                ..
+            } = &event
+            {
+                let shift = keymod.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD);
+                let ctrl = keymod.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD);
+                if let Some(slot) = slot_number(*scancode).filter(|_| shift || ctrl) {
+                    let message = if view.waiting {
+                        "No ROM is running".to_string()
+                    } else if shift {
+                        match slots.save(slot, &chip8.lock()) {
+                            Ok(_) => format!("Saved slot {slot}"),
+                            Err(e) => {
+                                eprintln!("{e}");
+                                format!("Failed to save slot {slot}")
+                            }
+                        }
+                    } else if !can_switch_rom {
+                        "Can't load states during netplay or a replay".to_string()
+                    } else {
+                        match slots.load(slot, &mut chip8.lock()) {
+                            Ok(true) => format!("Loaded slot {slot}"),
+                            Ok(false) => format!("Slot {slot} is empty"),
+                            Err(e) => {
+                                eprintln!("{e}");
+                                format!("Failed to load slot {slot}")
+                            }
+                        }
+                    };
+                    view.overlay.show_message(message);
+                    view.render(&mut canvas, &chip8);
+                    continue;
+                }
+            }
+
+            match event {
+                Event::KeyDown {
+                    scancode: Some(Scancode::F2),
+                    ..
+                } => {
+                    view.renderer.toggle_scanlines();
+                    view.render(&mut canvas, &chip8);
+                }
+                Event::KeyDown {
+                    scancode: Some(Scancode::F3),
+                    ..
+                } => {
+                    view.renderer.toggle_pixel_grid();
+                    view.render(&mut canvas, &chip8);
+                }
+                Event::KeyDown {
+                    scancode: Some(Scancode::F4),
+                    ..
+                } => {
+                    view.overlay.perf = !view.overlay.perf;
+                    view.render(&mut canvas, &chip8);
+                }
+                Event::KeyDown {
+                    scancode: Some(Scancode::F10),
+                    ..
+                } => {
+                    view.overlay.toggle_debug();
+                    emulation.send(Input::Trace(view.overlay.debug));
+                    view.render(&mut canvas, &chip8);
+                }
+                Event::KeyDown {
+                    scancode: Some(Scancode::F7),
+                    ..
+                } => {
+                    if let Some(key) = view.keypad.release() {
+                        key_event = Some((key, false));
+                    }
+                    view.keypad.shown = !view.keypad.shown;
+                    resize_for_keypad(&mut canvas, &view.keypad);
+                    view.render(&mut canvas, &chip8);
+                }
+                Event::KeyDown {
+                    scancode: Some(Scancode::F6),
+                    ..
+                } => emulation.send(Input::ToggleCheats),
+                Event::KeyDown {
+                    scancode: Some(Scancode::F12),
+                    ..
+                } => {
+                    let screenshot = &config.screenshot;
+                    match screenshot::save(
+                        &chip8.lock(),
+                        &palette,
+                        screenshot.scale,
+                        &screenshot.directory,
+                    ) {
+                        Ok(path) => println!("Saved screenshot to {}", path.display()),
+                        Err(e) => eprintln!("Failed to save screenshot: {e}"),
+                    }
+                }
+                Event::KeyDown {
+                    scancode: Some(Scancode::F9),
+                    ..
+                } => match recorder.take() {
+                    Some(finished) => {
+                        let recording = &config.recording;
+                        match finished.save(&palette, recording.scale, &recording.directory) {
+                            Ok(path) => println!("Saved recording to {}", path.display()),
+                            Err(e) => eprintln!("Failed to save recording: {e}"),
+                        }
+                    }
+                    None => {
+                        println!("Recording started");
+                        recorder = Some(Recorder::new());
+                        last_capture = Instant::now();
+                    }
+                },
+                Event::KeyDown {
+                    scancode: Some(Scancode::P | Scancode::Pause),
+                    ..
+                } if !netplay_on => {
+                    paused = !paused;
+                }
+                Event::KeyDown {
+                    scancode: Some(Scancode::Comma),
+                    ..
+                } if paused && !view.waiting => {
+                    if !chip8.lock().step_back() {
+                        view.overlay
+                            .show_message("No earlier state kept".to_string());
+                    }
+                    view.render(&mut canvas, &chip8);
+                }
+                Event::KeyDown {
+                    scancode: Some(Scancode::Period),
+                    ..
+                } if paused && !view.waiting => {
+                    let stepped = {
+                        let mut machine = chip8.lock();
+                        view.overlay.record(machine.pc(), machine.current_opcode());
+                        machine.run_cycle()
+                    };
+                    if let Err(e) = stepped {
+                        eprintln!("Emulation error: {e}");
+                        break 'running;
+                    }
+                    view.render(&mut canvas, &chip8);
+                }
+                Event::KeyDown {
+                    scancode: Some(Scancode::M),
+                    ..
+                } => {
+                    buzzer.toggle_mute();
+                    view.overlay.show_message(buzzer.describe());
+                    view.render(&mut canvas, &chip8);
+                }
+                Event::KeyDown {
+                    scancode: Some(scancode @ (Scancode::LeftBracket | Scancode::RightBracket)),
+                    ..
+                } => {
+                    buzzer.step_volume(scancode == Scancode::RightBracket);
+                    view.overlay.show_message(buzzer.describe());
+                    view.render(&mut canvas, &chip8);
+                }
+                Event::KeyDown {
+                    scancode: Some(scancode @ (Scancode::Minus | Scancode::Equals)),
+                    ..
+                } if !netplay_on => {
+                    speed = if scancode == Scancode::Minus {
+                        (speed / 2.0).max(MIN_SPEED)
+                    } else {
+                        (speed * 2.0).min(MAX_SPEED)
+                    };
+                    emulation.send(Input::Speed(speed));
+                }
+                Event::KeyDown {
+                    scancode: Some(Scancode::O),
+                    keymod,
+                    ..
+                } if keymod.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD) => {
+                    if !can_switch_rom {
+                        eprintln!("Can't switch ROMs during netplay or a replay");
+                        continue;
+                    }
+                    match RomPicker::open(&config.roms.directory) {
+                        Ok(opened) => {
+                            view.picker = Some(opened);
+                            view.render(&mut canvas, &chip8);
+                        }
+                        Err(e) => eprintln!("{e}"),
+                    }
+                }
+                Event::KeyDown {
+                    scancode: Some(Scancode::R),
+                    keymod,
+                    ..
+                } if keymod.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD) => {
+                    if !can_switch_rom {
+                        eprintln!("Can't switch ROMs during netplay or a replay");
+                        continue;
+                    }
+                    match RomPicker::recent(recent.paths()) {
+                        Ok(opened) => {
+                            view.picker = Some(opened);
+                            view.render(&mut canvas, &chip8);
+                        }
+                        Err(e) => eprintln!("{e}"),
+                    }
+                }
+                Event::KeyDown {
+                    scancode: Some(Scancode::F11),
+                    ..
+                } => {
+                    toggle_fullscreen(canvas.window_mut());
+                }
+                Event::KeyDown {
+                    scancode: Some(Scancode::Return),
+                    keymod,
+                    ..
+                } if keymod.intersects(Mod::LALTMOD | Mod::RALTMOD) => {
+                    toggle_fullscreen(canvas.window_mut());
+                }
+                Event::KeyDown {
+                    scancode: Some(scancode),
+                    ..
+                } => {
+                    key_event = keymap::name_to_key(scancode.name()).map(|key| (key, true));
+                }
+                Event::KeyUp {
+                    scancode: Some(scancode),
+                    ..
+                } => {
+                    key_event = keymap::name_to_key(scancode.name()).map(|key| (key, false));
+                }
+                // Touches arrive as left clicks, since SDL mirrors them as
+                // mouse events.
+                Event::MouseButtonDown {
+                    mouse_btn: MouseButton::Left,
+                    x,
+                    y,
+                    ..
+                } => {
+                    key_event = view.keypad.press(x, y).map(|key| (key, true));
+                }
+                Event::MouseButtonUp {
+                    mouse_btn: MouseButton::Left,
+                    ..
+                } => {
+                    key_event = view.keypad.release().map(|key| (key, false));
+                }
+                Event::Window {
+                    win_event: WindowEvent::SizeChanged(..),
+                    ..
+                } => {
+                    view.render(&mut canvas, &chip8);
+                }
+                Event::DropFile { filename, .. } => {
+                    requested_rom = Some(PathBuf::from(filename));
+                }
+                Event::Quit { .. } => {
+                    // The latency probe isn't a game worth coming back to.
+                    if !view.waiting && !args.latency {
+                        if let Err(e) = slots.autosave(&chip8.lock()) {
+                            eprintln!("{e}");
+                        }
+                    }
+                    break 'running;
+                }
+                _ => {}
+            }
+
+            if let Some((key, pressed)) = key_event {
+                if playing_replay {
+                    continue;
+                }
+                emulation.send(Input::Key(key, pressed));
+                if let (Some(meter), false) = (&mut latency_meter, pressed) {
+                    meter.key_released();
+                }
+                keys_changed = true;
+            }
+        }
+
+        #[cfg(feature = "watch")]
+        if let Some(watcher) = &mut watcher {
+            if watcher.poll() {
+                requested_rom = Some(watcher.path().to_path_buf());
+            }
+        }
+
+        if let Some(path) = requested_rom.take() {
+            if !can_switch_rom {
+                eprintln!("Can't switch ROMs during netplay or a replay");
+            } else {
+                match crate::read_rom(&path, args) {
+                    Ok(rom) => {
+                        println!("Loaded {}", path.display());
+                        recent.add(&path);
+                        rom_name = file_name(&path);
+                        #[cfg(feature = "discord")]
+                        if let Some(presence) = &mut presence {
+                            presence.show_rom(&rom_name);
+                        }
+                        #[cfg(feature = "watch")]
+                        if args.watch {
+                            watcher = watch_rom(&path);
+                        }
+                        let mut machine = new_machine(&rom, args, config);
+                        crate::use_flag_file(&mut machine, &rom);
+                        view.stats.attach(&mut machine);
+                        machine.set_sound_hook(buzzer.sound_hook());
+                        *chip8.lock() = machine;
+                        slots = SaveSlots::for_rom(&rom);
+                        achievements = Achievements::for_rom(&path, &rom).unwrap_or_else(|e| {
+                            eprintln!("{e}");
+                            None
+                        });
+                        view.waiting = false;
+                        view.picker = None;
+                        emulation.send(Input::Reset(Cheats::for_rom(&path).unwrap_or_else(|e| {
+                            eprintln!("{e}");
+                            None
+                        })));
+                        view.overlay.symbols = sym_file::for_rom(&path)
+                            .unwrap_or_else(|e| {
+                                eprintln!("{e}");
+                                None
+                            })
+                            .unwrap_or_default();
+                        view.render(&mut canvas, &chip8);
+                    }
+                    Err(e) => {
+                        eprintln!("{e}");
+                        view.render(&mut canvas, &chip8);
+                    }
+                }
+            }
+        }
+
+        update_title(&mut canvas, &rom_name, speed, paused, recorder.is_some());
+        // Keep the readout current and clear old messages even when the game
+        // isn't drawing.
+        let expired = view.overlay.expire_message();
+        if (view.perf.update() && view.overlay.perf) || expired {
+            view.render(&mut canvas, &chip8);
+        }
+    }
+    let replay_mode = emulation.finish();
+
+    if let Some(meter) = &latency_meter {
+        meter.report();
+    }
+
+    if let (ReplayMode::Record(replay), Some(path)) = (&replay_mode, &args.record_input) {
+        match replay.save(path) {
+            Ok(()) => println!("Saved replay to {}", path.display()),
+            Err(e) => eprintln!("Failed to save replay: {e}"),
+        }
+    }
+
+    crate::coverage_map::save(args.coverage.as_deref(), &chip8.lock());
+    view.stats.save();
+    config.audio.volume = buzzer.volume();
+    save_window_geometry(config, canvas.window());
+}
+
+// A fresh machine running `rom`, keeping the --seed.
+fn new_machine(rom: &[u8], args: &RunArgs, config: &Config) -> Chip8 {
+    args.machine(rom, config, args.seed)
+}
+
+fn slot_number(scancode: Scancode) -> Option<u8> {
+    let keys = [
+        Scancode::Num0,
+        Scancode::Num1,
+        Scancode::Num2,
+        Scancode::Num3,
+        Scancode::Num4,
+        Scancode::Num5,
+        Scancode::Num6,
+        Scancode::Num7,
+        Scancode::Num8,
+        Scancode::Num9,
+    ];
+    keys.iter()
+        .position(|&key| key == scancode)
+        .map(|slot| slot as u8)
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .unwrap_or(path.as_os_str())
+        .to_string_lossy()
+        .into_owned()
+}
+
+// Shows e.g. "pong.ch8 (2x) [PAUSED] [REC] - CHIP-8 Emulator", setting the
+// title only when it changes.
+fn update_title(
+    canvas: &mut Canvas<Window>,
+    rom_name: &str,
+    speed: f64,
+    paused: bool,
+    recording: bool,
+) {
+    let mut title = String::new();
+    if !rom_name.is_empty() {
+        title.push_str(&format!("{rom_name} ({speed}x) "));
+    }
+    if paused {
+        title.push_str("[PAUSED] ");
+    }
+    if recording {
+        title.push_str("[REC] ");
+    }
+    if !title.is_empty() {
+        title.push_str("- ");
+    }
+    title.push_str(TITLE);
+
+    let window = canvas.window_mut();
+    if window.title() != title {
+        window.set_title(&title).unwrap();
+    }
+}
+
+#[cfg(feature = "watch")]
+fn watch_rom(rom: &Path) -> Option<RomWatcher> {
+    RomWatcher::new(rom)
+        .map_err(|e| eprintln!("Failed to watch {}: {e}", rom.display()))
+        .ok()
+}
+
+// Everything that decides what the window shows.
+struct View<'a> {
+    renderer: Renderer<'a>,
+    palette: Palette,
+    waiting: bool,
+    picker: Option<RomPicker>,
+    overlay: Overlay,
+    perf: PerfCounter,
+    stats: SessionStats,
+    keypad: Keypad,
+}
+
+impl View<'_> {
+    fn render(&mut self, canvas: &mut Canvas<Window>, chip8: &SharedChip8) {
+        match &self.picker {
+            Some(picker) => picker.render(canvas, &self.palette),
+            None if self.waiting => render_drop_screen(canvas, &mut self.renderer, &self.palette),
+            None => {
+                // Copy out what's shown so the emulation thread isn't kept
+                // waiting while it's drawn.
+                let (display, colors, state, keys) = {
+                    let chip8 = chip8.lock();
+                    let colors = chip8.color_map().copied();
+                    (*chip8.frame_buffer(), colors, chip8.state(), chip8.keys())
+                };
+                self.renderer.draw(canvas, &display, colors.as_ref());
+                self.overlay.draw(canvas, &state, &self.perf, &self.palette);
+                self.keypad.draw(canvas, keys, &self.palette);
+                canvas.present();
+                self.perf.frame();
+                self.stats.frame();
+            }
+        }
+    }
+}
+
+fn render_drop_screen(canvas: &mut Canvas<Window>, renderer: &mut Renderer, palette: &Palette) {
+    renderer.draw(canvas, &FrameBuffer::default(), None);
+    let message = "Drop a ROM file here";
+    text::draw(
+        canvas,
+        message,
+        (TEXT_WIDTH - text::width(message, DROP_TEXT_SIZE)) / 2,
+        (TEXT_HEIGHT - text::height(DROP_TEXT_SIZE)) / 2,
+        DROP_TEXT_SIZE,
+        Color::from(palette.foreground),
+    );
+    canvas.present();
+}
+
+// Fits the logical size to the keypad being shown or hidden, widening or
+// narrowing a windowed window so the display keeps its size.
+fn resize_for_keypad(canvas: &mut Canvas<Window>, keypad: &Keypad) {
+    let (width, height) = keypad.logical_size();
+    canvas.set_logical_size(width, height).unwrap();
+
+    let window = canvas.window_mut();
+    if window.fullscreen_state() == FullscreenType::Off {
+        let (window_width, window_height) = window.size();
+        let keypad_width = KEYPAD_WIDTH * window_height / DISPLAY_HEIGHT as u32;
+        let window_width = if keypad.shown {
+            window_width + keypad_width
+        } else {
+            window_width.saturating_sub(keypad_width).max(1)
+        };
+        window.set_size(window_width, window_height).unwrap();
+    }
+}
+
+// Sizes a new window for the display's pixel density. Where the system
+// measures windows in scaled units, as macOS, Wayland, and Windows with DPI
+// scaling do, the renderer already draws at full resolution and only an
+// explicit --scale, which counts screen pixels, needs converting. Elsewhere
+// the window grows with the display's DPI, so that the display isn't left
+// quarter-sized on a scaled desktop unless --scale asked for that.
+fn fit_to_display(
+    canvas: &mut Canvas<Window>,
+    video: &VideoSubsystem,
+    exact_scale: bool,
+    centered: bool,
+) {
+    let (window_width, window_height) = canvas.window().size();
+    let (output_width, _) = canvas.output_size().unwrap();
+    let density = output_width as f32 / window_width as f32;
+    let factor = match (density > 1.0, exact_scale) {
+        (true, true) => 1.0 / density,
+        (true, false) | (false, true) => return,
+        (false, false) => {
+            let dpi = canvas
+                .window()
+                .display_index()
+                .and_then(|index| video.display_dpi(index))
+                .map_or(BASE_DPI, |(_, horizontal, _)| horizontal);
+            // To the nearest quarter, as desktop scaling goes.
+            (dpi / BASE_DPI * 4.0).round() / 4.0
+        }
+    };
+    if factor <= 1.0 && !exact_scale {
+        return;
+    }
+
+    let resize = |size: u32| ((size as f32 * factor).round() as u32).max(1);
+    let window = canvas.window_mut();
+    window
+        .set_size(resize(window_width), resize(window_height))
+        .unwrap();
+    if centered {
+        window.set_position(WindowPos::Centered, WindowPos::Centered);
+    }
+}
+
+fn toggle_fullscreen(window: &mut sdl2::video::Window) {
+    let state = match window.fullscreen_state() {
+        FullscreenType::Off => FullscreenType::Desktop,
+        _ => FullscreenType::Off,
+    };
+    window.set_fullscreen(state).unwrap();
+}
+
+fn save_window_geometry(config: &mut Config, window: &sdl2::video::Window) {
+    config.window.fullscreen = window.fullscreen_state() != FullscreenType::Off;
+    if !config.window.fullscreen {
+        let (x, y) = window.position();
+        let (width, height) = window.size();
+        config.window.x = Some(x);
+        config.window.y = Some(y);
+        config.window.width = Some(width);
+        config.window.height = Some(height);
+    }
+    config.save();
+}
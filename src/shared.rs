@@ -0,0 +1,80 @@
+use crate::{Chip8, Chip8Error, CpuState, StateError};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// A handle to one machine that several threads can use, such as a GUI
+/// thread sending keys and drawing the display while another runs the
+/// emulation. Clones refer to the same machine.
+///
+/// `Chip8` is `Send` but not `Sync`, since hooks and the RNG are only
+/// `Send`, so sharing goes through a mutex. Each method locks it for the
+/// length of the call; `lock` and `with` hold it across several.
+#[derive(Debug, Clone)]
+pub struct SharedChip8 {
+    chip8: Arc<Mutex<Chip8>>,
+}
+
+impl SharedChip8 {
+    pub fn new(chip8: Chip8) -> SharedChip8 {
+        SharedChip8 {
+            chip8: Arc::new(Mutex::new(chip8)),
+        }
+    }
+
+    /// Locks the machine until the guard is dropped. A thread that panicked
+    /// while holding the lock leaves the machine usable by the others, as
+    /// it was when the panic happened.
+    pub fn lock(&self) -> MutexGuard<'_, Chip8> {
+        self.chip8
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Runs `f` with the machine locked.
+    pub fn with<R>(&self, f: impl FnOnce(&mut Chip8) -> R) -> R {
+        f(&mut self.lock())
+    }
+
+    /// Runs a frame as `Chip8::run_frame`, returning whether it drew and
+    /// whether the buzzer should sound.
+    pub fn run_frame(&self) -> Result<(bool, bool), Chip8Error> {
+        let mut chip8 = self.lock();
+        let frame = chip8.run_frame()?;
+        Ok((frame.drawn, frame.sound))
+    }
+
+    pub fn key_down(&self, key: u8) {
+        self.lock().key_down(key);
+    }
+
+    pub fn key_up(&self, key: u8) {
+        self.lock().key_up(key);
+    }
+
+    /// A copy of the display, a row per word as from `Chip8::display_rows`.
+    pub fn display_rows(&self) -> Vec<u64> {
+        self.lock().display_rows().to_vec()
+    }
+
+    pub fn state(&self) -> CpuState {
+        self.lock().state()
+    }
+
+    pub fn save_state(&self) -> Vec<u8> {
+        self.lock().save_state()
+    }
+
+    pub fn load_state(&self, data: &[u8]) -> Result<(), StateError> {
+        self.lock().load_state(data)
+    }
+
+    /// The machine itself, if this is the last handle to it.
+    pub fn try_unwrap(self) -> Result<Chip8, SharedChip8> {
+        Arc::try_unwrap(self.chip8)
+            .map(|mutex| {
+                mutex
+                    .into_inner()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner())
+            })
+            .map_err(|chip8| SharedChip8 { chip8 })
+    }
+}
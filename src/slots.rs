@@ -0,0 +1,75 @@
+use crate::config;
+use crate::replay;
+use my_chip8::{Chip8, StateError};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+const STATES_DIR: &str = "states";
+const AUTOSAVE: &str = "auto";
+
+// Numbered save states for one ROM, kept in the config directory as
+// `states/<rom hash>.<slot>.state` so they follow the ROM if it is renamed.
+// The state saved on exit goes in `<rom hash>.auto.state`.
+pub struct SaveSlots {
+    rom_hash: u64,
+}
+
+impl SaveSlots {
+    pub fn for_rom(rom: &[u8]) -> SaveSlots {
+        SaveSlots {
+            rom_hash: replay::rom_hash(rom),
+        }
+    }
+
+    pub fn save(&self, slot: u8, chip8: &Chip8) -> Result<PathBuf, String> {
+        self.save_as(&slot.to_string(), chip8)
+    }
+
+    // Restores the state in `slot`, returning false if the slot is empty.
+    pub fn load(&self, slot: u8, chip8: &mut Chip8) -> Result<bool, String> {
+        self.load_from(&slot.to_string(), chip8)
+    }
+
+    pub fn autosave(&self, chip8: &Chip8) -> Result<PathBuf, String> {
+        self.save_as(AUTOSAVE, chip8)
+    }
+
+    // Restores the state saved on exit, returning false if there is none.
+    pub fn resume(&self, chip8: &mut Chip8) -> Result<bool, String> {
+        self.load_from(AUTOSAVE, chip8)
+    }
+
+    fn save_as(&self, name: &str, chip8: &Chip8) -> Result<PathBuf, String> {
+        let path = self.path(name)?;
+        path.parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|()| fs::write(&path, chip8.save_state()))
+            .map_err(|e| format!("Failed to save state to {}: {e}", path.display()))?;
+        Ok(path)
+    }
+
+    fn load_from(&self, name: &str, chip8: &mut Chip8) -> Result<bool, String> {
+        let path = self.path(name)?;
+        let data = match fs::read(&path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(false),
+            Err(e) => return Err(format!("Failed to read {}: {e}", path.display())),
+        };
+        chip8.load_state(&data).map_err(|e| match e {
+            StateError::Invalid => format!("{} is not a valid save state", path.display()),
+            StateError::UnsupportedVersion(version) => format!(
+                "{} uses save state version {version}, which this build can't read",
+                path.display()
+            ),
+        })?;
+        Ok(true)
+    }
+
+    fn path(&self, name: &str) -> Result<PathBuf, String> {
+        let dir = config::config_dir().ok_or("No config directory to keep save states in")?;
+        Ok(dir
+            .join(STATES_DIR)
+            .join(format!("{:016x}.{name}.state", self.rom_hash)))
+    }
+}
@@ -0,0 +1,153 @@
+use crate::frame_buffer::MAX_HEIGHT;
+use crate::{Chip8, Variant, DISPLAY_HEIGHT, MEM_SIZE, NUM_REGISTERS, STACK_SIZE};
+use alloc::vec::Vec;
+
+const MAGIC: &[u8; 4] = b"C8ST";
+const VERSION: u8 = 2;
+// Version 1 had no variant and only the standard display's rows.
+const VERSION_1: u8 = 1;
+
+/// The size in bytes of a save state from `Chip8::save_state`.
+pub const STATE_SIZE: usize = state_size(1, MAX_HEIGHT);
+
+// The size of a save state with a variant field `variant_len` bytes long and
+// `rows` display rows.
+const fn state_size(variant_len: usize, rows: usize) -> usize {
+    sp_offset(variant_len, rows)
+        + 1 // sp
+        + 2 // delay and sound timers
+        + NUM_REGISTERS
+        + 2 // released keys
+        + 1 // waiting for key
+        + 4 // cycles per frame
+        + 4 // frame cycle
+}
+
+const fn sp_offset(variant_len: usize, rows: usize) -> usize {
+    MAGIC.len()
+        + 1 // version
+        + variant_len
+        + MEM_SIZE
+        + rows * 8
+        + 2 // pc
+        + 2 // i
+        + STACK_SIZE * 2
+}
+
+/// Why `Chip8::load_state` rejected a save state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StateError {
+    /// The data isn't a save state, or is cut short.
+    Invalid,
+    /// The save state comes from a newer format version.
+    UnsupportedVersion(u8),
+}
+
+impl Chip8 {
+    /// Serializes the whole machine except for the held keys, the RNG, hooks,
+    /// and breakpoints, which belong to the host rather than the game, and
+    /// CHIP-8X's colors and I/O port.
+    pub fn save_state(&self) -> Vec<u8> {
+        let mut data = Vec::with_capacity(STATE_SIZE);
+        data.extend_from_slice(MAGIC);
+        data.push(VERSION);
+        data.push(match self.variant {
+            Variant::Chip8 => 0,
+            Variant::Chip8X => 1,
+            Variant::HiRes => 2,
+        });
+        data.extend_from_slice(&self.memory);
+        // Room for the tallest display, so every state is the same size.
+        let rows = self.display.row_bits();
+        for row in 0..MAX_HEIGHT {
+            let bits = rows.get(row).copied().unwrap_or(0);
+            data.extend_from_slice(&bits.to_be_bytes());
+        }
+        data.extend_from_slice(&self.pc.to_be_bytes());
+        data.extend_from_slice(&self.i.to_be_bytes());
+        for addr in self.stack {
+            data.extend_from_slice(&addr.to_be_bytes());
+        }
+        data.push(self.sp);
+        data.push(self.delay_timer);
+        data.push(self.sound_timer);
+        data.extend_from_slice(&self.v);
+        data.extend_from_slice(&self.released_keys.to_be_bytes());
+        data.push(self.waiting_for_key as u8);
+        data.extend_from_slice(&self.cycles_per_frame.to_be_bytes());
+        data.extend_from_slice(&self.frame_cycle.to_be_bytes());
+        data
+    }
+
+    /// Restores a save state from `save_state`. On an error the machine is
+    /// left unchanged.
+    pub fn load_state(&mut self, data: &[u8]) -> Result<(), StateError> {
+        if data.len() < MAGIC.len() + 1 || &data[..MAGIC.len()] != MAGIC {
+            return Err(StateError::Invalid);
+        }
+        let version = data[MAGIC.len()];
+        let (variant_len, rows) = match version {
+            VERSION_1 => (0, DISPLAY_HEIGHT),
+            VERSION => (1, MAX_HEIGHT),
+            _ => return Err(StateError::UnsupportedVersion(version)),
+        };
+        if data.len() != state_size(variant_len, rows) {
+            return Err(StateError::Invalid);
+        }
+        if data[sp_offset(variant_len, rows)] as usize > STACK_SIZE {
+            return Err(StateError::Invalid);
+        }
+
+        let mut reader = Reader(&data[MAGIC.len() + 1..]);
+        let variant = match reader.take(variant_len) {
+            [] | [0] => Variant::Chip8,
+            [1] => Variant::Chip8X,
+            [2] => Variant::HiRes,
+            _ => return Err(StateError::Invalid),
+        };
+        if variant != self.variant {
+            self.set_variant(variant);
+        }
+        self.memory.copy_from_slice(reader.take(MEM_SIZE));
+        let mut display_rows = self.display.row_bits_mut().iter_mut();
+        for _ in 0..rows {
+            let bits = u64::from_be_bytes(reader.array());
+            if let Some(row) = display_rows.next() {
+                *row = bits;
+            }
+        }
+        self.pc = u16::from_be_bytes(reader.array());
+        self.i = u16::from_be_bytes(reader.array());
+        for addr in &mut self.stack {
+            *addr = u16::from_be_bytes(reader.array());
+        }
+        let [sp, delay_timer, sound_timer] = reader.array();
+        self.sp = sp;
+        self.delay_timer = delay_timer;
+        self.update_sound_timer(sound_timer);
+        self.v = reader.array();
+        self.released_keys = u16::from_be_bytes(reader.array());
+        self.waiting_for_key = reader.array::<1>()[0] != 0;
+        self.set_cycles_per_frame(u32::from_be_bytes(reader.array()));
+        self.frame_cycle = u32::from_be_bytes(reader.array()).min(self.frame_length() - 1);
+
+        self.invalidate(0, MEM_SIZE);
+        self.draw_flag = true;
+        Ok(())
+    }
+}
+
+// Reads fields in order from a buffer already checked to be long enough.
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> &'a [u8] {
+        let (field, rest) = self.0.split_at(len);
+        self.0 = rest;
+        field
+    }
+
+    fn array<const N: usize>(&mut self) -> [u8; N] {
+        self.take(N).try_into().unwrap()
+    }
+}
@@ -0,0 +1,139 @@
+use crate::RunArgs;
+use my_chip8::Chip8;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+// What a session did, for the --stats report: instructions run, frames
+// shown, and draws, counted over the wall-clock time from start to exit.
+pub struct SessionStats {
+    path: Option<PathBuf>,
+    start: Instant,
+    frames: u64,
+    counts: Arc<Mutex<Counts>>,
+}
+
+#[derive(Default)]
+struct Counts {
+    instructions: u64,
+    // Keyed by the instruction's pattern, e.g. "DXYN" or "FX1E".
+    opcodes: BTreeMap<String, u64>,
+}
+
+#[derive(Serialize)]
+struct Report<'a> {
+    duration_secs: f64,
+    instructions: u64,
+    frames: u64,
+    draws: u64,
+    opcodes: &'a BTreeMap<String, u64>,
+}
+
+impl SessionStats {
+    // Starts counting what `chip8` runs if --stats was given.
+    pub fn start(args: &RunArgs, chip8: &mut Chip8) -> SessionStats {
+        let stats = SessionStats {
+            path: args.stats.clone(),
+            start: Instant::now(),
+            frames: 0,
+            counts: Arc::default(),
+        };
+        stats.attach(chip8);
+        stats
+    }
+
+    // Counts what `chip8` runs too, for a machine that replaced the one the
+    // session started with.
+    pub fn attach(&self, chip8: &mut Chip8) {
+        if self.path.is_none() {
+            return;
+        }
+        let counts = Arc::clone(&self.counts);
+        chip8.set_post_hook(move |_, opcode| {
+            let mut counts = counts.lock().unwrap();
+            counts.instructions += 1;
+            *counts.opcodes.entry(pattern(opcode)).or_default() += 1;
+        });
+    }
+
+    // Counts a frame shown on screen.
+    #[cfg(any(
+        feature = "sdl",
+        feature = "terminal",
+        feature = "pixels",
+        feature = "egui"
+    ))]
+    pub fn frame(&mut self) {
+        self.frames += 1;
+    }
+
+    // Writes the report to the --stats file, if one was given, as CSV if its
+    // name ends in .csv and as JSON otherwise.
+    pub fn save(&self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let counts = self.counts.lock().unwrap();
+        let report = Report {
+            duration_secs: self.start.elapsed().as_secs_f64(),
+            instructions: counts.instructions,
+            frames: self.frames,
+            draws: counts.opcodes.get("DXYN").copied().unwrap_or(0),
+            opcodes: &counts.opcodes,
+        };
+        let is_csv = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("csv"));
+        let text = if is_csv {
+            to_csv(&report)
+        } else {
+            serde_json::to_string_pretty(&report).unwrap() + "\n"
+        };
+        if let Err(e) = fs::write(path, text) {
+            eprintln!("Failed to save stats {}: {e}", path.display());
+        }
+    }
+}
+
+// One "name,value" row per statistic, with the opcode counts after the
+// totals as e.g. "opcode DXYN,120".
+fn to_csv(report: &Report) -> String {
+    let mut csv = format!(
+        "name,value\nduration_secs,{:.3}\ninstructions,{}\nframes,{}\ndraws,{}\n",
+        report.duration_secs, report.instructions, report.frames, report.draws
+    );
+    for (pattern, count) in report.opcodes {
+        csv.push_str(&format!("opcode {pattern},{count}\n"));
+    }
+    csv
+}
+
+// The pattern an opcode is documented by, with its operands as X, Y, N, NN,
+// or NNN.
+fn pattern(opcode: u16) -> String {
+    let low = opcode & 0xff;
+    match opcode >> 12 {
+        0x0 => match opcode {
+            0x00e0 | 0x00ee | 0x00fb..=0x00ff => format!("{opcode:04X}"),
+            _ if opcode & 0xfff0 == 0x00c0 => "00CN".to_string(),
+            _ => "0NNN".to_string(),
+        },
+        0x1 => "1NNN".to_string(),
+        0x2 => "2NNN".to_string(),
+        0x3 => "3XNN".to_string(),
+        0x4 => "4XNN".to_string(),
+        0x5 => "5XY0".to_string(),
+        0x6 => "6XNN".to_string(),
+        0x7 => "7XNN".to_string(),
+        0x8 => format!("8XY{:X}", opcode & 0xf),
+        0x9 => "9XY0".to_string(),
+        0xa => "ANNN".to_string(),
+        0xb => "BNNN".to_string(),
+        0xc => "CXNN".to_string(),
+        0xd => "DXYN".to_string(),
+        high => format!("{high:X}X{low:02X}"),
+    }
+}
@@ -0,0 +1,39 @@
+use crate::RunArgs;
+use my_chip8::Symbols;
+use std::fs;
+use std::path::Path;
+use std::process;
+
+// Loads the symbols given with --symbols, or else those in a .sym file next
+// to the ROM being run, if it has one.
+pub fn from_args(args: &RunArgs) -> Symbols {
+    let symbols = match (&args.symbols, &args.rom) {
+        (Some(path), _) => load(path).map(Some),
+        (None, Some(rom)) => for_rom(Path::new(rom)),
+        (None, None) => Ok(None),
+    };
+    symbols
+        .unwrap_or_else(|e| {
+            eprintln!("{e}");
+            process::exit(1);
+        })
+        .unwrap_or_default()
+}
+
+pub fn for_rom(rom: &Path) -> Result<Option<Symbols>, String> {
+    let path = rom.with_extension("sym");
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let symbols = load(&path)?;
+    println!("Loaded {} symbols from {}", symbols.len(), path.display());
+    Ok(Some(symbols))
+}
+
+pub fn load(path: &Path) -> Result<Symbols, String> {
+    let text =
+        fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+    Symbols::parse(&text)
+        .map_err(|e| format!("{}:{}: invalid symbol: {}", path.display(), e.line, e.text))
+}
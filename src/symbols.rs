@@ -0,0 +1,129 @@
+use crate::MEM_SIZE;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+
+/// Names for addresses, as from an assembler's .sym file, for disassembly and
+/// debugging. Each line of the file gives an address and a name in either
+/// order, with the address in hex:
+///
+/// ```text
+/// 0x2a4 draw_player
+/// main = 0x200
+/// ```
+///
+/// `#` and `;` start comments.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Symbols {
+    names: BTreeMap<u16, String>,
+    addresses: BTreeMap<String, u16>,
+}
+
+/// A line of a symbol file that isn't an address and a name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolError {
+    /// The line number, counting from 1.
+    pub line: usize,
+    pub text: String,
+}
+
+impl fmt::Display for SymbolError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: invalid symbol: {}", self.line, self.text)
+    }
+}
+
+impl Symbols {
+    pub fn new() -> Symbols {
+        Symbols::default()
+    }
+
+    pub fn parse(text: &str) -> Result<Symbols, SymbolError> {
+        let mut symbols = Symbols::new();
+        for (n, line) in text.lines().enumerate() {
+            let line = line.split(['#', ';']).next().unwrap().trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let fields: Vec<&str> = line
+                .split(|c: char| c.is_whitespace() || c == '=')
+                .filter(|field| !field.is_empty())
+                .collect();
+            // Either order will do, but the address comes first if both
+            // fields could be one.
+            let symbol = match fields[..] {
+                [a, b] => symbol(a, b).or_else(|| symbol(b, a)),
+                _ => None,
+            };
+            let (addr, name) = symbol.ok_or_else(|| SymbolError {
+                line: n + 1,
+                text: line.to_string(),
+            })?;
+            symbols.insert(addr, name);
+        }
+        Ok(symbols)
+    }
+
+    /// Names `addr`. The first name given to an address is the one shown for
+    /// it, but every name can be looked up.
+    pub fn insert(&mut self, addr: u16, name: &str) {
+        self.names.entry(addr).or_insert_with(|| name.to_string());
+        self.addresses.insert(name.to_string(), addr);
+    }
+
+    pub fn name(&self, addr: u16) -> Option<&str> {
+        self.names.get(&addr).map(String::as_str)
+    }
+
+    pub fn address(&self, name: &str) -> Option<u16> {
+        self.addresses.get(name).copied()
+    }
+
+    /// The address `target` stands for, either as a name or in hex, for
+    /// setting breakpoints by name.
+    pub fn resolve(&self, target: &str) -> Option<u16> {
+        self.address(target).or_else(|| parse_address(target))
+    }
+
+    /// The name of `addr`, or the address in hex if it has none.
+    pub fn label(&self, addr: u16) -> String {
+        match self.name(addr) {
+            Some(name) => name.to_string(),
+            None => format!("{:#05x}", addr),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.addresses.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.addresses.is_empty()
+    }
+}
+
+fn symbol<'a>(addr: &str, name: &'a str) -> Option<(u16, &'a str)> {
+    parse_address(addr)
+        .filter(|_| is_name(name))
+        .map(|addr| (addr, name))
+}
+
+fn parse_address(text: &str) -> Option<u16> {
+    let digits = text
+        .strip_prefix("0x")
+        .or_else(|| text.strip_prefix("0X"))
+        .unwrap_or(text);
+    u16::from_str_radix(digits, 16)
+        .ok()
+        .filter(|&addr| (addr as usize) < MEM_SIZE)
+}
+
+fn is_name(text: &str) -> bool {
+    text.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && text
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.')
+}
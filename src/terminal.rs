@@ -0,0 +1,191 @@
+use crate::config::Config;
+use crate::driver::{DisplaySink, Driver};
+use crate::palette::{Palette, Rgb};
+use crate::stats::SessionStats;
+use crate::{keymap, RunArgs};
+use crossterm::event::{
+    self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, KeyboardEnhancementFlags,
+    PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+};
+use crossterm::style::{Color, Print, SetBackgroundColor, SetForegroundColor};
+use crossterm::{cursor, execute, queue, terminal};
+use my_chip8::{Chip8, FrameBuffer};
+use std::io::{self, Stdout, Write};
+use std::process;
+use std::time::{Duration, Instant};
+
+// Most terminals only report key presses, so a key is released once it
+// hasn't been repeated for this long.
+const KEY_HOLD: Duration = Duration::from_millis(150);
+
+struct TerminalSink {
+    stdout: Stdout,
+    palette: Palette,
+}
+
+impl DisplaySink for TerminalSink {
+    fn present(&mut self, display: &FrameBuffer) {
+        draw(&mut self.stdout, display, &self.palette).unwrap();
+    }
+}
+
+pub fn run(args: &RunArgs, config: &Config, mut chip8: Chip8) {
+    let mut stats = SessionStats::start(args, &mut chip8);
+    let mut sink = TerminalSink {
+        stdout: io::stdout(),
+        palette: args.palette(config),
+    };
+    let key_releases = enter(&mut sink.stdout).unwrap();
+
+    let mut driver = Driver::new(chip8);
+    let mut held_keys = HeldKeys::new(key_releases);
+    let mut error = None;
+    'running: loop {
+        if let Err(e) = driver.run_due_cycles() {
+            error = Some(e);
+            break 'running;
+        }
+        if driver.needs_present() {
+            driver.present(&mut sink);
+            stats.frame();
+        }
+
+        while event::poll(Duration::ZERO).unwrap() {
+            let Event::Key(KeyEvent {
+                code,
+                modifiers,
+                kind,
+                ..
+            }) = event::read().unwrap()
+            else {
+                continue;
+            };
+
+            match code {
+                KeyCode::Esc => break 'running,
+                KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => break 'running,
+                KeyCode::Char(c) => {
+                    let Some(key) = keymap::char_to_key(c) else {
+                        continue;
+                    };
+                    held_keys.key_event(driver.chip8_mut(), key, kind);
+                }
+                _ => {}
+            }
+        }
+        held_keys.release_expired(driver.chip8_mut());
+
+        let now = Instant::now();
+        if driver.next_cycle() > now {
+            std::thread::sleep(driver.next_cycle() - now);
+        }
+    }
+
+    leave(&mut sink.stdout, key_releases).unwrap();
+    crate::coverage_map::save(args.coverage.as_deref(), driver.chip8());
+    stats.save();
+    if let Some(e) = error {
+        eprintln!("Emulation error: {e}");
+        process::exit(1);
+    }
+}
+
+// Switches the terminal into raw mode on the alternate screen, returning
+// whether it will report key releases.
+pub fn enter(stdout: &mut impl Write) -> io::Result<bool> {
+    terminal::enable_raw_mode()?;
+    execute!(stdout, terminal::EnterAlternateScreen, cursor::Hide)?;
+    let key_releases = terminal::supports_keyboard_enhancement().unwrap_or(false);
+    if key_releases {
+        execute!(
+            stdout,
+            PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::REPORT_EVENT_TYPES)
+        )?;
+    }
+    Ok(key_releases)
+}
+
+pub fn leave(stdout: &mut impl Write, key_releases: bool) -> io::Result<()> {
+    if key_releases {
+        execute!(stdout, PopKeyboardEnhancementFlags)?;
+    }
+    execute!(stdout, cursor::Show, terminal::LeaveAlternateScreen)?;
+    terminal::disable_raw_mode()
+}
+
+pub struct HeldKeys {
+    key_releases: bool,
+    held_until: [Option<Instant>; 16],
+}
+
+impl HeldKeys {
+    pub fn new(key_releases: bool) -> HeldKeys {
+        HeldKeys {
+            key_releases,
+            held_until: [None; 16],
+        }
+    }
+
+    pub fn key_event(&mut self, chip8: &mut Chip8, key: u8, kind: KeyEventKind) {
+        match kind {
+            KeyEventKind::Release => chip8.key_up(key),
+            _ => {
+                chip8.key_down(key);
+                if !self.key_releases {
+                    self.held_until[key as usize] = Some(Instant::now() + KEY_HOLD);
+                }
+            }
+        }
+    }
+
+    pub fn release_expired(&mut self, chip8: &mut Chip8) {
+        for (key, deadline) in self.held_until.iter_mut().enumerate() {
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                chip8.key_up(key as u8);
+                *deadline = None;
+            }
+        }
+    }
+}
+
+// Each character cell shows two vertically stacked pixels using the upper
+// half block, with the top pixel as foreground and the bottom as background.
+fn draw(stdout: &mut impl Write, display: &FrameBuffer, palette: &Palette) -> io::Result<()> {
+    let color = |lit: bool| {
+        let rgb = if lit {
+            palette.foreground
+        } else {
+            palette.background
+        };
+        terminal_color(rgb)
+    };
+
+    let (width, height) = display.size();
+    for row in 0..height / 2 {
+        queue!(stdout, cursor::MoveTo(0, row as u16))?;
+        for col in 0..width {
+            let top = display.pixel(col, row * 2);
+            let bottom = display.pixel(col, row * 2 + 1);
+            queue!(
+                stdout,
+                SetForegroundColor(color(top)),
+                SetBackgroundColor(color(bottom)),
+                Print('▀')
+            )?;
+        }
+    }
+    queue!(
+        stdout,
+        SetForegroundColor(Color::Reset),
+        SetBackgroundColor(Color::Reset)
+    )?;
+    stdout.flush()
+}
+
+pub fn terminal_color(rgb: Rgb) -> Color {
+    Color::Rgb {
+        r: rgb.r,
+        g: rgb.g,
+        b: rgb.b,
+    }
+}
@@ -0,0 +1,133 @@
+use my_chip8::{DISPLAY_HEIGHT, DISPLAY_WIDTH};
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::Canvas;
+use sdl2::video::Window;
+
+// Text is laid out on a grid this many times finer than the window's logical
+// pixels, so messages stay legible at the display's 64x32 logical size. The
+// display covers the top-left TEXT_WIDTH x TEXT_HEIGHT of the grid.
+pub const TEXT_SCALE: u32 = 8;
+pub const TEXT_WIDTH: i32 = DISPLAY_WIDTH as i32 * TEXT_SCALE as i32;
+pub const TEXT_HEIGHT: i32 = DISPLAY_HEIGHT as i32 * TEXT_SCALE as i32;
+
+const GLYPH_WIDTH: i32 = 3;
+const GLYPH_HEIGHT: i32 = 5;
+
+/// Draws `text` in a 3x5 pixel font with its top-left corner at (x, y) on
+/// the text grid, each font pixel `size` grid units wide. Lower case is drawn
+/// as upper case and unsupported characters as blanks.
+pub fn draw(canvas: &mut Canvas<Window>, text: &str, x: i32, y: i32, size: u32, color: Color) {
+    let size = size as i32;
+    let mut rects = Vec::new();
+    for (n, c) in text.chars().enumerate() {
+        let left = x + n as i32 * (GLYPH_WIDTH + 1) * size;
+        for (row, bits) in glyph(c).into_iter().enumerate() {
+            for col in 0..GLYPH_WIDTH {
+                if bits & (0b100 >> col) != 0 {
+                    rects.push(Rect::new(
+                        left + col * size,
+                        y + row as i32 * size,
+                        size as u32,
+                        size as u32,
+                    ));
+                }
+            }
+        }
+    }
+    on_grid(canvas, |canvas| {
+        canvas.set_draw_color(color);
+        canvas.fill_rects(&rects).unwrap();
+    });
+}
+
+/// Fills `rect`, given on the text grid.
+pub fn fill_rect(canvas: &mut Canvas<Window>, rect: Rect, color: Color) {
+    on_grid(canvas, |canvas| {
+        canvas.set_draw_color(color);
+        canvas.fill_rect(rect).unwrap();
+    });
+}
+
+/// Runs `draw` with the canvas working in text grid units. It scales the
+/// canvas rather than changing its logical size, which keeps the area of the
+/// window that integer scaling settled on.
+pub fn on_grid<R>(canvas: &mut Canvas<Window>, draw: impl FnOnce(&mut Canvas<Window>) -> R) -> R {
+    let (scale_x, scale_y) = canvas.scale();
+    let grid = TEXT_SCALE as f32;
+    canvas.set_scale(scale_x / grid, scale_y / grid).unwrap();
+    let result = draw(canvas);
+    canvas.set_scale(scale_x, scale_y).unwrap();
+    result
+}
+
+/// The width of `text` on the text grid, without trailing spacing.
+pub fn width(text: &str, size: u32) -> i32 {
+    let chars = text.chars().count() as i32;
+    (chars * (GLYPH_WIDTH + 1) - 1).max(0) * size as i32
+}
+
+pub fn height(size: u32) -> i32 {
+    GLYPH_HEIGHT * size as i32
+}
+
+fn glyph(c: char) -> [u8; GLYPH_HEIGHT as usize] {
+    match c.to_ascii_uppercase() {
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b011, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b010, 0b010, 0b010],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        'A' => [0b010, 0b101, 0b111, 0b101, 0b101],
+        'B' => [0b110, 0b101, 0b110, 0b101, 0b110],
+        'C' => [0b011, 0b100, 0b100, 0b100, 0b011],
+        'D' => [0b110, 0b101, 0b101, 0b101, 0b110],
+        'E' => [0b111, 0b100, 0b110, 0b100, 0b111],
+        'F' => [0b111, 0b100, 0b110, 0b100, 0b100],
+        'G' => [0b011, 0b100, 0b101, 0b101, 0b011],
+        'H' => [0b101, 0b101, 0b111, 0b101, 0b101],
+        'I' => [0b111, 0b010, 0b010, 0b010, 0b111],
+        'J' => [0b001, 0b001, 0b001, 0b101, 0b010],
+        'K' => [0b101, 0b101, 0b110, 0b101, 0b101],
+        'L' => [0b100, 0b100, 0b100, 0b100, 0b111],
+        'M' => [0b101, 0b111, 0b111, 0b101, 0b101],
+        'N' => [0b110, 0b101, 0b101, 0b101, 0b101],
+        'O' => [0b010, 0b101, 0b101, 0b101, 0b010],
+        'P' => [0b110, 0b101, 0b110, 0b100, 0b100],
+        'Q' => [0b010, 0b101, 0b101, 0b110, 0b011],
+        'R' => [0b110, 0b101, 0b110, 0b101, 0b101],
+        'S' => [0b011, 0b100, 0b010, 0b001, 0b110],
+        'T' => [0b111, 0b010, 0b010, 0b010, 0b010],
+        'U' => [0b101, 0b101, 0b101, 0b101, 0b111],
+        'V' => [0b101, 0b101, 0b101, 0b101, 0b010],
+        'W' => [0b101, 0b101, 0b111, 0b111, 0b101],
+        'X' => [0b101, 0b101, 0b010, 0b101, 0b101],
+        'Y' => [0b101, 0b101, 0b010, 0b010, 0b010],
+        'Z' => [0b111, 0b001, 0b010, 0b100, 0b111],
+        '.' => [0b000, 0b000, 0b000, 0b000, 0b010],
+        ',' => [0b000, 0b000, 0b000, 0b010, 0b100],
+        ':' => [0b000, 0b010, 0b000, 0b010, 0b000],
+        '-' => [0b000, 0b000, 0b111, 0b000, 0b000],
+        '_' => [0b000, 0b000, 0b000, 0b000, 0b111],
+        '+' => [0b000, 0b010, 0b111, 0b010, 0b000],
+        '=' => [0b000, 0b111, 0b000, 0b111, 0b000],
+        '/' => [0b001, 0b001, 0b010, 0b100, 0b100],
+        '!' => [0b010, 0b010, 0b010, 0b000, 0b010],
+        '?' => [0b110, 0b001, 0b010, 0b000, 0b010],
+        '(' => [0b001, 0b010, 0b010, 0b010, 0b001],
+        ')' => [0b100, 0b010, 0b010, 0b010, 0b100],
+        '[' => [0b011, 0b010, 0b010, 0b010, 0b011],
+        ']' => [0b110, 0b010, 0b010, 0b010, 0b110],
+        '<' => [0b001, 0b010, 0b100, 0b010, 0b001],
+        '>' => [0b100, 0b010, 0b001, 0b010, 0b100],
+        '%' => [0b101, 0b001, 0b010, 0b100, 0b101],
+        '#' => [0b101, 0b111, 0b101, 0b111, 0b101],
+        '\'' => [0b010, 0b010, 0b000, 0b000, 0b000],
+        _ => [0; GLYPH_HEIGHT as usize],
+    }
+}
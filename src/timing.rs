@@ -0,0 +1,71 @@
+use crate::decode::Inst;
+
+/// The CDP1802 machine cycles the COSMAC VIP interpreter gets in a 60Hz
+/// frame: the CPU's 3668 less the 1024 the display's DMA steals and the
+/// interrupt routine's 60 or so.
+pub const VIP_CYCLES_PER_FRAME: u32 = 2584;
+
+/// How long instructions take, which decides how many run between ticks of
+/// the 60Hz timers.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Timing {
+    /// Every instruction takes the same time, `Chip8::cycles_per_frame` of
+    /// them to a frame.
+    #[default]
+    Flat,
+    /// Each instruction takes about as many machine cycles as the COSMAC VIP
+    /// interpreter spends on it, out of `VIP_CYCLES_PER_FRAME` a frame. Draws
+    /// cost more for taller sprites and for ones not aligned to a byte.
+    Vip,
+}
+
+// The machine cycles the VIP interpreter spends on `inst`, fetch and decode
+// included, approximated from measurements of the original.
+pub(crate) fn vip_cycles(inst: Inst, v: &[u8; 16]) -> u32 {
+    match inst {
+        Inst::SetImm(..) => 6,
+        Inst::AddImm(..)
+        | Inst::GetDelayTimer(_)
+        | Inst::GetKey(_)
+        | Inst::SetDelayTimer(_)
+        | Inst::SetSoundTimer(_) => 10,
+        Inst::SkipEqImm(..) | Inst::SkipNeqImm(..) | Inst::SetI(_) => 12,
+        Inst::SkipEqReg(..)
+        | Inst::SkipNeqReg(..)
+        | Inst::SkipKeyPressed(_)
+        | Inst::SkipKeyNotPressed(_)
+        | Inst::SkipKey2Pressed(_)
+        | Inst::SkipKey2NotPressed(_) => 16,
+        Inst::AddI(_) => 19,
+        Inst::Font(_) | Inst::LargeFont(_) => 20,
+        Inst::Cls => 24,
+        Inst::Random(..) => 36,
+        Inst::SetReg(..)
+        | Inst::Or(..)
+        | Inst::And(..)
+        | Inst::Xor(..)
+        | Inst::AddReg(..)
+        | Inst::Sub(..)
+        | Inst::ShiftRight(..)
+        | Inst::ReverseSub(..)
+        | Inst::ShiftLeft(..)
+        | Inst::AddNibbles(..) => 44,
+        Inst::Bcd(_) => 204,
+        Inst::RegDump(x) | Inst::RegLoad(x) | Inst::SaveFlags(x) | Inst::LoadFlags(x) => {
+            14 + 8 * (x as u32 + 1)
+        }
+        // Sprites that straddle two bytes of the display are shifted into
+        // both a row at a time.
+        Inst::Draw(x, _, n) => {
+            let per_row = if v[x as usize].is_multiple_of(8) {
+                12
+            } else {
+                24
+            };
+            68 + per_row * n as u32
+        }
+        Inst::Undecoded | Inst::Unknown => 0,
+        // Jumps, calls, returns, machine code, and the CHIP-8X extensions.
+        _ => 23,
+    }
+}
@@ -0,0 +1,355 @@
+use crate::config::Config;
+use crate::palette::Palette;
+use crate::perf::PerfCounter;
+use crate::stats::SessionStats;
+use crate::terminal::{self as term, HeldKeys};
+use crate::{keymap, sym_file, RunArgs, FRAME_INTERVAL};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use my_chip8::{disasm, Chip8, FrameBuffer, Symbols, DISPLAY_HEIGHT, DISPLAY_WIDTH};
+use ratatui::backend::CrosstermBackend;
+use ratatui::buffer::Buffer;
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, List, ListItem, Paragraph, Tabs, Widget};
+use ratatui::{Frame, Terminal};
+use std::collections::VecDeque;
+use std::io;
+use std::process;
+use std::time::{Duration, Instant};
+
+const HISTORY_LEN: usize = 32;
+const SCREEN_WIDTH: u16 = DISPLAY_WIDTH as u16 + 2;
+const SCREEN_HEIGHT: u16 = DISPLAY_HEIGHT as u16 / 2 + 2;
+const BYTES_PER_ROW: usize = 16;
+const MEMORY_PAGE: usize = 16;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Tab {
+    Play,
+    Debug,
+    Memory,
+}
+
+struct App {
+    chip8: Chip8,
+    palette: Palette,
+    tab: Tab,
+    history: VecDeque<(u16, u16)>,
+    symbols: Symbols,
+    perf: PerfCounter,
+    // The first row shown in the memory view.
+    memory_scroll: usize,
+}
+
+pub fn run(args: &RunArgs, config: &Config, mut chip8: Chip8) {
+    let mut stats = SessionStats::start(args, &mut chip8);
+    let mut stdout = io::stdout();
+    let key_releases = term::enter(&mut stdout).unwrap();
+    let mut terminal = Terminal::new(CrosstermBackend::new(stdout)).unwrap();
+
+    let mut app = App {
+        chip8,
+        palette: args.palette(config),
+        tab: Tab::Play,
+        history: VecDeque::with_capacity(HISTORY_LEN),
+        symbols: sym_file::from_args(args),
+        perf: PerfCounter::default(),
+        memory_scroll: 0,
+    };
+    let mut held_keys = HeldKeys::new(key_releases);
+
+    let mut dirty = true;
+    let mut last_render = Instant::now();
+    let mut error = None;
+    'running: loop {
+        if app.history.len() == HISTORY_LEN {
+            app.history.pop_front();
+        }
+        app.history
+            .push_back((app.chip8.pc(), app.chip8.current_opcode()));
+        if let Err(e) = app.chip8.run_cycle() {
+            error = Some(e);
+            break 'running;
+        }
+        app.perf.cycle();
+        dirty |= app.chip8.draw_flag() || app.tab != Tab::Play;
+
+        if dirty && last_render.elapsed() >= FRAME_INTERVAL {
+            terminal.draw(|frame| app.draw(frame)).unwrap();
+            app.perf.frame();
+            stats.frame();
+            dirty = false;
+            last_render = Instant::now();
+        }
+
+        dirty |= app.perf.update();
+
+        while event::poll(Duration::ZERO).unwrap() {
+            let Event::Key(KeyEvent {
+                code,
+                modifiers,
+                kind,
+                ..
+            }) = event::read().unwrap()
+            else {
+                dirty = true;
+                continue;
+            };
+
+            match code {
+                KeyCode::Esc => break 'running,
+                KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => break 'running,
+                KeyCode::Tab if kind == KeyEventKind::Press => {
+                    app.tab = match app.tab {
+                        Tab::Play => Tab::Debug,
+                        Tab::Debug => Tab::Memory,
+                        Tab::Memory => Tab::Play,
+                    };
+                    dirty = true;
+                }
+                KeyCode::Up
+                | KeyCode::Down
+                | KeyCode::PageUp
+                | KeyCode::PageDown
+                | KeyCode::Home
+                | KeyCode::End
+                    if app.tab == Tab::Memory && kind != KeyEventKind::Release =>
+                {
+                    app.scroll_memory(code);
+                    dirty = true;
+                }
+                KeyCode::Char(c) => {
+                    if let Some(key) = keymap::char_to_key(c) {
+                        held_keys.key_event(&mut app.chip8, key, kind);
+                    }
+                }
+                _ => {}
+            }
+        }
+        held_keys.release_expired(&mut app.chip8);
+
+        std::thread::sleep(app.chip8.last_instruction_duration());
+    }
+
+    term::leave(terminal.backend_mut(), key_releases).unwrap();
+    crate::coverage_map::save(args.coverage.as_deref(), &app.chip8);
+    stats.save();
+    if let Some(e) = error {
+        eprintln!("Emulation error: {e}");
+        process::exit(1);
+    }
+}
+
+impl App {
+    fn draw(&self, frame: &mut Frame) {
+        let [header, body, footer] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+
+        let selected = match self.tab {
+            Tab::Play => 0,
+            Tab::Debug => 1,
+            Tab::Memory => 2,
+        };
+        frame.render_widget(
+            Tabs::new(["Play", "Debug", "Memory"])
+                .select(selected)
+                .highlight_style(Style::new().bold().reversed()),
+            header,
+        );
+        frame.render_widget(
+            Line::from(format!(
+                " FPS: {}  IPS: {}  (Tab: switch view, Esc: quit)",
+                self.perf.fps(),
+                self.perf.ips()
+            )),
+            footer,
+        );
+
+        match self.tab {
+            Tab::Play => {
+                let [_, screen, _] = Layout::horizontal([
+                    Constraint::Fill(1),
+                    Constraint::Length(SCREEN_WIDTH),
+                    Constraint::Fill(1),
+                ])
+                .areas(body);
+                let [screen, _] =
+                    Layout::vertical([Constraint::Length(SCREEN_HEIGHT), Constraint::Min(0)])
+                        .areas(screen);
+                self.draw_screen(frame, screen);
+            }
+            Tab::Debug => {
+                let [left, registers, history] = Layout::horizontal([
+                    Constraint::Length(SCREEN_WIDTH),
+                    Constraint::Length(20),
+                    Constraint::Min(24),
+                ])
+                .areas(body);
+                let [screen, _] =
+                    Layout::vertical([Constraint::Length(SCREEN_HEIGHT), Constraint::Min(0)])
+                        .areas(left);
+                self.draw_screen(frame, screen);
+                self.draw_registers(frame, registers);
+                self.draw_history(frame, history);
+            }
+            Tab::Memory => self.draw_memory(frame, body),
+        }
+    }
+
+    fn scroll_memory(&mut self, code: KeyCode) {
+        let last = self.chip8.memory().len() / BYTES_PER_ROW - 1;
+        self.memory_scroll = match code {
+            KeyCode::Up => self.memory_scroll.saturating_sub(1),
+            KeyCode::Down => self.memory_scroll + 1,
+            KeyCode::PageUp => self.memory_scroll.saturating_sub(MEMORY_PAGE),
+            KeyCode::PageDown => self.memory_scroll + MEMORY_PAGE,
+            KeyCode::Home => 0,
+            KeyCode::End => last,
+            _ => self.memory_scroll,
+        }
+        .min(last);
+    }
+
+    // A hexdump of memory with the two bytes at PC shown reversed and the
+    // byte at I underlined.
+    fn draw_memory(&self, frame: &mut Frame, area: Rect) {
+        let state = self.chip8.state();
+        let pc = state.pc as usize;
+        let i = state.i as usize;
+        let visible = area.height.saturating_sub(2) as usize;
+        let memory = self.chip8.memory();
+        let rows = memory.len() / BYTES_PER_ROW;
+        let first = self.memory_scroll.min(rows.saturating_sub(visible));
+
+        let lines: Vec<Line> = memory
+            .chunks(BYTES_PER_ROW)
+            .enumerate()
+            .skip(first)
+            .take(visible)
+            .map(|(row, bytes)| {
+                let start = row * BYTES_PER_ROW;
+                let mut spans = vec![Span::raw(format!("{start:#05x} "))];
+                for (offset, byte) in bytes.iter().enumerate() {
+                    let addr = start + offset;
+                    let mut style = Style::new();
+                    if addr == pc || addr == pc + 1 {
+                        style = style.reversed();
+                    }
+                    if addr == i {
+                        style = style.underlined().bold();
+                    }
+                    spans.push(Span::raw(" "));
+                    spans.push(Span::styled(format!("{byte:02x}"), style));
+                }
+                let ascii: String = bytes
+                    .iter()
+                    .map(|&b| if b.is_ascii_graphic() { b as char } else { '.' })
+                    .collect();
+                spans.push(Span::raw(format!("  {ascii}")));
+                Line::from(spans)
+            })
+            .collect();
+
+        let title = format!("Memory  PC {pc:#05x}  I {i:#05x}  (arrows/PgUp/PgDn: scroll)");
+        frame.render_widget(
+            Paragraph::new(lines).block(Block::bordered().title(title)),
+            area,
+        );
+    }
+
+    fn draw_screen(&self, frame: &mut Frame, area: Rect) {
+        let block = Block::bordered().title("Screen");
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+        frame.render_widget(
+            Screen {
+                display: self.chip8.frame_buffer(),
+                palette: &self.palette,
+            },
+            inner,
+        );
+    }
+
+    fn draw_registers(&self, frame: &mut Frame, area: Rect) {
+        let state = self.chip8.state();
+        let mut lines = vec![
+            Line::from(format!("PC  {:#05x}", state.pc)),
+            Line::from(format!("I   {:#05x}", state.i)),
+            Line::from(format!("SP  {}", state.sp)),
+            Line::from(format!("DT  {}", state.delay_timer)),
+            Line::from(format!("ST  {}", state.sound_timer)),
+            Line::default(),
+        ];
+        lines.extend(
+            state
+                .v
+                .iter()
+                .enumerate()
+                .map(|(i, v)| Line::from(format!("V{:X}  {:#04x}", i, v))),
+        );
+        frame.render_widget(
+            Paragraph::new(lines).block(Block::bordered().title("Registers")),
+            area,
+        );
+    }
+
+    fn draw_history(&self, frame: &mut Frame, area: Rect) {
+        let visible = area.height.saturating_sub(2) as usize;
+        let items: Vec<ListItem> = self
+            .history
+            .iter()
+            .rev()
+            .take(visible)
+            .map(|&(pc, opcode)| {
+                ListItem::new(format!(
+                    "{}  {:04x}  {}",
+                    self.symbols.label(pc),
+                    opcode,
+                    disasm::disassemble_with(opcode, &self.symbols)
+                ))
+            })
+            .collect();
+        frame.render_widget(
+            List::new(items).block(Block::bordered().title("Last instructions")),
+            area,
+        );
+    }
+}
+
+struct Screen<'a> {
+    display: &'a FrameBuffer,
+    palette: &'a Palette,
+}
+
+impl Widget for Screen<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let color = |lit: bool| {
+            let rgb = if lit {
+                self.palette.foreground
+            } else {
+                self.palette.background
+            };
+            Color::Rgb(rgb.r, rgb.g, rgb.b)
+        };
+
+        let (width, height) = self.display.size();
+        let rows = area.height.min(height as u16 / 2);
+        let cols = area.width.min(width as u16);
+        for row in 0..rows {
+            for col in 0..cols {
+                let (x, y) = (col as usize, row as usize * 2);
+                let top = self.display.pixel(x, y);
+                let bottom = self.display.pixel(x, y + 1);
+                buf[(area.x + col, area.y + row)]
+                    .set_char('▀')
+                    .set_fg(color(top))
+                    .set_bg(color(bottom));
+            }
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use crate::{CHIP_8X_START_ADDR, DISPLAY_HEIGHT, DISPLAY_WIDTH, START_ADDR};
+
+/// The machine a ROM was written for, when it uses instructions beyond the
+/// original CHIP-8 set that clash with it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Variant {
+    /// CHIP-8, with whichever quirks are set.
+    #[default]
+    Chip8,
+    /// CHIP-8X, for the COSMAC VIP with the VP-590 color board and a second
+    /// keypad. It adds color (02A0, BXY0, BXYN), nibble-wise addition (5XY1),
+    /// the second keypad (EXF2, EXF5), and the I/O port (FXF8, FXFB). Its
+    /// ROMs load at `CHIP_8X_START_ADDR`.
+    Chip8X,
+    /// The two-page hi-res interpreter for the COSMAC VIP, with a 64x64
+    /// display. Its ROMs start by jumping to 0x260 (1260), which enters
+    /// hi-res mode and continues at 0x2C0, and clear the screen with 0230.
+    HiRes,
+}
+
+impl Variant {
+    /// Guesses the variant from the start of a ROM loaded at `START_ADDR`.
+    /// Only hi-res ROMs can be told apart; everything else is `Chip8`.
+    pub fn detect(rom: &[u8]) -> Variant {
+        if rom.starts_with(&[0x12, 0x60]) {
+            Variant::HiRes
+        } else {
+            Variant::Chip8
+        }
+    }
+
+    /// Where the variant's ROMs load and start.
+    pub fn start_address(self) -> u16 {
+        match self {
+            Variant::Chip8X => CHIP_8X_START_ADDR,
+            Variant::Chip8 | Variant::HiRes => START_ADDR,
+        }
+    }
+
+    pub(crate) fn display_size(self) -> (usize, usize) {
+        match self {
+            Variant::Chip8 | Variant::Chip8X => (DISPLAY_WIDTH, DISPLAY_HEIGHT),
+            Variant::HiRes => (DISPLAY_WIDTH, 2 * DISPLAY_HEIGHT),
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use crate::{with_quirks, VerifyArgs};
+use my_chip8::debugger::trace_line;
+use my_chip8::{disasm, Chip8, CpuState};
+use std::fs;
+use std::process;
+
+// A field of a trace line that can be checked against the machine.
+#[derive(Clone, Copy)]
+enum Field {
+    Pc,
+    Opcode,
+    I,
+    Sp,
+    Dt,
+    St,
+    V(usize),
+}
+
+impl Field {
+    fn parse(key: &str) -> Option<Field> {
+        let key = key.to_ascii_uppercase();
+        let field = match key.as_str() {
+            "PC" => Field::Pc,
+            "OP" | "OPCODE" => Field::Opcode,
+            "I" => Field::I,
+            "SP" => Field::Sp,
+            "DT" => Field::Dt,
+            "ST" => Field::St,
+            _ => {
+                let x = key.strip_prefix('V').filter(|x| x.len() == 1)?;
+                Field::V(usize::from_str_radix(x, 16).ok()?)
+            }
+        };
+        Some(field)
+    }
+
+    fn name(self) -> String {
+        match self {
+            Field::Pc => "PC".to_string(),
+            Field::Opcode => "the opcode".to_string(),
+            Field::I => "I".to_string(),
+            Field::Sp => "SP".to_string(),
+            Field::Dt => "DT".to_string(),
+            Field::St => "ST".to_string(),
+            Field::V(x) => format!("V{x:X}"),
+        }
+    }
+
+    fn value(self, state: &CpuState, opcode: u16) -> u16 {
+        match self {
+            Field::Pc => state.pc,
+            Field::Opcode => opcode,
+            Field::I => state.i,
+            Field::Sp => state.sp as u16,
+            Field::Dt => state.delay_timer as u16,
+            Field::St => state.sound_timer as u16,
+            Field::V(x) => state.v[x] as u16,
+        }
+    }
+}
+
+pub fn run(args: &VerifyArgs) {
+    let rom = fs::read(&args.rom).unwrap_or_else(|e| {
+        eprintln!("Failed to load ROM {}: {e}", args.rom.display());
+        process::exit(1);
+    });
+    let log = fs::read_to_string(&args.against).unwrap_or_else(|e| {
+        eprintln!("Failed to read {}: {e}", args.against.display());
+        process::exit(1);
+    });
+
+    let mut chip8 = Chip8::with_seed(args.seed);
+    chip8.load_rom_bytes(&rom);
+    let profile = args.quirk_profile.map(|p| p.quirks()).unwrap_or_default();
+    chip8.set_quirks(with_quirks(profile, &args.quirks));
+
+    let mut count = 0;
+    // Lines without any known fields, like headers, aren't instructions.
+    let lines = log
+        .lines()
+        .enumerate()
+        .map(|(n, line)| (n + 1, line, fields(line)))
+        .filter(|(_, _, fields)| !fields.is_empty());
+    for (line_number, line, fields) in lines {
+        let state = chip8.state();
+        let opcode = chip8.current_opcode();
+        let difference = fields
+            .iter()
+            .find(|&&(field, expected)| field.value(&state, opcode) != expected);
+        if let Some(&(field, expected)) = difference {
+            println!(
+                "Diverged at instruction {} ({}:{line_number}), before {:#05x} {}",
+                count + 1,
+                args.against.display(),
+                state.pc,
+                disasm::disassemble(opcode)
+            );
+            println!("  expected: {}", line.trim());
+            println!("  actual:   {}", trace_line(&state, opcode));
+            println!(
+                "{} is {:#x}, expected {:#x}",
+                field.name(),
+                field.value(&state, opcode),
+                expected
+            );
+            process::exit(1);
+        }
+
+        if let Err(e) = chip8.run_cycle() {
+            println!(
+                "Emulation error at instruction {}: {e}\n  {}",
+                count + 1,
+                trace_line(&state, opcode)
+            );
+            process::exit(1);
+        }
+        count += 1;
+    }
+    println!("All {count} instructions match");
+}
+
+// The known fields of a log line, as "KEY:VALUE" or "KEY=VALUE" with the
+// value in hex, separated by spaces or commas.
+fn fields(line: &str) -> Vec<(Field, u16)> {
+    line.split(|c: char| c.is_whitespace() || c == ',')
+        .filter_map(|token| {
+            let (key, value) = token.split_once([':', '='])?;
+            let field = Field::parse(key)?;
+            let digits = value
+                .strip_prefix("0x")
+                .or_else(|| value.strip_prefix('$'))
+                .unwrap_or(value);
+            Some((field, u16::from_str_radix(digits, 16).ok()?))
+        })
+        .collect()
+}
@@ -0,0 +1,80 @@
+use crate::{Chip8, Variant};
+use core::time::Duration;
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    // The page's monotonic clock, in milliseconds.
+    #[wasm_bindgen(js_namespace = performance, js_name = now)]
+    fn performance_now() -> f64;
+}
+
+#[wasm_bindgen(js_name = Chip8)]
+pub struct WebChip8 {
+    chip8: Chip8,
+}
+
+#[wasm_bindgen(js_class = Chip8)]
+impl WebChip8 {
+    /// The timers tick by `performance.now()`, so they keep time even when
+    /// the browser calls `run_frame` late or throttles the tab.
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> WebChip8 {
+        let mut chip8 = Chip8::new();
+        chip8.set_clock(|| Duration::from_secs_f64(performance_now() / 1000.0));
+        WebChip8 { chip8 }
+    }
+
+    pub fn load_rom_bytes(&mut self, rom: &[u8]) {
+        self.chip8.set_variant(Variant::detect(rom));
+        self.chip8.load_rom_bytes(rom);
+    }
+
+    /// Runs one 60Hz frame worth of cycles and returns whether the display changed.
+    /// Throws if an instruction faults.
+    pub fn run_frame(&mut self) -> Result<bool, JsError> {
+        let frame = self
+            .chip8
+            .run_frame()
+            .map_err(|e| JsError::new(&e.to_string()))?;
+        Ok(frame.drawn)
+    }
+
+    pub fn key_down(&mut self, key: u8) {
+        self.chip8.key_down(key);
+    }
+
+    pub fn key_up(&mut self, key: u8) {
+        self.chip8.key_up(key);
+    }
+
+    pub fn set_keys(&mut self, mask: u16) {
+        self.chip8.set_keys(mask);
+    }
+
+    pub fn keys(&self) -> u16 {
+        self.chip8.keys()
+    }
+
+    pub fn display(&self) -> Vec<u8> {
+        self.chip8.display()
+    }
+
+    pub fn sound_active(&self) -> bool {
+        self.chip8.sound_timer() > 0
+    }
+
+    pub fn display_width(&self) -> usize {
+        self.chip8.display_size().0
+    }
+
+    pub fn display_height(&self) -> usize {
+        self.chip8.display_size().1
+    }
+}
+
+impl Default for WebChip8 {
+    fn default() -> WebChip8 {
+        WebChip8::new()
+    }
+}
@@ -0,0 +1,60 @@
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::time::{Duration, Instant};
+
+// Assemblers may write a ROM in several steps, so a change is only reported
+// once the file has been quiet for this long.
+const SETTLE_TIME: Duration = Duration::from_millis(100);
+
+// Watches a ROM file for changes. The directory is watched rather than the
+// file itself, since many tools replace the file instead of rewriting it.
+pub struct RomWatcher {
+    _watcher: RecommendedWatcher,
+    events: Receiver<notify::Result<notify::Event>>,
+    path: PathBuf,
+    changed_at: Option<Instant>,
+}
+
+impl RomWatcher {
+    pub fn new(rom: &Path) -> Result<RomWatcher, String> {
+        let path = fs::canonicalize(rom).map_err(|e| e.to_string())?;
+        let dir = path.parent().unwrap_or(Path::new("/"));
+        let (sender, events) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(sender).map_err(|e| e.to_string())?;
+        watcher
+            .watch(dir, RecursiveMode::NonRecursive)
+            .map_err(|e| e.to_string())?;
+
+        Ok(RomWatcher {
+            _watcher: watcher,
+            events,
+            path,
+            changed_at: None,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    // Returns true once the ROM has changed and settled since the last time
+    // it returned true.
+    pub fn poll(&mut self) -> bool {
+        for event in self.events.try_iter().flatten() {
+            let touches_rom = event.paths.contains(&self.path);
+            if touches_rom && matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+                self.changed_at = Some(Instant::now());
+            }
+        }
+
+        match self.changed_at {
+            Some(at) if at.elapsed() >= SETTLE_TIME => {
+                self.changed_at = None;
+                true
+            }
+            _ => false,
+        }
+    }
+}
@@ -0,0 +1,46 @@
+// Endless loops that each stress one part of the interpreter. They are shared
+// by the criterion benches and the `bench` subcommand.
+pub const WORKLOADS: [(&str, &[u8]); 3] = [
+    ("draw", &DRAW),
+    ("arithmetic", &ARITHMETIC),
+    ("bcd-dump", &BCD_DUMP),
+];
+
+// Draws a tall sprite across the screen, moving it every iteration.
+const DRAW: [u8; 29] = [
+    0xa2, 0x0e, // 200: LD I, 0x20e
+    0x60, 0x00, // 202: LD V0, 0
+    0x61, 0x00, // 204: LD V1, 0
+    0xd0, 0x1f, // 206: DRW V0, V1, 15
+    0x70, 0x03, // 208: ADD V0, 3
+    0x71, 0x01, // 20a: ADD V1, 1
+    0x12, 0x06, // 20c: JP 0x206
+    0xff, 0x81, 0xbd, 0xa5, 0xa5, 0xbd, 0x81, 0xff, // 20e: sprite
+    0x81, 0xbd, 0xa5, 0xa5, 0xbd, 0x81, 0xff,
+];
+
+// Cycles through the 8XYN ALU instructions.
+const ARITHMETIC: [u8; 22] = [
+    0x60, 0x01, // 200: LD V0, 1
+    0x61, 0x03, // 202: LD V1, 3
+    0x80, 0x14, // 204: ADD V0, V1
+    0x82, 0x05, // 206: SUB V2, V0
+    0x83, 0x02, // 208: AND V3, V0
+    0x84, 0x03, // 20a: XOR V4, V0
+    0x85, 0x01, // 20c: OR V5, V0
+    0x80, 0x06, // 20e: SHR V0
+    0x81, 0x0e, // 210: SHL V1
+    0x71, 0x07, // 212: ADD V1, 7
+    0x12, 0x04, // 214: JP 0x204
+];
+
+// Converts a counter to BCD and dumps and reloads every register.
+const BCD_DUMP: [u8; 14] = [
+    0xa3, 0x00, // 200: LD I, 0x300
+    0x60, 0x00, // 202: LD V0, 0
+    0xf0, 0x33, // 204: LD B, V0
+    0xff, 0x55, // 206: LD [I], VF
+    0xff, 0x65, // 208: LD VF, [I]
+    0x70, 0x01, // 20a: ADD V0, 1
+    0x12, 0x04, // 20c: JP 0x204
+];
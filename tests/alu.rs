@@ -0,0 +1,83 @@
+use my_chip8::{Chip8, CpuState};
+use proptest::prelude::*;
+
+// Runs a single 8XYN instruction with VX = a and VY = b, using V1 and V2.
+fn alu(n: u8, a: u8, b: u8) -> CpuState {
+    alu_with(1, 2, n, a, b)
+}
+
+fn alu_with(x: u8, y: u8, n: u8, a: u8, b: u8) -> CpuState {
+    let mut chip8 = Chip8::with_seed(0);
+    chip8.load_rom_bytes(&[0x80 | x, (y << 4) | n]);
+    chip8.set_register(x, a);
+    chip8.set_register(y, b);
+    chip8.run_cycle().unwrap();
+    chip8.state()
+}
+
+proptest! {
+    #[test]
+    fn or_and_xor(a: u8, b: u8) {
+        prop_assert_eq!(alu(0x1, a, b).v[1], a | b);
+        prop_assert_eq!(alu(0x2, a, b).v[1], a & b);
+        prop_assert_eq!(alu(0x3, a, b).v[1], a ^ b);
+    }
+
+    #[test]
+    fn xor_is_self_inverse(a: u8, b: u8) {
+        let once = alu(0x3, a, b);
+        let twice = alu(0x3, once.v[1], b);
+        prop_assert_eq!(twice.v[1], a);
+    }
+
+    #[test]
+    fn add_sets_carry(a: u8, b: u8) {
+        let state = alu(0x4, a, b);
+        let sum = a as u16 + b as u16;
+        prop_assert_eq!(state.v[1], sum as u8);
+        prop_assert_eq!(state.v[0xf], (sum > 0xff) as u8);
+    }
+
+    #[test]
+    fn sub_sets_not_borrow(a: u8, b: u8) {
+        let state = alu(0x5, a, b);
+        prop_assert_eq!(state.v[1], a.wrapping_sub(b));
+        prop_assert_eq!(state.v[0xf], (a >= b) as u8);
+    }
+
+    #[test]
+    fn reverse_sub_sets_not_borrow(a: u8, b: u8) {
+        let state = alu(0x7, a, b);
+        prop_assert_eq!(state.v[1], b.wrapping_sub(a));
+        prop_assert_eq!(state.v[0xf], (b >= a) as u8);
+    }
+
+    #[test]
+    fn sub_and_reverse_sub_are_negations(a: u8, b: u8) {
+        let sub = alu(0x5, a, b);
+        let rsb = alu(0x7, a, b);
+        prop_assert_eq!(sub.v[1], rsb.v[1].wrapping_neg());
+        prop_assert_eq!(sub.v[0xf] & rsb.v[0xf], (a == b) as u8);
+    }
+
+    // Shifts work on VX in place and ignore VY.
+    #[test]
+    fn shifts_set_shifted_out_bit(a: u8, b: u8) {
+        let right = alu(0x6, a, b);
+        prop_assert_eq!(right.v[1], a >> 1);
+        prop_assert_eq!(right.v[0xf], a & 1);
+        prop_assert_eq!(right.v[2], b);
+
+        let left = alu(0xe, a, b);
+        prop_assert_eq!(left.v[1], a << 1);
+        prop_assert_eq!(left.v[0xf], a >> 7);
+        prop_assert_eq!(left.v[2], b);
+    }
+
+    // With VF as the destination, the flag overwrites the result.
+    #[test]
+    fn flag_is_written_last(n in prop::sample::select(vec![0x4u8, 0x5, 0x6, 0x7, 0xe]), a: u8, b: u8) {
+        let expected = alu(n, a, b).v[0xf];
+        prop_assert_eq!(alu_with(0xf, 2, n, a, b).v[0xf], expected);
+    }
+}
@@ -0,0 +1,71 @@
+use my_chip8::analysis::{analyze, Edge, Issue, Region};
+
+#[test]
+fn splits_code_into_blocks() {
+    let analysis = analyze(&[
+        0x22, 0x06, // 200: CALL 0x206
+        0x12, 0x02, // 202: JP 0x202
+        0xf0, 0x90, // 204: sprite
+        0xa2, 0x04, // 206: LD I, 0x204
+        0x30, 0x01, // 208: SE V0, 1
+        0x00, 0xee, // 20A: RET
+        0x00, 0xee, // 20C: RET
+    ]);
+    let edges: Vec<(u16, Vec<Edge>)> = analysis
+        .blocks
+        .iter()
+        .map(|block| (block.start, block.edges.clone()))
+        .collect();
+    assert_eq!(
+        edges,
+        [
+            (0x200, vec![Edge::Call(0x206), Edge::Next(0x202)]),
+            (0x202, vec![Edge::Jump(0x202)]),
+            (0x206, vec![Edge::Next(0x20a), Edge::Skip(0x20c)]),
+            (0x20a, vec![]),
+            (0x20c, vec![]),
+        ]
+    );
+    assert_eq!(
+        analysis.unreached,
+        [Region {
+            start: 0x204,
+            end: 0x206,
+            loaded: true,
+        }]
+    );
+    assert_eq!(analysis.issues, []);
+}
+
+#[test]
+fn reports_suspicious_control_flow() {
+    let analysis = analyze(&[
+        0x30, 0x00, // 200: SE V0, 0
+        0x00, 0xee, // 202: RET
+        0x13, 0x00, // 204: JP 0x300
+    ]);
+    assert_eq!(
+        analysis.issues,
+        [
+            Issue::JumpOutsideRom {
+                from: 0x204,
+                to: 0x300
+            },
+            Issue::ReturnFromMain { at: 0x202 },
+        ]
+    );
+}
+
+#[test]
+fn dot_output_dashes_calls() {
+    let dot = analyze(&[
+        0x22, 0x04, // 200: CALL 0x204
+        0x12, 0x02, // 202: JP 0x202
+        0x00, 0xee, // 204: RET
+    ])
+    .to_dot();
+    assert!(dot.starts_with("digraph rom {"));
+    assert!(dot.contains("b200 -> b204 [style=dashed];"));
+    assert!(dot.contains("b200 -> b202;"));
+    assert!(dot.contains("b202 -> b202;"));
+}
@@ -0,0 +1,62 @@
+use my_chip8::{BoundsPolicy, Chip8, ErrorKind};
+
+// Stores V0–V2 at I = 0xffe, two bytes before the end of memory.
+const STORE_AT_END: [u8; 10] = [
+    0x60, 0x11, // 200: LD V0, 0x11
+    0x61, 0x22, // 202: LD V1, 0x22
+    0x62, 0x33, // 204: LD V2, 0x33
+    0xaf, 0xfe, // 206: LD I, 0xffe
+    0xf2, 0x55, // 208: LD [I], V2
+];
+
+fn load(rom: &[u8], bounds: BoundsPolicy) -> Chip8 {
+    let mut chip8 = Chip8::with_seed(0);
+    chip8.set_bounds_policy(bounds);
+    chip8.load_rom_bytes(rom);
+    chip8
+}
+
+#[test]
+fn fails_by_default() {
+    let mut chip8 = load(&STORE_AT_END, BoundsPolicy::default());
+    assert_eq!(
+        chip8.run_cycles(5).map_err(|e| e.kind),
+        Err(ErrorKind::AddressOutOfBounds(0x1000))
+    );
+    assert_eq!(chip8.pc(), 0x208);
+    assert_eq!(chip8.memory()[0xffe..], [0, 0]);
+}
+
+#[test]
+fn wraps_accesses_through_i() {
+    let mut chip8 = load(&STORE_AT_END, BoundsPolicy::Wrap);
+    chip8.run_cycles(5).unwrap();
+    assert_eq!(chip8.memory()[0xffe..], [0x11, 0x22]);
+    assert_eq!(chip8.memory()[0], 0x33);
+}
+
+#[test]
+fn wraps_pc() {
+    let mut chip8 = load(&[0x1f, 0xfe], BoundsPolicy::Wrap); // 200: JP 0xffe
+    chip8.write_byte(0xffe, 0x71).unwrap(); // ffe: ADD V1, 1
+    chip8.write_byte(0xfff, 0x01).unwrap();
+    chip8.write_byte(0x000, 0x12).unwrap(); // 000: JP 0x200
+    chip8.write_byte(0x001, 0x00).unwrap();
+    chip8.run_cycles(2).unwrap();
+    assert_eq!((chip8.pc(), chip8.state().v[1]), (0x000, 1));
+    chip8.run_cycles(1).unwrap();
+    assert_eq!(chip8.pc(), 0x200);
+}
+
+#[test]
+fn halts_on_the_faulting_instruction() {
+    let mut chip8 = load(&STORE_AT_END, BoundsPolicy::Halt);
+    chip8.run_cycles(10).unwrap();
+    assert!(chip8.halted());
+    assert_eq!(chip8.pc(), 0x208);
+    assert_eq!(chip8.memory()[0xffe..], [0, 0]);
+
+    chip8.run_cycles(10).unwrap();
+    assert!(chip8.halted());
+    assert_eq!(chip8.pc(), 0x208);
+}
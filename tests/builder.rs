@@ -0,0 +1,52 @@
+use my_chip8::{BuildError, Chip8, Quirks, Variant, CHIP_8X_START_ADDR, ETI_660_START_ADDR};
+
+// 200: RND V1, FF
+const RANDOM: [u8; 2] = [0xc1, 0xff];
+
+#[test]
+fn configures_the_machine() {
+    let mut chip8 = Chip8::builder()
+        .quirks(Quirks::VIP)
+        .cycles_per_second(700)
+        .start_address(ETI_660_START_ADDR)
+        .seed(42)
+        .rom_bytes(&RANDOM)
+        .build()
+        .unwrap();
+    assert_eq!(chip8.quirks(), Quirks::VIP);
+    assert_eq!(chip8.cycles_per_frame(), 12);
+    assert_eq!(chip8.pc(), 0x600);
+
+    let mut seeded = Chip8::with_seed(42);
+    seeded.load_rom_bytes(&RANDOM);
+    chip8.run_cycle().unwrap();
+    seeded.run_cycle().unwrap();
+    assert_eq!(chip8.state().v[1], seeded.state().v[1]);
+}
+
+#[test]
+fn starts_where_the_variant_does() {
+    let chip8 = Chip8::builder()
+        .variant(Variant::Chip8X)
+        .rom_bytes(&RANDOM)
+        .build()
+        .unwrap();
+    assert_eq!(chip8.pc(), CHIP_8X_START_ADDR);
+    assert_eq!(chip8.memory()[0x300..0x302], RANDOM);
+}
+
+#[test]
+fn rejects_roms_that_dont_fit() {
+    let rom = vec![0; 0xa01];
+    let result = Chip8::builder()
+        .start_address(ETI_660_START_ADDR)
+        .rom_bytes(&rom)
+        .build();
+    assert_eq!(
+        result.err(),
+        Some(BuildError::RomTooLarge {
+            size: 0xa01,
+            max: 0xa00
+        })
+    );
+}
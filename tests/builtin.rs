@@ -0,0 +1,30 @@
+mod snapshot;
+
+use my_chip8::{builtin, Chip8, Quirks};
+use snapshot::assert_snapshot;
+
+fn run(rom: &[u8], quirks: Quirks) -> Chip8 {
+    let mut chip8 = Chip8::with_seed(0);
+    chip8.set_quirks(quirks);
+    chip8.load_rom_bytes(rom);
+    chip8.run_cycles(2000).unwrap();
+    chip8
+}
+
+#[test]
+fn logo() {
+    assert_snapshot("builtin_logo", &run(builtin::LOGO, Quirks::default()));
+}
+
+#[test]
+fn opcode_test_passes_under_every_profile() {
+    for quirks in [Quirks::default(), Quirks::VIP, Quirks::SCHIP] {
+        assert_snapshot("builtin_test", &run(builtin::OPCODE_TEST, quirks));
+    }
+}
+
+#[test]
+fn looks_up_roms_by_name() {
+    assert_eq!(builtin::get("test"), Some(builtin::OPCODE_TEST));
+    assert_eq!(builtin::get("pong"), None);
+}
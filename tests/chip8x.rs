@@ -0,0 +1,145 @@
+use my_chip8::{Chip8, Variant, BACKGROUND_COLORS, CHIP_8X_START_ADDR, FOREGROUND_COLORS};
+
+fn run(rom: &[u8], cycles: u32) -> Chip8 {
+    let mut chip8 = Chip8::with_seed(0);
+    chip8.set_variant(Variant::Chip8X);
+    chip8.set_start_address(CHIP_8X_START_ADDR);
+    chip8.load_rom_bytes(rom);
+    chip8.run_cycles(cycles).unwrap();
+    chip8
+}
+
+#[test]
+fn plain_chip8_has_no_colors() {
+    let chip8 = Chip8::with_seed(0);
+    assert_eq!(chip8.variant(), Variant::Chip8);
+    assert!(chip8.color_map().is_none());
+}
+
+#[test]
+fn steps_the_background_color() {
+    let rom = [
+        0x02, 0xa0, // 300: 02A0
+        0x02, 0xa0, // 302: 02A0
+    ];
+    let colors = *run(&rom, 0).color_map().unwrap();
+    assert_eq!(colors.background(), BACKGROUND_COLORS[0]);
+    let chip8 = run(&rom, 2);
+    assert_eq!(
+        chip8.color_map().unwrap().background(),
+        BACKGROUND_COLORS[2]
+    );
+}
+
+#[test]
+fn adds_nibbles_separately() {
+    let chip8 = run(
+        &[
+            0x60, 0x35, // 300: LD V0, 0x35
+            0x61, 0x56, // 302: LD V1, 0x56
+            0x50, 0x11, // 304: 5011
+        ],
+        3,
+    );
+    assert_eq!(chip8.state().v[0], 0x03);
+}
+
+#[test]
+fn only_chip8x_adds_nibbles() {
+    let mut chip8 = Chip8::with_seed(0);
+    chip8.load_rom_bytes(&[
+        0x50, 0x11, // 200: SE V0, V1
+    ]);
+    chip8.run_cycles(1).unwrap();
+    assert_eq!(chip8.pc(), 0x204);
+}
+
+#[test]
+fn colors_zones() {
+    let chip8 = run(
+        &[
+            0x60, 0x11, // 300: LD V0, 0x11     columns 1-2
+            0x61, 0x02, // 302: LD V1, 0x02     row 2
+            0x62, 0x05, // 304: LD V2, 5        yellow
+            0xb0, 0x20, // 306: BXY0
+        ],
+        4,
+    );
+    let colors = chip8.color_map().unwrap();
+    for (x, y) in [(8, 8), (23, 11)] {
+        assert_eq!(colors.foreground(x, y), FOREGROUND_COLORS[5]);
+    }
+    for (x, y) in [(7, 8), (24, 8), (8, 7), (8, 12)] {
+        assert_eq!(colors.foreground(x, y), FOREGROUND_COLORS[1]);
+    }
+}
+
+#[test]
+fn colors_rows() {
+    let chip8 = run(
+        &[
+            0x60, 0x10, // 300: LD V0, 16
+            0x61, 0x05, // 302: LD V1, 5
+            0x62, 0x07, // 304: LD V2, 7        white
+            0xb0, 0x22, // 306: BXY2
+        ],
+        4,
+    );
+    let colors = chip8.color_map().unwrap();
+    assert_eq!(colors.foreground(20, 5), FOREGROUND_COLORS[7]);
+    assert_eq!(colors.foreground(16, 6), FOREGROUND_COLORS[7]);
+    assert_eq!(colors.foreground(16, 7), FOREGROUND_COLORS[1]);
+    assert_eq!(colors.foreground(24, 5), FOREGROUND_COLORS[1]);
+}
+
+#[test]
+fn reads_the_second_keypad() {
+    let rom = [
+        0x60, 0x03, // 300: LD V0, 3
+        0xe0, 0xf2, // 302: EXF2
+    ];
+    let mut chip8 = Chip8::with_seed(0);
+    chip8.set_variant(Variant::Chip8X);
+    chip8.set_start_address(CHIP_8X_START_ADDR);
+    chip8.load_rom_bytes(&rom);
+    chip8.key_down(3);
+    chip8.run_cycles(2).unwrap();
+    assert_eq!(chip8.pc(), 0x304);
+
+    chip8.set_start_address(CHIP_8X_START_ADDR);
+    chip8.set_second_keys(1 << 3);
+    chip8.run_cycles(2).unwrap();
+    assert_eq!(chip8.pc(), 0x306);
+}
+
+#[test]
+fn uses_the_io_port() {
+    let rom = [
+        0x60, 0x42, // 300: LD V0, 0x42
+        0xf0, 0xf8, // 302: FXF8
+        0xf1, 0xfb, // 304: FXFB
+    ];
+    let mut chip8 = Chip8::with_seed(0);
+    chip8.set_variant(Variant::Chip8X);
+    chip8.set_start_address(CHIP_8X_START_ADDR);
+    chip8.load_rom_bytes(&rom);
+    chip8.set_io_input(0x17);
+    chip8.run_cycles(3).unwrap();
+    assert_eq!(chip8.io_output(), 0x42);
+    assert_eq!(chip8.state().v[1], 0x17);
+}
+
+#[test]
+fn renders_in_color() {
+    let chip8 = run(
+        &[
+            0xa0, 0x50, // 300: LD I, 0x50     the font's 0
+            0xd0, 0x05, // 302: DRW V0, V0, 5
+        ],
+        2,
+    );
+    let colors = chip8.color_map().unwrap();
+    let image = colors.to_rgb(chip8.frame_buffer(), 1);
+    assert_eq!(image[..3], FOREGROUND_COLORS[1]);
+    assert_eq!(image[3 * 4..3 * 5], BACKGROUND_COLORS[0]);
+}
@@ -0,0 +1,43 @@
+use my_chip8::Chip8;
+
+fn load(rom: &[u8]) -> Chip8 {
+    let mut chip8 = Chip8::with_seed(0);
+    chip8.set_coverage(true);
+    chip8.load_rom_bytes(rom);
+    chip8
+}
+
+#[test]
+fn tracks_executed_read_and_written_bytes() {
+    let mut chip8 = load(&[
+        0xa3, 0x00, // 200: LD I, 0x300
+        0xf1, 0x55, // 202: LD [I], V1
+        0xa3, 0x10, // 204: LD I, 0x310
+        0xd0, 0x02, // 206: DRW V0, V0, 2
+        0x12, 0x08, // 208: JP 0x208
+        0x00, 0xe0, // 20A: never reached
+    ]);
+    chip8.run_cycles(5).unwrap();
+    let coverage = chip8.coverage().unwrap();
+
+    assert!((0x200..0x20a).all(|addr| coverage.executed(addr)));
+    assert!(!coverage.executed(0x20a));
+    assert!(coverage.written(0x300) && coverage.written(0x301));
+    assert!(!coverage.written(0x302));
+    assert!(coverage.read(0x310) && coverage.read(0x311));
+    assert!(!coverage.read(0x300));
+
+    let text = coverage.to_text();
+    let row = text.lines().find(|line| line.starts_with("0x200")).unwrap();
+    assert_eq!(&row[7..19], "XXXXXXXXXX..");
+    assert_eq!(coverage.to_rgb().len(), 64 * 64 * 3);
+}
+
+#[test]
+fn is_off_until_enabled() {
+    let mut chip8 = Chip8::with_seed(0);
+    assert!(chip8.coverage().is_none());
+    chip8.set_coverage(true);
+    chip8.set_coverage(false);
+    assert!(chip8.coverage().is_none());
+}
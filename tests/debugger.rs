@@ -0,0 +1,107 @@
+use my_chip8::debugger::{trace_line, Condition, ConditionError, Event};
+use my_chip8::Chip8;
+
+#[test]
+fn evaluates_conditions_against_the_registers() {
+    let mut chip8 = Chip8::with_seed(0);
+    chip8.load_rom_bytes(&[
+        0x63, 0x1f, // 200: LD V3, 0x1f
+        0x22, 0x06, // 202: CALL 0x206
+        0x00, 0x00, // 204: never reached
+        0x22, 0x0a, // 206: CALL 0x20a
+        0x22, 0x0a, // 208: never reached
+        0x12, 0x0a, // 20A: JP 0x20a
+    ]);
+    chip8.run_cycles(3).unwrap();
+    let state = chip8.state();
+
+    let holds = |text| Condition::parse(text).unwrap().eval(&state);
+    assert!(holds("V3 == 0x1F && SP > 1"));
+    assert!(!holds("V3 == 0x1F && SP > 2"));
+    assert!(holds("!(pc != 522) || DT"));
+    assert!(holds("V3"));
+    assert!(!holds("v0"));
+}
+
+#[test]
+fn rejects_malformed_conditions() {
+    assert_eq!(
+        Condition::parse("V3 =="),
+        Err(ConditionError::UnexpectedEnd)
+    );
+    assert_eq!(
+        Condition::parse("V3 = 1"),
+        Err(ConditionError::Unexpected(3))
+    );
+    assert_eq!(
+        Condition::parse("VG > 1"),
+        Err(ConditionError::Unexpected(0))
+    );
+    assert_eq!(
+        Condition::parse("(V1 > 1"),
+        Err(ConditionError::UnexpectedEnd)
+    );
+    assert_eq!(
+        Condition::parse("V1 V2"),
+        Err(ConditionError::Unexpected(3))
+    );
+}
+
+#[test]
+fn event_breakpoints_stop_anywhere() {
+    let mut chip8 = Chip8::with_seed(0);
+    chip8.load_rom_bytes(&[
+        0x60, 0x05, // 200: LD V0, 5
+        0xd0, 0x05, // 202: DRW V0, V0, 5
+        0xf0, 0x33, // 204: LD B, V0
+        0xe0, 0x9e, // 206: SKP V0
+        0x12, 0x00, // 208: JP 0x200
+    ]);
+    chip8.add_event_breakpoint(Event::Key);
+    chip8.add_event_breakpoint(Event::Draw);
+
+    assert_eq!(chip8.run_cycles(100).unwrap().breakpoint, Some(0x202));
+    assert_eq!(chip8.run_cycles(100).unwrap().breakpoint, Some(0x206));
+    chip8.remove_event_breakpoint(Event::Draw);
+    assert_eq!(chip8.run_cycles(100).unwrap().breakpoint, Some(0x206));
+    assert_eq!(Event::of(0xf218), Some(Event::Sound));
+    assert_eq!(Event::of(0xf215), None);
+}
+
+#[test]
+fn steps_back_through_history() {
+    let mut chip8 = Chip8::with_seed(0);
+    chip8.load_rom_bytes(&[
+        0x61, 0x07, // 200: LD V1, 7
+        0x22, 0x06, // 202: CALL 0x206
+        0x00, 0x00, // 204: never reached
+        0xa3, 0x00, // 206: LD I, 0x300
+        0xf1, 0x33, // 208: LD B, V1
+    ]);
+    chip8.set_history_depth(3);
+    chip8.run_cycles(4).unwrap();
+    assert_eq!(chip8.history_len(), 3);
+    assert_eq!(chip8.memory()[0x302], 7);
+
+    assert!(chip8.step_back());
+    assert_eq!(chip8.pc(), 0x208);
+    assert_eq!(chip8.memory()[0x302], 0);
+    assert!(chip8.step_back());
+    assert!(chip8.step_back());
+    assert_eq!((chip8.pc(), chip8.state().sp), (0x202, 0));
+    assert_eq!(chip8.state().v[1], 7);
+    assert!(!chip8.step_back());
+}
+
+#[test]
+fn trace_lines_show_the_state_before_each_instruction() {
+    let mut chip8 = Chip8::with_seed(0);
+    chip8.load_rom_bytes(&[
+        0x6a, 0x2f, // 200: LD VA, 0x2f
+        0xa3, 0x00, // 202: LD I, 0x300
+    ]);
+    chip8.run_cycle().unwrap();
+    let line = trace_line(&chip8.state(), chip8.current_opcode());
+    assert!(line.starts_with("PC:0202 OP:A300 I:0000 SP:00 DT:00 ST:00 V0:00"));
+    assert!(line.ends_with("VA:2F VB:00 VC:00 VD:00 VE:00 VF:00"));
+}
@@ -0,0 +1,49 @@
+use my_chip8::decompile::decompile;
+
+#[test]
+fn labels_code_and_writes_sprites_in_binary() {
+    let rom = [
+        0x22, 0x06, // 200: CALL 0x206
+        0x12, 0x02, // 202: JP 0x202
+        0x00, 0x00, // 204: data
+        0xa2, 0x0e, // 206: LD I, 0x20E
+        0xd0, 0x12, // 208: DRW V0, V1, 2
+        0x30, 0x01, // 20A: SE V0, 1
+        0x00, 0xee, // 20C: RET
+        0xf0, 0x90, // 20E: sprite
+    ];
+    assert_eq!(
+        decompile(&rom),
+        "\
+: main
+\tsub_206
+
+: label_202
+\tjump label_202
+\t0x00 0x00
+
+: sub_206
+\ti := sprite_20e
+\tsprite v0 v1 2
+\tif v0 != 0x01 then
+\treturn
+
+: sprite_20e
+\t0b11110000
+\t0b10010000
+"
+    );
+}
+
+#[test]
+fn writes_unreached_bytes_as_data() {
+    let rom = [
+        0x12, 0x00, // 200: JP 0x200
+        0x8a, 0xbc, // 202: unknown
+        0xa2, 0x00, // 204: never reached
+    ];
+    assert_eq!(
+        decompile(&rom),
+        ": main\n\tjump main\n\t0x8A 0xBC 0xA2 0x00\n"
+    );
+}
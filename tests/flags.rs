@@ -0,0 +1,55 @@
+use my_chip8::{Chip8, ErrorKind};
+#[cfg(feature = "std")]
+use std::fs;
+
+// Saves V0–V2 to the flag registers, clears them, and loads them back.
+const ROM: [u8; 16] = [
+    0x60, 0x11, // 200: LD V0, 0x11
+    0x61, 0x22, // 202: LD V1, 0x22
+    0x62, 0x33, // 204: LD V2, 0x33
+    0xf2, 0x75, // 206: LD R, V2
+    0x60, 0x00, // 208: LD V0, 0
+    0x61, 0x00, // 20a: LD V1, 0
+    0xf1, 0x85, // 20c: LD V1, R
+    0x12, 0x0e, // 20e: JP 0x20e
+];
+
+fn run(chip8: &mut Chip8) {
+    chip8.load_rom_bytes(&ROM);
+    chip8.run_cycles(7).unwrap();
+}
+
+#[test]
+fn saves_and_restores_registers() {
+    let mut chip8 = Chip8::with_seed(0);
+    run(&mut chip8);
+    assert_eq!(&chip8.state().v[..3], &[0x11, 0x22, 0x33]);
+    assert_eq!(chip8.flags(), [0x11, 0x22, 0x33, 0, 0, 0, 0, 0]);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn persists_flags_in_a_file() {
+    let path = std::env::temp_dir().join(format!("my-chip8-flags-{}", std::process::id()));
+    let _ = fs::remove_file(&path);
+
+    let mut chip8 = Chip8::with_seed(0);
+    chip8.set_flag_file(&path).unwrap();
+    run(&mut chip8);
+    assert_eq!(fs::read(&path).unwrap(), [0x11, 0x22, 0x33, 0, 0, 0, 0, 0]);
+
+    let mut next = Chip8::with_seed(0);
+    next.set_flag_file(&path).unwrap();
+    assert_eq!(next.flags(), [0x11, 0x22, 0x33, 0, 0, 0, 0, 0]);
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn only_has_eight_flags() {
+    let mut chip8 = Chip8::with_seed(0);
+    chip8.load_rom_bytes(&[0xf8, 0x75]);
+    assert_eq!(
+        chip8.run_cycle().map_err(|e| e.kind),
+        Err(ErrorKind::UnknownOpcode(0xf875))
+    );
+}
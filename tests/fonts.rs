@@ -0,0 +1,62 @@
+use my_chip8::{Chip8, FontSet, LARGE_FONT_SIZE, SMALL_FONT_SIZE};
+
+fn run(rom: &[u8], font: FontSet, cycles: u32) -> Chip8 {
+    let mut chip8 = Chip8::with_seed(0);
+    chip8.set_font(font);
+    chip8.load_rom_bytes(rom);
+    chip8.run_cycles(cycles).unwrap();
+    chip8
+}
+
+#[test]
+fn loads_the_schip_font_by_default() {
+    let chip8 = Chip8::with_seed(0);
+    let large = &chip8.memory()[0x50 + SMALL_FONT_SIZE..][..LARGE_FONT_SIZE];
+    assert_eq!(
+        chip8.memory()[0x50..0x50 + SMALL_FONT_SIZE],
+        FontSet::SCHIP.small
+    );
+    assert_eq!(Some(large), FontSet::SCHIP.large.as_ref().map(|f| &f[..]));
+}
+
+#[test]
+fn replaces_the_font() {
+    let chip8 = run(&[], FontSet::DREAM_6800, 0);
+    assert_eq!(
+        chip8.memory()[0x50..0x50 + SMALL_FONT_SIZE],
+        FontSet::DREAM_6800.small
+    );
+    assert!(chip8.memory()[0xa0..0xa0 + LARGE_FONT_SIZE]
+        .iter()
+        .all(|&b| b == 0));
+}
+
+#[test]
+fn draws_small_digits_from_the_chosen_font() {
+    let rom = [
+        0x60, 0x07, // 200: LD V0, 7
+        0xf0, 0x29, // 202: LD F, V0
+        0xd1, 0x15, // 204: DRW V1, V1, 5
+    ];
+    let chip8 = run(&rom, FontSet::VIP, 3);
+    assert_eq!(chip8.state().i, 0x50 + 7 * 5);
+    // The VIP's 7 has a straight stem on the right.
+    assert!((0..5).all(|y| chip8.pixel(3, y)));
+}
+
+#[test]
+fn points_fx30_at_the_large_digits() {
+    let rom = [
+        0x60, 0x02, // 200: LD V0, 2
+        0xf0, 0x30, // 202: LD HF, V0
+        0xd1, 0x1a, // 204: DRW V1, V1, 10
+    ];
+    let chip8 = run(&rom, FontSet::SCHIP, 3);
+    assert_eq!(chip8.state().i, 0xa0 + 2 * 10);
+    let two = FontSet::SCHIP.large.unwrap();
+    for (y, &row) in two[20..30].iter().enumerate() {
+        for x in 0..8 {
+            assert_eq!(chip8.pixel(x, y), row & (0x80 >> x) != 0);
+        }
+    }
+}
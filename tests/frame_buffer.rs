@@ -0,0 +1,121 @@
+use my_chip8::{Chip8, FrameBuffer, DISPLAY_HEIGHT, DISPLAY_WIDTH};
+
+fn draw_zero() -> Chip8 {
+    let mut chip8 = Chip8::with_seed(0);
+    chip8.load_rom_bytes(&[
+        0xa0, 0x50, // 200: LD I, 0x50     the font's 0
+        0xd0, 0x05, // 202: DRW V0, V0, 5
+    ]);
+    chip8.run_cycles(2).unwrap();
+    chip8
+}
+
+#[test]
+fn starts_at_the_standard_size() {
+    let chip8 = Chip8::with_seed(0);
+    assert_eq!(chip8.display_size(), (DISPLAY_WIDTH, DISPLAY_HEIGHT));
+    assert_eq!(chip8.frame_buffer().size(), chip8.display_size());
+    assert_eq!(*chip8.frame_buffer(), FrameBuffer::default());
+}
+
+#[test]
+fn matches_the_byte_display() {
+    let chip8 = draw_zero();
+    let frame_buffer = chip8.frame_buffer();
+    let (width, height) = frame_buffer.size();
+    assert_eq!(frame_buffer.row_bits().len(), height);
+
+    let display = chip8.display();
+    assert_eq!(display.len(), width * height);
+    for y in 0..height {
+        for x in 0..width {
+            assert_eq!(frame_buffer.pixel(x, y), display[y * width + x] == 1);
+        }
+    }
+    assert_eq!(frame_buffer.to_bytes(), display);
+    assert!(frame_buffer.pixel(0, 0) && !frame_buffer.pixel(1, 1));
+}
+
+#[test]
+fn iterates_by_position() {
+    let chip8 = draw_zero();
+    let frame_buffer = chip8.frame_buffer();
+    let (width, height) = frame_buffer.size();
+
+    let pixels: Vec<(usize, usize, bool)> = frame_buffer.iter_pixels().collect();
+    assert_eq!(pixels.len(), width * height);
+    for (n, &(x, y, lit)) in pixels.iter().enumerate() {
+        assert_eq!((x, y), (n % width, n / width));
+        assert_eq!(lit, frame_buffer.get(x, y));
+    }
+
+    let rows: Vec<Vec<bool>> = frame_buffer.rows().map(Iterator::collect).collect();
+    assert_eq!(rows.len(), height);
+    assert!(rows.iter().all(|row| row.len() == width));
+    // The top of the 0 is its four-pixel bar.
+    assert_eq!(rows[0][..5], [true, true, true, true, false]);
+
+    assert!(frame_buffer.get(0, 0));
+    assert!(!frame_buffer.get(width, 0) && !frame_buffer.get(0, height));
+}
+
+#[test]
+fn scales_to_rgb() {
+    let chip8 = draw_zero();
+    let (width, height) = chip8.display_size();
+    let image = chip8.frame_buffer().to_rgb([0xff; 3], [0x00; 3], 2);
+    assert_eq!(image.len(), width * 2 * height * 2 * 3);
+    // The top-left pixel is lit, so the first two image pixels on both of
+    // its rows are white.
+    let row = width * 2 * 3;
+    assert_eq!(image[..6], [0xff; 6]);
+    assert_eq!(image[row..row + 6], [0xff; 6]);
+    assert_eq!(image, chip8.display_rgb([0xff; 3], [0x00; 3], 2));
+}
+
+#[test]
+fn writes_rgba_texels() {
+    let chip8 = draw_zero();
+    let frame_buffer = chip8.frame_buffer();
+    let (foreground, background) = ([1, 2, 3, 0xff], [4, 5, 6, 0xff]);
+    let mut texels = vec![0; frame_buffer.width() * frame_buffer.height() * 4];
+    frame_buffer.write_rgba(foreground, background, &mut texels);
+
+    let expected: Vec<u8> = frame_buffer
+        .to_bytes()
+        .iter()
+        .flat_map(|&pixel| if pixel == 1 { foreground } else { background })
+        .collect();
+    assert_eq!(texels, expected);
+}
+
+#[test]
+fn diffs_against_an_earlier_frame() {
+    let mut chip8 = Chip8::with_seed(0);
+    let blank = *chip8.frame_buffer();
+    chip8.load_rom_bytes(&[
+        0xa0, 0x50, // 200: LD I, 0x50     the font's 0
+        0xd0, 0x05, // 202: DRW V0, V0, 5
+        0xd0, 0x05, // 204: DRW V0, V0, 5  erases it
+    ]);
+    chip8.run_cycles(2).unwrap();
+    let zero = *chip8.frame_buffer();
+    assert_eq!(zero.diff(&zero).count(), 0);
+
+    // Every lit pixel of the 0 is new, in row-major order.
+    let lit: Vec<(usize, usize, bool)> = zero.diff(&blank).collect();
+    let expected: Vec<(usize, usize, bool)> = (0..zero.height())
+        .flat_map(|y| (0..zero.width()).map(move |x| (x, y)))
+        .filter(|&(x, y)| zero.pixel(x, y))
+        .map(|(x, y)| (x, y, true))
+        .collect();
+    assert_eq!(lit, expected);
+
+    chip8.run_cycles(1).unwrap();
+    let erased: Vec<(usize, usize, bool)> = chip8.frame_buffer().diff(&zero).collect();
+    let expected: Vec<(usize, usize, bool)> = expected
+        .into_iter()
+        .map(|(x, y, _)| (x, y, false))
+        .collect();
+    assert_eq!(erased, expected);
+}
@@ -0,0 +1,98 @@
+use my_chip8::{Chip8, Variant, DISPLAY_WIDTH, MEM_SIZE};
+
+// Enters hi-res mode, clears the screen, and draws the font's 0 near the
+// bottom of the 64-row display.
+const HIRES_ROM: [u8; 8] = [
+    0x12, 0x60, // 200: JP 0x260     enter hi-res
+    0x00, 0x00, // 202
+    0x00, 0x00, // 204
+    0x00, 0x00, // 206
+];
+const PROGRAM: [u8; 10] = [
+    0x02, 0x30, // 2c0: CLS
+    0x61, 0x3a, // 2c2: LD V1, 58
+    0xa0, 0x50, // 2c4: LD I, 0x50
+    0xd0, 0x15, // 2c6: DRW V0, V1, 5
+    0x12, 0xc8, // 2c8: JP 0x2c8
+];
+
+fn load(variant: Variant) -> Chip8 {
+    let mut rom = vec![0; 0xc0 + PROGRAM.len()];
+    rom[..HIRES_ROM.len()].copy_from_slice(&HIRES_ROM);
+    rom[0xc0..].copy_from_slice(&PROGRAM);
+    let mut chip8 = Chip8::with_seed(0);
+    chip8.set_variant(variant);
+    chip8.load_rom_bytes(&rom);
+    chip8
+}
+
+#[test]
+fn detects_hires_roms() {
+    assert_eq!(Variant::detect(&HIRES_ROM), Variant::HiRes);
+    assert_eq!(Variant::detect(&[0x00, 0xe0]), Variant::Chip8);
+    assert_eq!(Variant::detect(&[]), Variant::Chip8);
+}
+
+#[test]
+fn has_a_64x64_display() {
+    let chip8 = load(Variant::HiRes);
+    assert_eq!(chip8.display_size(), (DISPLAY_WIDTH, 64));
+    let mut chip8 = load(Variant::Chip8);
+    assert_eq!(chip8.display_size(), (DISPLAY_WIDTH, 32));
+    chip8.set_variant(Variant::HiRes);
+    assert_eq!(chip8.display_size(), (DISPLAY_WIDTH, 64));
+}
+
+#[test]
+fn starts_the_program_at_0x2c0() {
+    let mut chip8 = load(Variant::HiRes);
+    chip8.run_cycles(1).unwrap();
+    assert_eq!(chip8.pc(), 0x2c0);
+    chip8.run_cycles(4).unwrap();
+    assert!(chip8.pixel(0, 58) && chip8.pixel(3, 62));
+    assert_eq!(chip8.state().v[0xf], 0);
+}
+
+#[test]
+fn clears_with_0230() {
+    let mut chip8 = load(Variant::HiRes);
+    chip8.run_cycles(6).unwrap();
+    // Loop back to the clear with JP 0x2c0.
+    chip8.write_byte(0x2c8, 0x12).unwrap();
+    chip8.write_byte(0x2c9, 0xc0).unwrap();
+    chip8.run_cycles(2).unwrap();
+    assert_eq!(chip8.pc(), 0x2c2);
+    assert!(chip8.display().iter().all(|&pixel| pixel == 0));
+}
+
+#[test]
+fn saves_the_whole_display() {
+    let mut chip8 = load(Variant::HiRes);
+    chip8.run_cycles(5).unwrap();
+    let state = chip8.save_state();
+
+    let mut copy = Chip8::with_seed(0);
+    copy.load_state(&state).unwrap();
+    assert_eq!(copy.variant(), Variant::HiRes);
+    assert_eq!(copy.frame_buffer(), chip8.frame_buffer());
+}
+
+#[test]
+fn loads_version_1_states() {
+    let mut chip8 = load(Variant::Chip8);
+    chip8.run_cycles(3).unwrap();
+    let state = chip8.save_state();
+    // Version 1 had no variant byte and 32 display rows instead of 64.
+    let display = 6 + MEM_SIZE;
+    let mut old = state[..5].to_vec();
+    old[4] = 1;
+    old.extend_from_slice(&state[6..display + 32 * 8]);
+    old.extend_from_slice(&state[display + 64 * 8..]);
+
+    let mut copy = Chip8::with_seed(0);
+    copy.set_variant(Variant::HiRes);
+    copy.load_state(&old).unwrap();
+    assert_eq!(copy.variant(), Variant::Chip8);
+    assert_eq!(copy.state(), chip8.state());
+    assert_eq!(copy.frame_buffer(), chip8.frame_buffer());
+}
@@ -0,0 +1,97 @@
+#![cfg(feature = "jit")]
+
+use my_chip8::{Chip8, Jit, Quirks};
+use proptest::prelude::*;
+
+// Register instructions the JIT compiles, interleaved with ones it hands back
+// to the interpreter.
+fn opcode() -> impl Strategy<Value = u16> {
+    prop_oneof![
+        (0u16..16, any::<u8>()).prop_map(|(x, nn)| 0x6000 | (x << 8) | nn as u16),
+        (0u16..16, any::<u8>()).prop_map(|(x, nn)| 0x7000 | (x << 8) | nn as u16),
+        (
+            0u16..16,
+            0u16..16,
+            prop::sample::select(vec![0, 1, 2, 3, 4, 5, 6, 7, 0xe])
+        )
+            .prop_map(|(x, y, n)| 0x8000 | (x << 8) | (y << 4) | n),
+        (0u16..0x1000).prop_map(|nnn| 0xa000 | nnn),
+        (0u16..16).prop_map(|x| 0xf01e | (x << 8)),
+        (0u16..16, any::<u8>()).prop_map(|(x, nn)| 0x3000 | (x << 8) | nn as u16),
+        (0u16..16).prop_map(|x| 0xf015 | (x << 8)),
+        (0u16..16).prop_map(|x| 0xf007 | (x << 8)),
+    ]
+}
+
+fn load(opcodes: &[u16]) -> Chip8 {
+    let mut rom: Vec<u8> = opcodes.iter().flat_map(|op| op.to_be_bytes()).collect();
+    rom.extend([0x12, 0x00]); // JP 0x200
+    let mut chip8 = Chip8::with_seed(0);
+    chip8.load_rom_bytes(&rom);
+    chip8
+}
+
+fn quirks() -> impl Strategy<Value = Quirks> {
+    (any::<bool>(), any::<bool>()).prop_map(|(shift_vy, logic_resets_vf)| Quirks {
+        shift_vy,
+        logic_resets_vf,
+        ..Quirks::default()
+    })
+}
+
+proptest! {
+    #[test]
+    fn matches_interpreter(
+        opcodes in prop::collection::vec(opcode(), 1..40),
+        cycles in 1u32..2000,
+        quirks in quirks(),
+    ) {
+        let mut interpreted = load(&opcodes);
+        interpreted.set_quirks(quirks);
+        // A skip over the final jump runs off the end of memory, which both
+        // should report at the same point.
+        let interpreted_result = (0..cycles).try_for_each(|_| interpreted.run_cycle());
+
+        let mut compiled = load(&opcodes);
+        compiled.set_quirks(quirks);
+        let mut jit = Jit::new().unwrap();
+        // Uneven batches exercise blocks that don't fit the remaining cycles.
+        let mut remaining = cycles;
+        let mut compiled_result = Ok(());
+        while remaining > 0 && compiled_result.is_ok() {
+            let batch = remaining.min(7);
+            compiled_result = jit.run(&mut compiled, batch).map(|_| ());
+            remaining -= batch;
+        }
+
+        prop_assert_eq!(compiled_result, interpreted_result);
+        prop_assert_eq!(compiled.state(), interpreted.state());
+    }
+}
+
+#[test]
+fn recompiles_overwritten_code() {
+    let mut chip8 = load(&[0x7101]); // ADD V1, 1
+    let mut jit = Jit::new().unwrap();
+    jit.run(&mut chip8, 20).unwrap();
+    assert_eq!(chip8.state().v[1], 10);
+
+    chip8.write_byte(0x201, 0x10).unwrap(); // ADD V1, 0x10
+    jit.run(&mut chip8, 20).unwrap();
+    assert_eq!(chip8.state().v[1], 10 + 10 * 0x10);
+}
+
+#[test]
+fn recompiles_when_quirks_change() {
+    let mut chip8 = load(&[0x6107, 0x8016]); // LD V1, 7; SHR V0, V1
+    let mut jit = Jit::new().unwrap();
+    jit.run(&mut chip8, 3).unwrap();
+    assert_eq!(chip8.state().v[0], 0);
+
+    chip8.set_quirks(Quirks {
+        shift_vy: true,
+        ..Quirks::default()
+    });
+    jit.run(&mut chip8, 2).unwrap();
+    assert_eq!(chip8.state().v[0], 3);
+}
@@ -0,0 +1,234 @@
+use my_chip8::{Chip8, Quirks, DISPLAY_HEIGHT, DISPLAY_WIDTH};
+
+fn run(rom: &[u8], quirks: Quirks, cycles: u32) -> Chip8 {
+    let mut chip8 = Chip8::with_seed(0);
+    chip8.set_quirks(quirks);
+    chip8.load_rom_bytes(rom);
+    chip8.run_cycles(cycles).unwrap();
+    chip8
+}
+
+const SHIFTS: [u8; 10] = [
+    0x60, 0x81, // 200: LD V0, 0x81
+    0x61, 0x06, // 202: LD V1, 0x06
+    0x80, 0x16, // 204: SHR V0, V1
+    0x62, 0x81, // 206: LD V2, 0x81
+    0x82, 0x1e, // 208: SHL V2, V1
+];
+
+#[test]
+fn shifts_vx_in_place_by_default() {
+    let chip8 = run(&SHIFTS, Quirks::default(), 3);
+    assert_eq!(chip8.state().v[0], 0x40);
+    assert_eq!(chip8.state().v[0xf], 1);
+
+    let chip8 = run(&SHIFTS, Quirks::default(), 5);
+    assert_eq!(chip8.state().v[2], 0x02);
+    assert_eq!(chip8.state().v[0xf], 1);
+}
+
+#[test]
+fn shifts_vy_into_vx_with_the_shift_quirk() {
+    let quirks = Quirks {
+        shift_vy: true,
+        ..Quirks::default()
+    };
+    let chip8 = run(&SHIFTS, quirks, 3);
+    assert_eq!(chip8.state().v[0], 0x03);
+    assert_eq!(chip8.state().v[0xf], 0);
+
+    let chip8 = run(&SHIFTS, quirks, 5);
+    assert_eq!(chip8.state().v[2], 0x0c);
+    assert_eq!(chip8.state().v[0xf], 0);
+    assert_eq!(chip8.state().v[1], 0x06);
+}
+
+const LOAD_STORE: [u8; 10] = [
+    0xa3, 0x00, // 200: LD I, 0x300
+    0x60, 0x11, // 202: LD V0, 0x11
+    0x61, 0x22, // 204: LD V1, 0x22
+    0xf1, 0x55, // 206: LD [I], V1
+    0xf0, 0x65, // 208: LD V0, [I]
+];
+
+#[test]
+fn load_store_leaves_i_alone_by_default() {
+    let chip8 = run(&LOAD_STORE, Quirks::default(), 5);
+    assert_eq!(chip8.state().i, 0x300);
+    assert_eq!(chip8.state().v[0], 0x11);
+}
+
+#[test]
+fn load_store_advances_i_with_the_load_store_quirk() {
+    let quirks = Quirks {
+        load_store_increments_i: true,
+        ..Quirks::default()
+    };
+    let chip8 = run(&LOAD_STORE, quirks, 4);
+    assert_eq!(chip8.state().i, 0x302);
+    assert_eq!(chip8.memory()[0x300..0x302], [0x11, 0x22]);
+
+    // The load then reads the byte after the stored registers.
+    let chip8 = run(&LOAD_STORE, quirks, 5);
+    assert_eq!(chip8.state().i, 0x303);
+    assert_eq!(chip8.state().v[0], 0);
+}
+
+const JUMP: [u8; 6] = [
+    0x60, 0x04, // 200: LD V0, 4
+    0x63, 0x10, // 202: LD V3, 0x10
+    0xb3, 0x00, // 204: JP V0, 0x300
+];
+
+#[test]
+fn jump_adds_v0_by_default() {
+    let chip8 = run(&JUMP, Quirks::default(), 3);
+    assert_eq!(chip8.pc(), 0x304);
+}
+
+#[test]
+fn jump_adds_vx_with_the_jump_quirk() {
+    let chip8 = run(&JUMP, Quirks::SCHIP, 3);
+    assert_eq!(chip8.pc(), 0x310);
+}
+
+const LOGIC: [u8; 6] = [
+    0x6f, 0x05, // 200: LD VF, 5
+    0x60, 0x0c, // 202: LD V0, 0x0c
+    0x80, 0xf1, // 204: OR V0, VF
+];
+
+#[test]
+fn logic_keeps_vf_by_default() {
+    let chip8 = run(&LOGIC, Quirks::default(), 3);
+    assert_eq!(chip8.state().v[0], 0x0d);
+    assert_eq!(chip8.state().v[0xf], 5);
+}
+
+#[test]
+fn logic_clears_vf_with_the_vf_reset_quirk() {
+    let chip8 = run(&LOGIC, Quirks::VIP, 3);
+    assert_eq!(chip8.state().v[0], 0x0d);
+    assert_eq!(chip8.state().v[0xf], 0);
+}
+
+// Draws the font's 0 (a 4x5 box) with its top-left corner at (V0, V1).
+fn draw_box(x: u8, y: u8, quirks: Quirks) -> Chip8 {
+    run(
+        &[
+            0x60, x, // 200: LD V0, x
+            0x61, y, // 202: LD V1, y
+            0xa0, 0x50, // 204: LD I, 0x50
+            0xd0, 0x15, // 206: DRW V0, V1, 5
+        ],
+        quirks,
+        4,
+    )
+}
+
+fn lit(chip8: &Chip8) -> Vec<(usize, usize)> {
+    let mut pixels = Vec::new();
+    for y in 0..DISPLAY_HEIGHT {
+        for x in 0..DISPLAY_WIDTH {
+            if chip8.pixel(x, y) {
+                pixels.push((x, y));
+            }
+        }
+    }
+    pixels
+}
+
+#[test]
+fn clips_sprites_at_the_edges_by_default() {
+    let chip8 = draw_box(62, 30, Quirks::default());
+    assert_eq!(lit(&chip8), [(62, 30), (63, 30), (62, 31)]);
+}
+
+#[test]
+fn wraps_the_starting_position() {
+    let chip8 = draw_box(64 + 62, 32 + 30, Quirks::default());
+    assert_eq!(lit(&chip8), [(62, 30), (63, 30), (62, 31)]);
+}
+
+#[test]
+fn wraps_sprites_with_the_wrap_quirk() {
+    let quirks = Quirks {
+        wrap_sprites: true,
+        ..Quirks::default()
+    };
+    let chip8 = draw_box(62, 30, quirks);
+    assert_eq!(
+        lit(&chip8),
+        [
+            (1, 0),
+            (62, 0),
+            (1, 1),
+            (62, 1),
+            (0, 2),
+            (1, 2),
+            (62, 2),
+            (63, 2),
+            (0, 30),
+            (1, 30),
+            (62, 30),
+            (63, 30),
+            (1, 31),
+            (62, 31),
+        ]
+    );
+    assert_eq!(chip8.state().v[0xf], 0);
+}
+
+const DRAW_TWICE: [u8; 6] = [
+    0xa0, 0x50, // 200: LD I, 0x50
+    0xd0, 0x15, // 202: DRW V0, V1, 5
+    0xd0, 0x15, // 204: DRW V0, V1, 5
+];
+
+fn display_wait(cycles: u32) -> Chip8 {
+    let quirks = Quirks {
+        display_wait: true,
+        ..Quirks::default()
+    };
+    let mut chip8 = Chip8::with_seed(0);
+    chip8.set_quirks(quirks);
+    chip8.set_cycles_per_frame(10);
+    chip8.load_rom_bytes(&DRAW_TWICE);
+    chip8.run_cycles(cycles).unwrap();
+    chip8
+}
+
+#[test]
+fn draws_immediately_by_default() {
+    let chip8 = run(&DRAW_TWICE, Quirks::default(), 3);
+    assert_eq!(chip8.state().pc, 0x206);
+    assert!(!chip8.pixel(0, 0));
+}
+
+#[test]
+fn waits_for_the_next_frame_with_the_display_wait_quirk() {
+    let chip8 = display_wait(10);
+    assert_eq!(chip8.state().pc, 0x202);
+    assert!(!chip8.pixel(0, 0));
+
+    let chip8 = display_wait(11);
+    assert_eq!(chip8.state().pc, 0x204);
+    assert!(chip8.pixel(0, 0));
+
+    // Only one sprite is drawn per frame.
+    let chip8 = display_wait(20);
+    assert_eq!(chip8.state().pc, 0x204);
+    let chip8 = display_wait(21);
+    assert_eq!(chip8.state().pc, 0x206);
+    assert!(!chip8.pixel(0, 0));
+}
+
+#[test]
+fn waits_for_the_next_frame_in_run_frame() {
+    let mut chip8 = Chip8::with_seed(0);
+    chip8.set_quirks(Quirks::VIP);
+    chip8.load_rom_bytes(&DRAW_TWICE);
+    assert!(!chip8.run_frame().unwrap().drawn);
+    assert!(chip8.run_frame().unwrap().drawn);
+    assert_eq!(chip8.state().pc, 0x204);
+}
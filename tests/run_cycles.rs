@@ -0,0 +1,76 @@
+use my_chip8::debugger::Condition;
+use my_chip8::{BatchResult, Chip8, ErrorKind};
+
+fn load(rom: &[u8]) -> Chip8 {
+    let mut chip8 = Chip8::with_seed(0);
+    chip8.load_rom_bytes(rom);
+    chip8
+}
+
+#[test]
+fn reports_draws_from_anywhere_in_the_batch() {
+    let mut chip8 = load(&[
+        0xd0, 0x05, // 200: DRW V0, V0, 5
+        0x71, 0x01, // 202: ADD V1, 1
+        0x12, 0x02, // 204: JP 0x202
+    ]);
+    let batch = chip8.run_cycles(10).unwrap();
+    assert_eq!(
+        batch,
+        BatchResult {
+            cycles: 10,
+            drawn: true,
+            breakpoint: None,
+        }
+    );
+    assert!(!chip8.run_cycles(10).unwrap().drawn);
+}
+
+#[test]
+fn stops_at_breakpoints_and_resumes() {
+    let mut chip8 = load(&[
+        0x71, 0x01, // 200: ADD V1, 1
+        0x72, 0x01, // 202: ADD V2, 1
+        0x12, 0x00, // 204: JP 0x200
+    ]);
+    chip8.add_breakpoint(0x202);
+
+    let batch = chip8.run_cycles(100).unwrap();
+    assert_eq!((batch.cycles, batch.breakpoint), (1, Some(0x202)));
+    assert_eq!(chip8.pc(), 0x202);
+
+    let batch = chip8.run_cycles(100).unwrap();
+    assert_eq!((batch.cycles, batch.breakpoint), (3, Some(0x202)));
+    assert_eq!(chip8.state().v[1..3], [2, 1]);
+
+    chip8.remove_breakpoint(0x202);
+    assert_eq!(chip8.run_cycles(100).unwrap().cycles, 100);
+}
+
+#[test]
+fn conditional_breakpoints_stop_only_when_they_hold() {
+    let mut chip8 = load(&[
+        0x71, 0x01, // 200: ADD V1, 1
+        0x12, 0x00, // 202: JP 0x200
+    ]);
+    chip8.add_conditional_breakpoint(0x202, Condition::parse("V1 == 3 || v1 >= 0x0a").unwrap());
+
+    let batch = chip8.run_cycles(100).unwrap();
+    assert_eq!((batch.cycles, batch.breakpoint), (5, Some(0x202)));
+    let batch = chip8.run_cycles(100).unwrap();
+    assert_eq!((batch.cycles, batch.breakpoint), (14, Some(0x202)));
+    assert_eq!(chip8.state().v[1], 10);
+}
+
+#[test]
+fn stops_at_errors() {
+    let mut chip8 = load(&[
+        0x71, 0x01, // 200: ADD V1, 1
+        0xff, 0xff, // 202: unknown
+    ]);
+    assert_eq!(
+        chip8.run_cycles(10).map_err(|e| e.kind),
+        Err(ErrorKind::UnknownOpcode(0xffff))
+    );
+    assert_eq!((chip8.pc(), chip8.state().v[1]), (0x202, 1));
+}
@@ -0,0 +1,59 @@
+use my_chip8::{Chip8, CYCLES_PER_FRAME};
+
+// Sets both timers to 3 and then spins.
+fn load() -> Chip8 {
+    let mut chip8 = Chip8::with_seed(0);
+    chip8.load_rom_bytes(&[
+        0x60, 0x03, // 200: LD V0, 3
+        0xf0, 0x15, // 202: LD DT, V0
+        0xf0, 0x18, // 204: LD ST, V0
+        0x12, 0x06, // 206: JP 0x206
+    ]);
+    chip8
+}
+
+#[test]
+fn ticks_the_timers_once_per_frame() {
+    let mut chip8 = load();
+    let frame = chip8.run_frame().unwrap();
+    assert!(frame.sound);
+    assert!(!frame.drawn);
+    assert_eq!(chip8.state().delay_timer, 2);
+
+    assert!(chip8.run_frame().unwrap().sound);
+    assert!(!chip8.run_frame().unwrap().sound);
+    assert_eq!(chip8.state().delay_timer, 0);
+}
+
+#[test]
+fn run_cycle_ticks_at_the_same_rate() {
+    let mut chip8 = load();
+    for _ in 0..CYCLES_PER_FRAME {
+        chip8.run_cycle().unwrap();
+    }
+    assert_eq!(chip8.state().delay_timer, 2);
+
+    chip8.set_cycles_per_frame(2);
+    chip8.run_cycle().unwrap();
+    assert_eq!(chip8.state().delay_timer, 2);
+    chip8.run_cycle().unwrap();
+    assert_eq!(chip8.state().delay_timer, 1);
+}
+
+#[test]
+fn reports_the_display() {
+    let mut chip8 = Chip8::with_seed(0);
+    chip8.load_rom_bytes(&[
+        0xa0, 0x50, // 200: LD I, 0x50     the font's 0
+        0xd0, 0x05, // 202: DRW V0, V0, 5
+        0x12, 0x04, // 204: JP 0x204
+    ]);
+    let frame = chip8.run_frame().unwrap();
+    assert!(frame.drawn);
+    let rows: Vec<u64> = frame.frame_buffer.row_bits()[..5]
+        .iter()
+        .map(|row| row >> 56)
+        .collect();
+    assert_eq!(rows, [0xf0, 0x90, 0x90, 0x90, 0xf0]);
+    assert_eq!(frame.display()[..4], [1, 1, 1, 1]);
+}
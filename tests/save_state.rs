@@ -0,0 +1,112 @@
+use my_chip8::{Chip8, StateError, STATE_SIZE};
+
+// Counts up in V0 and draws the font's 0, storing V0 into memory each loop.
+fn load() -> Chip8 {
+    let mut chip8 = Chip8::with_seed(0);
+    chip8.load_rom_bytes(&[
+        0xa0, 0x50, // 200: LD I, 0x50
+        0xd1, 0x15, // 202: DRW V1, V1, 5
+        0x70, 0x01, // 204: ADD V0, 1
+        0xa3, 0x00, // 206: LD I, 0x300
+        0xf0, 0x55, // 208: LD [I], V0
+        0xf0, 0x15, // 20a: LD DT, V0
+        0x12, 0x04, // 20c: JP 0x204
+    ]);
+    chip8
+}
+
+#[test]
+fn restores_a_saved_machine() {
+    let mut chip8 = load();
+    chip8.run_cycles(25).unwrap();
+    let state = chip8.save_state();
+    assert_eq!(state.len(), STATE_SIZE);
+    let saved = (chip8.state(), chip8.display(), chip8.memory().to_vec());
+
+    chip8.run_cycles(40).unwrap();
+    assert_ne!(chip8.state(), saved.0);
+
+    chip8.load_state(&state).unwrap();
+    assert_eq!(
+        (chip8.state(), chip8.display(), chip8.memory().to_vec()),
+        saved
+    );
+}
+
+#[test]
+fn loaded_machine_runs_like_the_original() {
+    let mut original = load();
+    original.run_cycles(33).unwrap();
+
+    let mut copy = Chip8::with_seed(1);
+    copy.load_state(&original.save_state()).unwrap();
+    for _ in 0..100 {
+        original.run_cycle().unwrap();
+        copy.run_cycle().unwrap();
+    }
+    assert_eq!(copy.state(), original.state());
+    assert_eq!(copy.memory(), original.memory());
+}
+
+#[test]
+fn rejects_bad_states() {
+    let mut chip8 = load();
+    let state = chip8.save_state();
+    let before = chip8.state();
+
+    assert_eq!(chip8.load_state(b"not a state"), Err(StateError::Invalid));
+    assert_eq!(
+        chip8.load_state(&state[..state.len() - 1]),
+        Err(StateError::Invalid)
+    );
+
+    let mut newer = state.clone();
+    newer[4] = 99;
+    assert_eq!(
+        chip8.load_state(&newer),
+        Err(StateError::UnsupportedVersion(99))
+    );
+    assert_eq!(chip8.state(), before);
+}
+
+#[test]
+fn clones_run_like_the_original() {
+    let mut chip8 = load();
+    chip8.run_cycles(25).unwrap();
+    let mut clone = chip8.clone();
+    assert_eq!(clone, chip8);
+
+    chip8.run_cycles(40).unwrap();
+    assert_ne!(clone, chip8);
+    clone.run_cycles(40).unwrap();
+    assert_eq!(clone, chip8);
+}
+
+#[test]
+fn debugs_the_registers() {
+    let mut chip8 = load();
+    chip8.run_cycles(3).unwrap();
+    let debug = format!("{chip8:?}");
+    assert!(debug.starts_with("Chip8 { pc: 0x206, opcode: A300, i: 0x050, v: [01, "));
+    assert!(debug.ends_with(", .. }"));
+}
+
+#[test]
+fn clones_draw_the_same_random_numbers() {
+    let mut next = 0u8;
+    let mut chip8 = Chip8::with_rng(move || {
+        next = next.wrapping_add(37);
+        next
+    });
+    chip8.load_rom_bytes(&[
+        0xc0, 0xff, // 200: RND V0, FF
+        0xc1, 0xff, // 202: RND V1, FF
+    ]);
+    chip8.run_cycle().unwrap();
+    let mut clone = chip8.clone();
+
+    chip8.run_cycle().unwrap();
+    clone.run_cycle().unwrap();
+    assert_eq!(chip8.state().v[1], 74);
+    assert_eq!(clone, chip8);
+}
@@ -0,0 +1,48 @@
+use my_chip8::Chip8;
+
+// Both ROMs run an instruction, overwrite it, and jump back to run it again,
+// so a stale decode of the old instruction gives a different result.
+fn run(rom: &[u8]) -> Chip8 {
+    let mut chip8 = Chip8::with_seed(0);
+    chip8.load_rom_bytes(rom);
+    for _ in 0..100 {
+        chip8.run_cycle().unwrap();
+    }
+    chip8
+}
+
+#[test]
+fn reg_dump_over_executed_code() {
+    let chip8 = run(&[
+        0x62, 0x00, // 200: LD V2, 0
+        0x72, 0x01, // 202: ADD V2, 1      becomes ADD V2, 0x10
+        0x32, 0x01, // 204: SE V2, 1
+        0x12, 0x12, // 206: JP 0x212
+        0x60, 0x72, // 208: LD V0, 0x72
+        0x61, 0x10, // 20a: LD V1, 0x10
+        0xa2, 0x02, // 20c: LD I, 0x202
+        0xf1, 0x55, // 20e: LD [I], V1
+        0x12, 0x02, // 210: JP 0x202
+        0x12, 0x12, // 212: JP 0x212
+    ]);
+    assert_eq!(chip8.state().v[2], 0x11);
+}
+
+// The BCD digits land on the second byte of one instruction and all of the
+// next, so the write has to drop the decode that starts before it too.
+#[test]
+fn bcd_over_executed_code() {
+    let chip8 = run(&[
+        0x63, 0x00, // 200: LD V3, 0       becomes LD V3, 2
+        0x73, 0x01, // 202: ADD V3, 1      becomes SYS 0x505
+        0x74, 0x01, // 204: ADD V4, 1
+        0x34, 0x02, // 206: SE V4, 2
+        0x12, 0x0c, // 208: JP 0x20c
+        0x12, 0x0a, // 20a: JP 0x20a
+        0x60, 0xff, // 20c: LD V0, 255
+        0xa2, 0x01, // 20e: LD I, 0x201
+        0xf0, 0x33, // 210: LD B, V0
+        0x12, 0x00, // 212: JP 0x200
+    ]);
+    assert_eq!(chip8.state().v[3], 2);
+}
@@ -0,0 +1,45 @@
+#![cfg(feature = "std")]
+
+use my_chip8::{Chip8, SharedChip8};
+use std::thread;
+use std::time::Duration;
+
+fn assert_send<T: Send>() {}
+fn assert_send_sync<T: Send + Sync>() {}
+
+#[test]
+fn is_thread_safe() {
+    assert_send::<Chip8>();
+    assert_send_sync::<SharedChip8>();
+}
+
+#[test]
+fn threads_share_one_machine() {
+    let mut chip8 = Chip8::with_seed(0);
+    chip8.load_rom_bytes(&[
+        0xf0, 0x0a, // 200: LD V0, K
+        0x12, 0x02, // 202: JP 0x202
+    ]);
+    let shared = SharedChip8::new(chip8);
+
+    let emulation = {
+        let shared = shared.clone();
+        thread::spawn(move || {
+            while shared.state().pc != 0x202 {
+                shared.run_frame().unwrap();
+                thread::sleep(Duration::from_millis(1));
+            }
+        })
+    };
+    // FX0A only sees presses released while it waits, so keep tapping.
+    while !emulation.is_finished() {
+        shared.key_down(0x7);
+        thread::sleep(Duration::from_millis(1));
+        shared.key_up(0x7);
+    }
+    emulation.join().unwrap();
+
+    assert_eq!(shared.state().v[0], 0x7);
+    let chip8 = shared.try_unwrap().unwrap();
+    assert_eq!(chip8.pc(), 0x202);
+}
@@ -0,0 +1,71 @@
+use my_chip8::Chip8;
+use std::env;
+use std::fs::{self, File};
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+const SCALE: usize = 8;
+
+// Compares the screen with tests/snapshots/<name>.txt, one line per row with
+// '#' for lit pixels. Running the tests with BLESS=1 writes the current screen
+// as the new snapshot instead. On a mismatch the actual screen is also saved
+// as target/snapshots/<name>.png for a closer look.
+pub fn assert_snapshot(name: &str, chip8: &Chip8) {
+    let actual = render_text(chip8);
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/snapshots")
+        .join(format!("{name}.txt"));
+
+    if env::var_os("BLESS").is_some_and(|bless| bless != "0") {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, &actual).unwrap();
+        return;
+    }
+
+    let expected = match fs::read_to_string(&path) {
+        Ok(expected) => expected,
+        Err(e) => panic!(
+            "failed to read {}: {e}\nrun with BLESS=1 to create it\n\nactual:\n{actual}",
+            path.display()
+        ),
+    };
+    if actual != expected {
+        let png = save_png(name, chip8);
+        panic!(
+            "screen differs from {}\nrun with BLESS=1 to accept it, actual screen saved to {}\n\nexpected:\n{expected}\nactual:\n{actual}",
+            path.display(),
+            png.display()
+        );
+    }
+}
+
+fn render_text(chip8: &Chip8) -> String {
+    let (width, height) = chip8.display_size();
+    let mut text = String::with_capacity((width + 1) * height);
+    for row in chip8.display().chunks(width) {
+        text.extend(row.iter().map(|&pixel| if pixel == 1 { '#' } else { '.' }));
+        text.push('\n');
+    }
+    text
+}
+
+fn save_png(name: &str, chip8: &Chip8) -> PathBuf {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("target/snapshots");
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(format!("{name}.png"));
+
+    let image = chip8.display_rgb([0xff; 3], [0x00; 3], SCALE);
+    let (width, height) = chip8.display_size();
+    let file = File::create(&path).unwrap();
+    let mut encoder = png::Encoder::new(
+        BufWriter::new(file),
+        (width * SCALE) as u32,
+        (height * SCALE) as u32,
+    );
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().unwrap();
+    writer.write_image_data(&image).unwrap();
+
+    path
+}
@@ -0,0 +1,51 @@
+use my_chip8::{Chip8, ErrorKind};
+
+fn load(rom: &[u8]) -> Chip8 {
+    let mut chip8 = Chip8::with_seed(0);
+    chip8.load_rom_bytes(rom);
+    chip8
+}
+
+#[test]
+fn reports_overflow_at_the_call() {
+    let mut chip8 = load(&[
+        0x00, 0xe0, // 200: CLS
+        0x22, 0x02, // 202: CALL 0x202
+    ]);
+    assert_eq!(
+        chip8.run_cycles(100).map_err(|e| e.kind),
+        Err(ErrorKind::StackOverflow(0x202))
+    );
+    let state = chip8.state();
+    assert_eq!((state.pc, state.sp), (0x202, 16));
+    assert_eq!(state.stack, [0x204; 16]);
+}
+
+#[test]
+fn reports_underflow_at_the_return() {
+    let mut chip8 = load(&[
+        0x22, 0x04, // 200: CALL 0x204
+        0x00, 0xee, // 202: RET
+        0x00, 0xee, // 204: RET
+    ]);
+    assert_eq!(
+        chip8.run_cycles(100).map_err(|e| e.kind),
+        Err(ErrorKind::StackUnderflow(0x202))
+    );
+    let state = chip8.state();
+    assert_eq!((state.pc, state.sp), (0x202, 0));
+}
+
+#[test]
+fn describes_the_failing_instruction() {
+    let mut chip8 = load(&[
+        0xa3, 0x00, // 200: LD I, 0x300
+        0x00, 0xee, // 202: RET
+    ]);
+    let e = chip8.run_cycles(100).unwrap_err();
+    assert_eq!((e.pc, e.opcode, e.i), (0x202, 0x00ee, 0x300));
+    assert_eq!(
+        e.to_string(),
+        "return with an empty stack at PC 0x202 (opcode 00EE, I 0x300)"
+    );
+}
@@ -0,0 +1,24 @@
+use my_chip8::{Chip8, ETI_660_START_ADDR, START_ADDR};
+
+#[test]
+fn starts_at_0x200_by_default() {
+    let mut chip8 = Chip8::with_seed(0);
+    chip8.load_rom_bytes(&[0x61, 0x07]); // LD V1, 7
+    assert_eq!(chip8.start_address(), START_ADDR);
+    assert_eq!(chip8.max_rom_size(), 0xe00);
+    chip8.run_cycle().unwrap();
+    assert_eq!(chip8.state().v[1], 7);
+}
+
+#[test]
+fn loads_and_starts_eti_660_roms_at_0x600() {
+    let mut chip8 = Chip8::with_seed(0);
+    chip8.set_start_address(ETI_660_START_ADDR);
+    chip8.load_rom_bytes(&[0x61, 0x07]); // LD V1, 7
+    assert_eq!(chip8.memory()[0x600..0x602], [0x61, 0x07]);
+    assert_eq!(chip8.memory()[0x200..0x202], [0, 0]);
+    assert_eq!(chip8.max_rom_size(), 0xa00);
+
+    chip8.run_cycle().unwrap();
+    assert_eq!((chip8.pc(), chip8.state().v[1]), (0x602, 7));
+}
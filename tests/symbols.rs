@@ -0,0 +1,32 @@
+use my_chip8::disasm::disassemble_with;
+use my_chip8::Symbols;
+
+#[test]
+fn parses_either_order_and_comments() {
+    let symbols = Symbols::parse(
+        "# generated by the assembler\n\
+         0x2a4 draw_player\n\
+         main = 200 ; entry point\n\
+         \n\
+         add 0x300\n",
+    )
+    .unwrap();
+    assert_eq!(symbols.name(0x2a4), Some("draw_player"));
+    assert_eq!(symbols.address("main"), Some(0x200));
+    assert_eq!(symbols.address("add"), Some(0x300));
+    assert_eq!(symbols.resolve("draw_player"), Some(0x2a4));
+    assert_eq!(symbols.resolve("0x2b0"), Some(0x2b0));
+    assert_eq!(symbols.resolve("nowhere"), None);
+
+    let error = Symbols::parse("main 0x200\nmain player\n").unwrap_err();
+    assert_eq!(error.line, 2);
+}
+
+#[test]
+fn disassembly_names_addresses() {
+    let mut symbols = Symbols::new();
+    symbols.insert(0x2a4, "draw_player");
+    assert_eq!(disassemble_with(0x22a4, &symbols), "CALL draw_player");
+    assert_eq!(disassemble_with(0xa2a4, &symbols), "LD I, draw_player");
+    assert_eq!(disassemble_with(0x12a6, &symbols), "JP 0x2a6");
+}
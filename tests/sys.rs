@@ -0,0 +1,45 @@
+use my_chip8::{Chip8, ErrorKind, SysPolicy};
+use std::sync::{Arc, Mutex};
+
+const CALL_MACHINE_CODE: [u8; 4] = [
+    0x03, 0x45, // 200: SYS 0x345
+    0x71, 0x01, // 202: ADD V1, 1
+];
+
+fn load(sys: SysPolicy) -> Chip8 {
+    let mut chip8 = Chip8::with_seed(0);
+    chip8.set_sys_policy(sys);
+    chip8.load_rom_bytes(&CALL_MACHINE_CODE);
+    chip8
+}
+
+#[test]
+fn ignores_machine_code_by_default() {
+    let mut chip8 = load(SysPolicy::default());
+    chip8.run_cycles(2).unwrap();
+    assert_eq!((chip8.pc(), chip8.state().v[1]), (0x204, 1));
+}
+
+#[test]
+fn fails_on_machine_code() {
+    let mut chip8 = load(SysPolicy::Error);
+    assert_eq!(
+        chip8.run_cycles(2).map_err(|e| e.kind),
+        Err(ErrorKind::MachineCode(0x345))
+    );
+    assert_eq!(chip8.pc(), 0x200);
+}
+
+#[test]
+fn calls_the_handler() {
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    let mut chip8 = load(SysPolicy::Callback);
+    let seen = calls.clone();
+    chip8.set_sys_handler(move |chip8, addr| {
+        seen.lock().unwrap().push((chip8.pc(), addr));
+        chip8.set_register(2, 0x42);
+    });
+    chip8.run_cycles(2).unwrap();
+    assert_eq!(*calls.lock().unwrap(), [(0x202, 0x345)]);
+    assert_eq!(chip8.state().v[1..3], [1, 0x42]);
+}
@@ -0,0 +1,57 @@
+mod snapshot;
+
+use my_chip8::{Chip8, Quirks};
+use snapshot::assert_snapshot;
+
+// Timendus' CHIP-8 test suite bundles the IBM logo, the corax89 opcode test
+// and the flags and quirks tests. Writing a test number to 0x1ff skips its
+// menu, and 0x1fe picks the platform the quirks test checks against.
+const TEST_SUITE: &[u8] = include_bytes!("roms/chip8-test-suite.ch8");
+const TEST_SELECT: u16 = 0x1ff;
+const PLATFORM_SELECT: u16 = 0x1fe;
+
+// Every test has drawn its results and is idling well before this.
+const CYCLES: usize = 5000;
+
+fn run_suite(test: u8, platform: u8) -> Chip8 {
+    run_suite_with(test, platform, Quirks::default())
+}
+
+fn run_suite_with(test: u8, platform: u8, quirks: Quirks) -> Chip8 {
+    let mut chip8 = Chip8::with_seed(0);
+    chip8.set_quirks(quirks);
+    chip8.load_rom_bytes(TEST_SUITE);
+    chip8.write_byte(TEST_SELECT, test).unwrap();
+    chip8.write_byte(PLATFORM_SELECT, platform).unwrap();
+    for _ in 0..CYCLES {
+        chip8.run_cycle().unwrap();
+    }
+    chip8
+}
+
+#[test]
+fn ibm_logo() {
+    assert_snapshot("ibm_logo", &run_suite(1, 0));
+}
+
+#[test]
+fn corax_opcodes() {
+    assert_snapshot("corax_opcodes", &run_suite(2, 0));
+}
+
+#[test]
+fn flags() {
+    assert_snapshot("flags", &run_suite(3, 0));
+}
+
+// Each platform's quirks test is run with the profile that platform expects,
+// so every quirk should be marked as right.
+#[test]
+fn quirks_chip8() {
+    assert_snapshot("quirks_chip8", &run_suite_with(4, 1, Quirks::VIP));
+}
+
+#[test]
+fn quirks_schip() {
+    assert_snapshot("quirks_schip", &run_suite_with(4, 2, Quirks::SCHIP));
+}
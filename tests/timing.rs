@@ -0,0 +1,131 @@
+use my_chip8::{Chip8, ManualClock, Timing, CYCLES_PER_FRAME, VIP_CYCLES_PER_FRAME};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+// Counts in V0 forever.
+const COUNT: [u8; 4] = [
+    0x70, 0x01, // 200: ADD V0, 1
+    0x12, 0x00, // 202: JP 0x200
+];
+
+fn load(timing: Timing, rom: &[u8]) -> Chip8 {
+    let mut chip8 = Chip8::with_seed(0);
+    chip8.set_timing(timing);
+    chip8.load_rom_bytes(rom);
+    chip8
+}
+
+fn machine_cycles(duration: Duration) -> u32 {
+    (duration.as_secs_f64() * 60.0 * VIP_CYCLES_PER_FRAME as f64).round() as u32
+}
+
+#[test]
+fn flat_timing_runs_a_fixed_number_of_instructions() {
+    let mut chip8 = load(Timing::Flat, &COUNT);
+    chip8.run_frame().unwrap();
+    assert_eq!(chip8.state().v[0], CYCLES_PER_FRAME as u8 / 2);
+    assert_eq!(
+        chip8.last_instruction_duration(),
+        Duration::from_secs_f64(1.0 / 600.0)
+    );
+}
+
+#[test]
+fn vip_timing_runs_a_frame_of_machine_cycles() {
+    let mut chip8 = load(Timing::Vip, &COUNT);
+    chip8.run_frame().unwrap();
+    // Each loop takes 10 + 23 machine cycles, and the frame ends on the ADD
+    // that reaches 2584.
+    assert_eq!(chip8.state().v[0], 79);
+}
+
+#[test]
+fn vip_timing_ticks_the_timers_by_machine_cycles() {
+    let mut chip8 = load(
+        Timing::Vip,
+        &[
+            0x60, 0x01, // 200: LD V0, 1
+            0xf0, 0x15, // 202: LD DT, V0
+            0x12, 0x04, // 204: JP 0x204
+        ],
+    );
+    let mut cycles = 0;
+    while chip8.state().delay_timer == 0 || cycles < 2 {
+        chip8.run_cycle().unwrap();
+        cycles += 1;
+    }
+    while chip8.state().delay_timer != 0 {
+        chip8.run_cycle().unwrap();
+        cycles += 1;
+    }
+    // 6 + 10 machine cycles, then jumps until the frame is used up.
+    assert_eq!(cycles, 2 + (VIP_CYCLES_PER_FRAME - 16).div_ceil(23));
+}
+
+#[test]
+fn unaligned_draws_cost_more() {
+    let draw_at = |x| {
+        let mut chip8 = load(
+            Timing::Vip,
+            &[
+                0x60, x, // 200: LD V0, x
+                0xa0, 0x50, // 202: LD I, 0x50
+                0xd0, 0x05, // 204: DRW V0, V0, 5
+            ],
+        );
+        chip8.run_cycles(3).unwrap();
+        machine_cycles(chip8.last_instruction_duration())
+    };
+    assert_eq!(draw_at(8), 68 + 5 * 12);
+    assert_eq!(draw_at(3), 68 + 5 * 24);
+}
+
+#[test]
+fn ticks_the_timers_by_the_clock() {
+    let clock = ManualClock::new();
+    let mut chip8 = load(
+        Timing::Flat,
+        &[
+            0x60, 0x3c, // 200: LD V0, 60
+            0xf0, 0x15, // 202: LD DT, V0
+            0x12, 0x04, // 204: JP 0x204
+        ],
+    );
+    chip8.set_clock(clock.clone());
+
+    chip8.run_cycles(100).unwrap();
+    assert_eq!(chip8.state().delay_timer, 60);
+    clock.advance(Duration::from_millis(500));
+    chip8.run_cycle().unwrap();
+    assert_eq!(chip8.state().delay_timer, 30);
+    clock.advance(Duration::from_millis(17));
+    chip8.run_frame().unwrap();
+    assert_eq!(chip8.state().delay_timer, 29);
+}
+
+#[test]
+fn tells_the_sound_hook_when_the_buzzer_starts_and_stops() {
+    let mut chip8 = load(
+        Timing::Flat,
+        &[
+            0x60, 0x02, // 200: LD V0, 2
+            0xf0, 0x18, // 202: LD ST, V0
+            0x12, 0x04, // 204: JP 0x204
+        ],
+    );
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let hook_events = Arc::clone(&events);
+    chip8.set_sound_hook(move |on| hook_events.lock().unwrap().push(on));
+
+    chip8.run_cycles(2).unwrap();
+    assert_eq!(*events.lock().unwrap(), [true]);
+    let sounding = chip8.save_state();
+    chip8.run_frame().unwrap();
+    assert_eq!(*events.lock().unwrap(), [true]);
+    chip8.run_frame().unwrap();
+    assert_eq!(*events.lock().unwrap(), [true, false]);
+
+    chip8.clear_hooks();
+    chip8.load_state(&sounding).unwrap();
+    assert_eq!(*events.lock().unwrap(), [true, false]);
+}
@@ -0,0 +1,131 @@
+import init, { Chip8 } from "../pkg/my_chip8.js";
+
+const KEYMAP = {
+  Digit1: 0x1, Digit2: 0x2, Digit3: 0x3, Digit4: 0xc,
+  KeyQ: 0x4, KeyW: 0x5, KeyE: 0x6, KeyR: 0xd,
+  KeyA: 0x7, KeyS: 0x8, KeyD: 0x9, KeyF: 0xe,
+  KeyZ: 0xa, KeyX: 0x0, KeyC: 0xb, KeyV: 0xf,
+};
+// The COSMAC VIP's hex keypad layout, row by row.
+const KEYPAD_LAYOUT = [
+  0x1, 0x2, 0x3, 0xc,
+  0x4, 0x5, 0x6, 0xd,
+  0x7, 0x8, 0x9, 0xe,
+  0xa, 0x0, 0xb, 0xf,
+];
+const FOREGROUND = [0xff, 0xff, 0xff];
+const BACKGROUND = [0x00, 0x00, 0x00];
+
+await init();
+
+const canvas = document.getElementById("screen");
+const context = canvas.getContext("2d");
+let image = null;
+
+let chip8 = null;
+let audio = null;
+let oscillator = null;
+
+function setBeep(active) {
+  if (active && !oscillator) {
+    audio ??= new AudioContext();
+    oscillator = audio.createOscillator();
+    oscillator.type = "square";
+    oscillator.frequency.value = 440;
+    const gain = audio.createGain();
+    gain.gain.value = 0.1;
+    oscillator.connect(gain).connect(audio.destination);
+    oscillator.start();
+  } else if (!active && oscillator) {
+    oscillator.stop();
+    oscillator = null;
+  }
+}
+
+function draw() {
+  const width = chip8.display_width();
+  const height = chip8.display_height();
+  if (image?.width !== width || image?.height !== height) {
+    canvas.width = width;
+    canvas.height = height;
+    image = context.createImageData(width, height);
+  }
+  const display = chip8.display();
+  for (let i = 0; i < display.length; i++) {
+    const color = display[i] ? FOREGROUND : BACKGROUND;
+    image.data.set([...color, 0xff], i * 4);
+  }
+  context.putImageData(image, 0, 0);
+}
+
+function frame() {
+  if (chip8) {
+    try {
+      if (chip8.run_frame()) {
+        draw();
+      }
+      setBeep(chip8.sound_active());
+    } catch (error) {
+      console.error(`Emulation error: ${error.message}`);
+      setBeep(false);
+      chip8.free();
+      chip8 = null;
+    }
+  }
+  requestAnimationFrame(frame);
+}
+
+document.getElementById("rom").addEventListener("change", async (event) => {
+  const file = event.target.files[0];
+  if (!file) {
+    return;
+  }
+  chip8?.free();
+  chip8 = new Chip8();
+  chip8.load_rom_bytes(new Uint8Array(await file.arrayBuffer()));
+});
+
+document.addEventListener("keydown", (event) => {
+  if (chip8 && event.code in KEYMAP) {
+    chip8.key_down(KEYMAP[event.code]);
+  }
+});
+
+document.addEventListener("keyup", (event) => {
+  if (chip8 && event.code in KEYMAP) {
+    chip8.key_up(KEYMAP[event.code]);
+  }
+});
+
+// Pointer events cover mouse clicks and touches alike. Each button holds its
+// key until the pointer is lifted or leaves it.
+const keypad = document.getElementById("keypad");
+for (const key of KEYPAD_LAYOUT) {
+  const button = document.createElement("button");
+  button.textContent = key.toString(16).toUpperCase();
+  const release = () => {
+    if (button.classList.contains("pressed")) {
+      button.classList.remove("pressed");
+      chip8?.key_up(key);
+    }
+  };
+  button.addEventListener("pointerdown", (event) => {
+    event.preventDefault();
+    button.classList.add("pressed");
+    chip8?.key_down(key);
+  });
+  button.addEventListener("pointerup", release);
+  button.addEventListener("pointerleave", release);
+  button.addEventListener("pointercancel", release);
+  keypad.append(button);
+}
+
+// Phones and tablets get the keypad by default.
+const showKeypad = document.getElementById("show-keypad");
+showKeypad.checked = matchMedia("(pointer: coarse)").matches;
+keypad.hidden = !showKeypad.checked;
+showKeypad.addEventListener("change", () => {
+  keypad.hidden = !showKeypad.checked;
+});
+
+requestAnimationFrame(frame);